@@ -31,7 +31,7 @@ fn main() {
     println!("1. Registering primitives...");
 
     app::register(42i32);
-    app::register(3.14f64);
+    app::register(std::f64::consts::PI);
     app::register(true);
 
     println!("   Registered: i32(42), f64(3.14), bool(true)");
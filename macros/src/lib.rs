@@ -0,0 +1,227 @@
+//! Attribute macros for [`singleton-registry`](https://docs.rs/singleton-registry).
+//!
+//! Use them through the `macros` feature of `singleton-registry`, which
+//! re-exports them. Like the registry's default build, this crate has no
+//! dependencies: the few tokens it needs are parsed by hand.
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+/// Give a struct an OO-style singleton facade backed by a registry.
+///
+/// `#[singleton(registry = app)]` generates two associated functions:
+///
+/// - `T::install(self)` registers the value in `app`, replacing any previous one
+/// - `T::instance() -> Arc<T>` returns the registered value, panicking at the
+///   caller when none is installed
+///
+/// `registry` names a module created with `define_registry!`, by a path as
+/// seen from the struct. Generic structs are not supported.
+#[proc_macro_attribute]
+pub fn singleton(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand(attr, &item) {
+        Ok(facade) => item.into_iter().chain(facade).collect(),
+        Err(message) => {
+            let error: TokenStream = format!("::core::compile_error!({:?});", message)
+                .parse()
+                .expect("compile_error! invocation");
+            item.into_iter().chain(error).collect()
+        }
+    }
+}
+
+fn expand(attr: TokenStream, item: &TokenStream) -> Result<TokenStream, String> {
+    let registry = registry_path(attr)?;
+    let name = struct_name(item)?;
+
+    let facade = format!(
+        r#"
+        impl {name} {{
+            /// The instance installed in the registry.
+            ///
+            /// # Panics
+            ///
+            /// If no instance is installed.
+            #[track_caller]
+            pub fn instance() -> ::std::sync::Arc<Self> {{
+                match {registry}::get::<Self>() {{
+                    ::std::result::Result::Ok(instance) => instance,
+                    ::std::result::Result::Err(err) => {{
+                        ::std::panic!("{name}::instance(): {{}}", err)
+                    }}
+                }}
+            }}
+
+            /// Install `self` in the registry, replacing any previous instance.
+            #[track_caller]
+            pub fn install(self) {{
+                {registry}::register(self)
+            }}
+        }}
+        "#
+    );
+    facade
+        .parse()
+        .map_err(|err| format!("#[singleton]: invalid registry path: {err}"))
+}
+
+/// Run a test against a fresh, private [`Registry`] instance.
+///
+/// The test function takes one parameter, which receives a new
+/// `Registry::named("<test name>")` converted with `Into`, so it can be a
+/// `Registry` or a `RegistryHandle`. No other test sees the instance, so
+/// tests using it need neither `clear()` nor `serial_test`. Works with
+/// `#[test]` and, for `async fn`, with async test attributes such as
+/// `#[tokio::test]`.
+///
+/// The expansion names `::singleton_registry`; pass
+/// `#[isolated_registry(crate = path)]` if the crate is renamed.
+///
+/// [`Registry`]: https://docs.rs/singleton-registry/latest/singleton_registry/struct.Registry.html
+#[proc_macro_attribute]
+pub fn isolated_registry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match expand_isolated(attr, item.clone()) {
+        Ok(test) => test,
+        Err(message) => {
+            let error: TokenStream = format!("::core::compile_error!({:?});", message)
+                .parse()
+                .expect("compile_error! invocation");
+            item.into_iter().chain(error).collect()
+        }
+    }
+}
+
+fn expand_isolated(attr: TokenStream, item: TokenStream) -> Result<TokenStream, String> {
+    let krate = crate_path(attr)?;
+
+    // The user's tokens are re-emitted as they are, keeping their spans, so
+    // `#[track_caller]` locations and compile errors point into the body
+    let mut tokens = item.into_iter().peekable();
+    let mut prefix = TokenStream::new();
+    let mut qualifier = None;
+    let fn_token = loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "fn" => break ident,
+            Some(token) => {
+                if matches!(&token, TokenTree::Ident(i) if i.to_string() == "async") {
+                    qualifier = Some(token.clone());
+                }
+                prefix.extend([token]);
+            }
+            None => return Err("#[isolated_registry] can only be applied to a fn".to_string()),
+        }
+    };
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(name)) => name,
+        _ => return Err("#[isolated_registry] can only be applied to a fn".to_string()),
+    };
+    let params = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => group,
+        _ => return Err("#[isolated_registry] does not support generic fns".to_string()),
+    };
+    if params.stream().is_empty() {
+        return Err("#[isolated_registry] expects a parameter receiving the registry".to_string());
+    }
+    // Everything up to the body: the return type, if any
+    let mut signature: Vec<TokenTree> = tokens.collect();
+    let body = match signature.pop() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        _ => {
+            return Err("#[isolated_registry] can only be applied to a fn with a body".to_string())
+        }
+    };
+    let output: TokenStream = signature.into_iter().collect();
+    let is_async = qualifier.is_some();
+
+    // Only the wrapper around the user's fn is generated
+    let registry: TokenStream = format!(
+        "::core::convert::Into::into({krate}::Registry::named({:?}))",
+        name.to_string()
+    )
+    .parse()
+    .map_err(|err| format!("#[isolated_registry]: invalid crate path: {err}"))?;
+
+    let mut inner = TokenStream::new();
+    inner.extend(qualifier);
+    inner.extend([
+        TokenTree::Ident(fn_token.clone()),
+        TokenTree::Ident(name.clone()),
+        TokenTree::Group(params),
+    ]);
+    inner.extend(output.clone());
+    inner.extend([
+        TokenTree::Group(body),
+        TokenTree::Ident(name.clone()),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, registry)),
+    ]);
+    if is_async {
+        inner.extend(".await".parse::<TokenStream>().expect("await tokens"));
+    }
+
+    let mut test = prefix;
+    test.extend([
+        TokenTree::Ident(fn_token),
+        TokenTree::Ident(name),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+    ]);
+    test.extend(output);
+    test.extend([TokenTree::Group(Group::new(Delimiter::Brace, inner))]);
+    Ok(test)
+}
+
+/// The crate path of an optional `crate = <path>`.
+fn crate_path(attr: TokenStream) -> Result<String, String> {
+    const USAGE: &str = "expected `#[isolated_registry]` or `#[isolated_registry(crate = <path>)]`";
+
+    let mut tokens = attr.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (None, _) => return Ok("::singleton_registry".to_string()),
+        (Some(TokenTree::Ident(key)), Some(TokenTree::Punct(eq)))
+            if key.to_string() == "crate" && eq.as_char() == '=' => {}
+        _ => return Err(USAGE.to_string()),
+    }
+    let path: TokenStream = tokens.collect();
+    if path.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(path.to_string())
+}
+
+/// The registry path of `registry = <path>`.
+fn registry_path(attr: TokenStream) -> Result<String, String> {
+    const USAGE: &str = "expected `#[singleton(registry = <registry module>)]`";
+
+    let mut tokens = attr.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(key)), Some(TokenTree::Punct(eq)))
+            if key.to_string() == "registry" && eq.as_char() == '=' => {}
+        _ => return Err(USAGE.to_string()),
+    }
+    let path: TokenStream = tokens.collect();
+    if path.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(path.to_string())
+}
+
+/// The name of the struct the attribute is applied to.
+fn struct_name(item: &TokenStream) -> Result<String, String> {
+    let mut tokens = item.clone().into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            // Outer attributes, e.g. `#[derive(..)]`
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {}
+            TokenTree::Ident(ident) if ident.to_string() == "struct" => {
+                let name = match tokens.next() {
+                    Some(TokenTree::Ident(name)) => name.to_string(),
+                    _ => break,
+                };
+                if matches!(tokens.next(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+                    return Err("#[singleton] does not support generic structs".to_string());
+                }
+                return Ok(name);
+            }
+            _ => {}
+        }
+    }
+    Err("#[singleton] can only be applied to a struct".to_string())
+}
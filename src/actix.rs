@@ -0,0 +1,195 @@
+//! actix-web integration (feature `actix-web`).
+//!
+//! Two ways to hand registry values to actix handlers:
+//!
+//! - [`FromRegistry<T, R>`] implements `FromRequest`, resolving `T` from the
+//!   registry on every request (replacements are picked up immediately).
+//! - [`RegistryAppData`] copies selected entries into the app as `web::Data<T>`
+//!   at startup, so existing handlers using the stock `Data<T>` extractor work
+//!   unchanged. `Data<T>` shares the registry's `Arc`, nothing is cloned.
+
+use std::future::{ready, Ready};
+use std::marker::PhantomData;
+
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, ServiceConfig};
+use actix_web::{FromRequest, HttpRequest, ResponseError};
+
+use crate::extract::{FromRegistry, RegistryRejection};
+use crate::{RegistryApi, RegistryError};
+
+impl ResponseError for RegistryRejection {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl<T, R> FromRequest for FromRegistry<T, R>
+where
+    T: Send + Sync + 'static,
+    R: RegistryApi + Default,
+{
+    type Error = RegistryRejection;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(_req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::resolve().map_err(RegistryRejection))
+    }
+}
+
+type Copier = Box<dyn Fn(&mut ServiceConfig) -> Result<(), RegistryError>>;
+
+/// Copies selected registry entries into an actix app as `web::Data<T>`.
+///
+/// # Example
+///
+/// ```rust
+/// use actix_web::{web, App};
+/// use singleton_registry::{define_registry, RegistryAppData};
+///
+/// define_registry!(services);
+///
+/// struct Db(String);
+///
+/// services::register(Db("postgres://localhost".into()));
+///
+/// let data = RegistryAppData::<services::Api>::new().with::<Db>();
+///
+/// let app = App::new()
+///     .configure(|cfg| data.configure(cfg))
+///     .route("/", web::get().to(|db: web::Data<Db>| async move { db.0.clone() }));
+/// ```
+pub struct RegistryAppData<R> {
+    copiers: Vec<Copier>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<R: RegistryApi + Default> RegistryAppData<R> {
+    /// Start with no selected types.
+    pub fn new() -> Self {
+        Self {
+            copiers: Vec::new(),
+            _registry: PhantomData,
+        }
+    }
+
+    /// Select `T` to be copied into the app.
+    pub fn with<T: Send + Sync + 'static>(mut self) -> Self {
+        self.copiers.push(Box::new(|cfg| {
+            let value = R::default().get::<T>()?;
+            cfg.app_data(Data::from(value));
+            Ok(())
+        }));
+        self
+    }
+
+    /// Copy the selected entries into `cfg`, stopping at the first missing type.
+    ///
+    /// # Errors
+    ///
+    /// - A selected type is not registered
+    pub fn try_configure(&self, cfg: &mut ServiceConfig) -> Result<(), RegistryError> {
+        self.copiers.iter().try_for_each(|copy| copy(cfg))
+    }
+
+    /// Copy the selected entries into `cfg`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a selected type is not registered; app configuration runs at
+    /// startup, where a missing singleton is a wiring bug.
+    pub fn configure(&self, cfg: &mut ServiceConfig) {
+        if let Err(err) = self.try_configure(cfg) {
+            panic!("cannot configure actix app data from registry: {err}");
+        }
+    }
+}
+
+impl<R: RegistryApi + Default> Default for RegistryAppData<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> std::fmt::Debug for RegistryAppData<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegistryAppData")
+            .field("types", &self.copiers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    use actix_web::{web, App};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_extracts_registered_value() {
+        define_registry!(reg);
+        reg::register("db".to_string());
+
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+        let value = futures_lite::future::block_on(FromRegistry::<String, reg::Api>::from_request(
+            &req,
+            &mut payload,
+        ))
+        .unwrap();
+        assert_eq!(&*value, "db");
+    }
+
+    #[test]
+    fn test_missing_value_is_500() {
+        define_registry!(reg);
+
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+        let rejection = futures_lite::future::block_on(FromRegistry::<u8, reg::Api>::from_request(
+            &req,
+            &mut payload,
+        ))
+        .unwrap_err();
+        assert_eq!(rejection.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_app_data_shares_registry_arc() {
+        define_registry!(reg);
+        reg::register(String::from("shared"));
+
+        let data = RegistryAppData::<reg::Api>::new().with::<String>();
+        actix_web::rt::System::new().block_on(async {
+            let app = init_service(App::new().configure(|cfg| data.configure(cfg)).route(
+                "/",
+                web::get().to(|s: web::Data<String>| async move {
+                    let registered = reg::get::<String>().unwrap();
+                    Arc::ptr_eq(&s.into_inner(), &registered).to_string()
+                }),
+            ))
+            .await;
+
+            let resp = call_service(&app, TestRequest::get().uri("/").to_request()).await;
+            assert_eq!(read_body(resp).await, "true");
+        });
+    }
+
+    #[test]
+    fn test_try_configure_reports_missing_type() {
+        define_registry!(reg);
+
+        let data = RegistryAppData::<reg::Api>::new().with::<u16>();
+        let mut result = Ok(());
+        let _ = App::new().configure(|cfg| result = data.try_configure(cfg));
+        assert!(matches!(
+            result,
+            Err(RegistryError::TypeNotFound {
+                type_name: "u16",
+                ..
+            })
+        ));
+    }
+}
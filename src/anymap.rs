@@ -0,0 +1,118 @@
+//! Conversions between registries and `anymap` containers (feature `anymap`).
+//!
+//! Codebases moving from [`anymap3`] to a registry can migrate one type list at
+//! a time: `import_anymap::<(Config, Db)>(&mut map)` moves the listed values
+//! out of the map into the registry, and `export_anymap::<(Config, Db)>()`
+//! copies them back for code that still expects a map.
+
+use std::any::Any;
+
+use crate::RegistryApi;
+
+/// An `anymap3` map holding `Send + Sync` values.
+pub type AnyMap = anymap3::Map<dyn Any + Send + Sync>;
+
+/// A tuple of types moved from an [`AnyMap`] by
+/// [`import_anymap`](RegistryApi::import_anymap).
+///
+/// Implemented for tuples of up to eight `Send + Sync + 'static` types.
+pub trait AnyMapImport {
+    /// Move the values of the listed types from `map` into `api`, returning
+    /// how many were present.
+    #[track_caller]
+    fn import<R: RegistryApi + ?Sized>(api: &R, map: &mut AnyMap) -> usize;
+}
+
+/// A tuple of types copied into an [`AnyMap`] by
+/// [`export_anymap`](RegistryApi::export_anymap).
+///
+/// Implemented for tuples of up to eight `Clone + Send + Sync + 'static` types.
+pub trait AnyMapExport {
+    /// Insert clones of the listed values registered in `api` into `map`,
+    /// returning how many were registered.
+    fn export<R: RegistryApi + ?Sized>(api: &R, map: &mut AnyMap) -> usize;
+}
+
+macro_rules! impl_anymap_types {
+    ($($T:ident),+) => {
+        impl<$($T),+> AnyMapImport for ($($T,)+)
+        where
+            $($T: Send + Sync + 'static),+
+        {
+            #[track_caller]
+            fn import<R: RegistryApi + ?Sized>(api: &R, map: &mut AnyMap) -> usize {
+                let mut imported = 0;
+                $(
+                    if let Some(value) = map.remove::<$T>() {
+                        api.register(value);
+                        imported += 1;
+                    }
+                )+
+                imported
+            }
+        }
+
+        impl<$($T),+> AnyMapExport for ($($T,)+)
+        where
+            $($T: Clone + Send + Sync + 'static),+
+        {
+            fn export<R: RegistryApi + ?Sized>(api: &R, map: &mut AnyMap) -> usize {
+                let mut exported = 0;
+                $(
+                    if let Some(value) = api.try_get::<$T>() {
+                        map.insert((*value).clone());
+                        exported += 1;
+                    }
+                )+
+                exported
+            }
+        }
+    };
+}
+
+impl_anymap_types!(A);
+impl_anymap_types!(A, B);
+impl_anymap_types!(A, B, C);
+impl_anymap_types!(A, B, C, D);
+impl_anymap_types!(A, B, C, D, E);
+impl_anymap_types!(A, B, C, D, E, F);
+impl_anymap_types!(A, B, C, D, E, F, G);
+impl_anymap_types!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Config(&'static str);
+
+    #[test]
+    fn test_import_moves_listed_types() {
+        define_registry!(reg);
+
+        let mut map = AnyMap::new();
+        map.insert(Config("prod"));
+        map.insert(8080u16);
+        map.insert(1u8);
+
+        assert_eq!(reg::import_anymap::<(Config, u16, u32)>(&mut map), 2);
+        assert_eq!(*reg::get::<Config>().unwrap(), Config("prod"));
+        assert_eq!(*reg::get::<u16>().unwrap(), 8080);
+        // Unlisted types stay in the map
+        assert_eq!((map.len(), map.get::<u8>()), (1, Some(&1)));
+    }
+
+    #[test]
+    fn test_export_clones_registered_types() {
+        define_registry!(reg);
+
+        reg::register(Config("prod"));
+        let map = reg::export_anymap::<(Config, u16)>();
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get::<Config>(), Some(&Config("prod")));
+        assert!(reg::contains::<Config>().unwrap());
+    }
+}
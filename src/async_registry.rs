@@ -0,0 +1,303 @@
+//! Async registry backend (features `async` and `tokio`).
+//!
+//! This module provides the `AsyncRegistryApi` trait, the async counterpart of
+//! [`RegistryApi`](crate::RegistryApi). Storage is guarded by an async `RwLock`,
+//! so tasks waiting for the lock yield to the executor instead of blocking a
+//! worker thread, and concurrent readers never wait for each other.
+//!
+//! With the `tokio` feature the lock is `tokio::sync::RwLock`; with only the
+//! `async` feature it is the executor-agnostic `async_lock::RwLock`, which works
+//! the same under async-std, smol or any other executor.
+//!
+//! Registries are created with [`define_async_registry!`](crate::define_async_registry).
+//! Tracing reuses the synchronous callback type: callbacks are invoked after the
+//! storage lock has been released and must not block.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::sync::{AsyncRwLock, LazyLock, Mutex};
+use crate::{EventMeta, RegistryEntry, RegistryError, RegistryEvent};
+
+/// Type alias for the trace callback storage.
+///
+/// Note: This type is also defined in the `define_async_registry!` macro.
+/// Keep both definitions in sync.
+type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>>>;
+
+/// Core trait defining async registry behavior.
+///
+/// Mirrors [`RegistryApi`](crate::RegistryApi) with `await`-able storage operations.
+/// Only the `storage` and `trace` accessors have to be implemented.
+pub trait AsyncRegistryApi: Sync {
+    // -------------------------------------------------------------------------------------------------
+    // Tracing
+    // -------------------------------------------------------------------------------------------------
+
+    /// Access the trace callback static.
+    fn trace() -> &'static TraceCallback;
+
+    /// Name of the registry, reported in the `registry` field of every event.
+    ///
+    /// `define_async_registry!` returns the module name; defaults to the implementing
+    /// type's name.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Set a tracing callback for registry operations.
+    ///
+    /// The callback is invoked synchronously after each operation, with no registry
+    /// lock held. Keep it short; forward events elsewhere for heavy processing.
+    fn set_trace_callback(&self, callback: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
+        let mut guard = LazyLock::force(Self::trace())
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        *guard = Some(Arc::new(callback));
+    }
+
+    /// Clear the tracing callback.
+    fn clear_trace_callback(&self) {
+        let mut guard = LazyLock::force(Self::trace())
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        *guard = None;
+    }
+
+    /// Emit a registry event using the current callback.
+    ///
+    /// The trace lock is released before the callback is invoked.
+    fn emit_event(&self, event: &RegistryEvent) {
+        let event = &event.with_meta(EventMeta::capture(Self::name()));
+        #[cfg(feature = "tracing")]
+        crate::tracing_bridge::emit(event);
+        let callback = {
+            let guard = LazyLock::force(Self::trace())
+                .lock()
+                .unwrap_or_else(|p| p.into_inner());
+            guard.as_ref().cloned()
+        };
+        if let Some(cb) = callback {
+            cb(event);
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Registry
+    // -------------------------------------------------------------------------------------------------
+
+    /// Access the storage static.
+    fn storage() -> &'static LazyLock<AsyncRwLock<HashMap<TypeId, RegistryEntry>>>;
+
+    /// Register a value in the registry, replacing any previous value of the same type.
+    fn register<T: Send + Sync + 'static>(&self, value: T) -> impl Future<Output = ()> + Send {
+        self.register_arc(Arc::new(value))
+    }
+
+    /// Register an Arc-wrapped value in the registry.
+    fn register_arc<T: Send + Sync + 'static>(
+        &self,
+        value: Arc<T>,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            self.emit_event(&RegistryEvent::Register {
+                type_name: std::any::type_name::<T>(),
+                meta: EventMeta::default(),
+            });
+
+            let started = Instant::now();
+
+            let replaced = LazyLock::force(Self::storage())
+                .write()
+                .await
+                .insert(TypeId::of::<T>(), RegistryEntry::new(value))
+                .is_some();
+
+            self.emit_event(&RegistryEvent::RegisterCompleted {
+                type_name: std::any::type_name::<T>(),
+                replaced,
+                rollback: false,
+                meta: EventMeta::timed(started),
+            });
+        }
+    }
+
+    /// Retrieve a value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch (extremely rare)
+    fn get<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl Future<Output = Result<Arc<T>, RegistryError>> + Send {
+        async move {
+            let started = Instant::now();
+            let any_arc_opt = LazyLock::force(Self::storage())
+                .read()
+                .await
+                .get(&TypeId::of::<T>())
+                .map(|e| e.value().clone());
+
+            let result = match any_arc_opt {
+                Some(any_arc) => any_arc
+                    .downcast::<T>()
+                    .map_err(|_| RegistryError::TypeMismatch {
+                        type_name: std::any::type_name::<T>(),
+                    }),
+                None => Err(RegistryError::TypeNotFound {
+                    type_name: std::any::type_name::<T>(),
+                    location: None,
+                    suggestions: Vec::new(),
+                }),
+            };
+
+            self.emit_event(&RegistryEvent::Get {
+                type_name: std::any::type_name::<T>(),
+                found: result.is_ok(),
+                meta: EventMeta::timed(started),
+            });
+
+            result
+        }
+    }
+
+    /// Retrieve a cloned value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch
+    fn get_cloned<T: Send + Sync + Clone + 'static>(
+        &self,
+    ) -> impl Future<Output = Result<T, RegistryError>> + Send {
+        async move { self.get::<T>().await.map(|arc| (*arc).clone()) }
+    }
+
+    /// Retrieve a value from the registry, returning `None` if not registered.
+    fn try_get<T: Send + Sync + 'static>(&self) -> impl Future<Output = Option<Arc<T>>> + Send {
+        async move { self.get::<T>().await.ok() }
+    }
+
+    /// Check if a type is registered in the registry.
+    fn contains<T: Send + Sync + 'static>(&self) -> impl Future<Output = bool> + Send {
+        async move {
+            let found = LazyLock::force(Self::storage())
+                .read()
+                .await
+                .contains_key(&TypeId::of::<T>());
+
+            self.emit_event(&RegistryEvent::Contains {
+                type_name: std::any::type_name::<T>(),
+                found,
+                meta: EventMeta::default(),
+            });
+
+            found
+        }
+    }
+
+    /// Clear all registered values from the registry.
+    ///
+    /// Intended for testing and only available with the `test-util` feature;
+    /// already-retrieved `Arc<T>` handles remain valid.
+    #[cfg(any(test, feature = "test-util"))]
+    fn clear(&self) -> impl Future<Output = ()> + Send {
+        async move {
+            self.emit_event(&RegistryEvent::Clear {
+                meta: EventMeta::default(),
+            });
+            LazyLock::force(Self::storage()).write().await.clear();
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_async_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_register_and_get() {
+        define_async_registry!(reg);
+
+        reg::register(42i32).await;
+        let value: Arc<i32> = reg::get().await.unwrap();
+        assert_eq!(*value, 42);
+
+        assert!(reg::contains::<i32>().await);
+        assert!(!reg::contains::<u8>().await);
+        assert_eq!(reg::get_cloned::<i32>().await.unwrap(), 42);
+        assert!(reg::try_get::<u8>().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_clear() {
+        define_async_registry!(reg);
+
+        reg::register("a".to_string()).await;
+        reg::register("b".to_string()).await;
+        assert_eq!(&*reg::get::<String>().await.unwrap(), "b");
+
+        reg::clear().await;
+        assert!(reg::get::<String>().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trace_events() {
+        define_async_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        reg::set_trace_callback(move |e| events_clone.lock().unwrap().push(e.to_string()));
+
+        reg::register(1u8).await;
+        let _ = reg::get::<u8>().await;
+        reg::clear_trace_callback();
+        let _ = reg::contains::<u8>().await;
+
+        let captured = events.lock().unwrap().clone();
+        assert_eq!(
+            captured,
+            vec![
+                "register { type_name: u8 }",
+                "register_completed { type_name: u8 }",
+                "get { type_name: u8, found: true }",
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_tasks() {
+        define_async_registry!(reg);
+
+        reg::register(0usize).await;
+        let handles: Vec<_> = (1..=8usize)
+            .map(|i| tokio::spawn(async move { reg::register(i).await }))
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        let value = reg::get::<usize>().await.unwrap();
+        assert!((1..=8).contains(&*value));
+    }
+
+    #[test]
+    fn test_without_tokio_runtime() {
+        define_async_registry!(reg);
+
+        futures_lite::future::block_on(async {
+            reg::register(7u64).await;
+            assert_eq!(*reg::get::<u64>().await.unwrap(), 7);
+        });
+    }
+}
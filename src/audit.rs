@@ -0,0 +1,244 @@
+//! Append-only audit log of registry mutations (feature `audit`).
+//!
+//! `enable_audit_log(path)` appends one JSON object per line for every
+//! registration, replacement, revert, removal and clear, with a UTC timestamp,
+//! the registry name and the correlation context. Lookups are never audited. The
+//! log is opened in append mode and each record is written with a single
+//! `write`, so existing lines are never rewritten; how often the file is
+//! fsync'ed is set with [`FsyncPolicy`].
+//!
+//! ```text
+//! {"timestamp":"2024-05-01T12:00:00.000Z","registry":"services","op":"replace","type":"app::Config"}
+//! ```
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sync::Mutex;
+use crate::RegistryEvent;
+
+/// When the audit log is flushed to stable storage with `fsync`.
+///
+/// Records are always handed to the operating system as they happen; the policy
+/// only decides how much of the log a power loss may cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// Leave flushing to the operating system.
+    #[default]
+    Never,
+    /// `fsync` after every record.
+    Always,
+    /// `fsync` after every `n`-th record.
+    EveryN(u32),
+}
+
+#[derive(Debug)]
+struct AuditSink {
+    file: File,
+    policy: FsyncPolicy,
+    unsynced: u32,
+}
+
+/// Per-registry audit log, disabled until `enable_audit_log` is called.
+#[derive(Debug, Default)]
+pub(crate) struct AuditLog {
+    sink: Mutex<Option<AuditSink>>,
+}
+
+impl AuditLog {
+    /// Start appending records to `path`, instead of any previously enabled log.
+    pub(crate) fn enable(&self, path: &Path, policy: FsyncPolicy) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        *self.sink.lock().unwrap_or_else(|p| p.into_inner()) = Some(AuditSink {
+            file,
+            policy,
+            unsynced: 0,
+        });
+        Ok(())
+    }
+
+    /// Stop auditing, syncing outstanding records first.
+    pub(crate) fn disable(&self) -> io::Result<()> {
+        let sink = self.sink.lock().unwrap_or_else(|p| p.into_inner()).take();
+        match sink {
+            Some(sink) if sink.unsynced > 0 => sink.file.sync_data(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Append `event` if it is a mutation and auditing is enabled.
+    ///
+    /// Write errors are dropped: emitting an event never fails.
+    pub(crate) fn record(&self, event: &RegistryEvent, registry: &str) {
+        let (op, type_name) = match event {
+            RegistryEvent::RegisterCompleted {
+                type_name,
+                replaced,
+                rollback,
+                ..
+            } => (
+                match (*replaced, *rollback) {
+                    (_, true) => "revert",
+                    (true, false) => "replace",
+                    (false, false) => "register",
+                },
+                Some(*type_name),
+            ),
+            RegistryEvent::Remove { type_name, .. } => ("remove", Some(*type_name)),
+            RegistryEvent::Clear { .. } => ("clear", None),
+            _ => return,
+        };
+
+        let mut guard = self.sink.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(sink) = guard.as_mut() else {
+            return;
+        };
+
+        let mut line = String::with_capacity(128);
+        line.push_str("{\"timestamp\":\"");
+        line.push_str(&rfc3339_now());
+        line.push_str("\",\"registry\":");
+        push_json_str(&mut line, registry);
+        line.push_str(",\"op\":\"");
+        line.push_str(op);
+        line.push('"');
+        if let Some(type_name) = type_name {
+            line.push_str(",\"type\":");
+            push_json_str(&mut line, type_name);
+        }
+        if let Some(context) = crate::trace_context::trace_context() {
+            line.push_str(",\"context\":");
+            push_json_str(&mut line, &context);
+        }
+        line.push_str("}\n");
+
+        if sink.file.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+        sink.unsynced += 1;
+        let sync = match sink.policy {
+            FsyncPolicy::Never => false,
+            FsyncPolicy::Always => true,
+            FsyncPolicy::EveryN(n) => sink.unsynced >= n.max(1),
+        };
+        if sync && sink.file.sync_data().is_ok() {
+            sink.unsynced = 0;
+        }
+    }
+}
+
+/// Append `value` as a quoted JSON string.
+fn push_json_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// The current UTC time as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+fn rfc3339_now() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    rfc3339(since_epoch.as_secs(), since_epoch.subsec_millis())
+}
+
+fn rfc3339(secs: u64, millis: u32) -> String {
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        millis
+    )
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date.
+///
+/// Howard Hinnant's `civil_from_days`, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, with_trace_context};
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(rfc3339(951_782_400, 5), "2000-02-29T00:00:00.005Z");
+        assert_eq!(rfc3339(1_714_564_799, 999), "2024-05-01T11:59:59.999Z");
+    }
+
+    #[test]
+    fn test_json_escaping() {
+        let mut out = String::new();
+        push_json_str(&mut out, "a\"b\\c\nd\u{1}");
+        assert_eq!(out, r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn test_mutations_are_appended() {
+        define_registry!(reg);
+
+        let path = std::env::temp_dir().join(format!(
+            "singleton-registry-audit-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        reg::enable_audit_log_with(&path, FsyncPolicy::Always).unwrap();
+
+        reg::register(1u8);
+        let _ = reg::get::<u8>();
+        with_trace_context("deploy-7", || reg::register(2u8));
+        reg::remove::<u8>().unwrap();
+        reg::disable_audit_log().unwrap();
+        reg::register(3u8); // not audited
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let records: Vec<&str> = log
+            .lines()
+            .map(|line| &line[line.find("\"registry\"").unwrap()..])
+            .collect();
+        assert_eq!(
+            records,
+            [
+                r#""registry":"reg","op":"register","type":"u8"}"#,
+                r#""registry":"reg","op":"replace","type":"u8","context":"deploy-7"}"#,
+                r#""registry":"reg","op":"remove","type":"u8"}"#,
+            ]
+        );
+        assert!(log.starts_with("{\"timestamp\":\""));
+    }
+}
@@ -0,0 +1,110 @@
+//! axum integration (feature `axum`).
+//!
+//! Implements axum's extractor traits for [`FromRegistry`], so handlers can
+//! declare `FromRegistry<MyService, services::Api>` parameters instead of
+//! threading singletons through `State`.
+//!
+//! A missing type is rejected with `500 Internal Server Error`. Extract
+//! `Option<FromRegistry<T, R>>` to treat it as optional instead.
+
+use axum_core::extract::{FromRequestParts, OptionalFromRequestParts};
+use axum_core::response::{IntoResponse, Response};
+use http::request::Parts;
+use http::StatusCode;
+
+use crate::extract::{FromRegistry, RegistryRejection};
+use crate::{RegistryApi, RegistryError};
+
+impl IntoResponse for RegistryRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.0.to_string()).into_response()
+    }
+}
+
+impl<S, T, R> FromRequestParts<S> for FromRegistry<T, R>
+where
+    S: Send + Sync,
+    T: Send + Sync + 'static,
+    R: RegistryApi + Default,
+{
+    type Rejection = RegistryRejection;
+
+    async fn from_request_parts(_parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::resolve().map_err(RegistryRejection)
+    }
+}
+
+impl<S, T, R> OptionalFromRequestParts<S> for FromRegistry<T, R>
+where
+    S: Send + Sync,
+    T: Send + Sync + 'static,
+    R: RegistryApi + Default,
+{
+    type Rejection = RegistryRejection;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match Self::resolve() {
+            Ok(extracted) => Ok(Some(extracted)),
+            Err(RegistryError::TypeNotFound { .. }) => Ok(None),
+            Err(err) => Err(RegistryRejection(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use futures_lite::future::block_on;
+
+    fn parts() -> Parts {
+        http::Request::new(()).into_parts().0
+    }
+
+    #[test]
+    fn test_extracts_registered_value() {
+        define_registry!(reg);
+        reg::register("db".to_string());
+
+        let value = block_on(
+            <FromRegistry<String, reg::Api> as FromRequestParts<()>>::from_request_parts(
+                &mut parts(),
+                &(),
+            ),
+        )
+        .unwrap();
+        assert_eq!(&*value, "db");
+    }
+
+    #[test]
+    fn test_missing_value_is_rejected_with_500() {
+        define_registry!(reg);
+
+        let rejection = block_on(
+            <FromRegistry<u8, reg::Api> as FromRequestParts<()>>::from_request_parts(
+                &mut parts(),
+                &(),
+            ),
+        )
+        .unwrap_err();
+        assert_eq!(
+            rejection.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_optional_extraction() {
+        define_registry!(reg);
+
+        let missing = block_on(<FromRegistry<u8, reg::Api> as OptionalFromRequestParts<
+            (),
+        >>::from_request_parts(&mut parts(), &()))
+        .unwrap();
+        assert!(missing.is_none());
+    }
+}
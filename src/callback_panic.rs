@@ -0,0 +1,144 @@
+//! Isolation of panicking trace callbacks.
+//!
+//! By default a panic in a trace callback propagates into whatever registry
+//! call emitted the event, so a broken metrics hook can take down unrelated
+//! business code calling `get()`. `set_callback_panic_policy` lets the registry
+//! catch such panics instead, drop the event, and optionally remove the
+//! callback once it keeps failing.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+/// What a registry does when its trace callback panics.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, CallbackPanicPolicy};
+///
+/// define_registry!(services);
+///
+/// services::set_trace_callback(|_| panic!("metrics backend unavailable"));
+/// services::set_callback_panic_policy(CallbackPanicPolicy::Disable(3));
+///
+/// services::register(1u32); // the callback panics, the registration succeeds
+/// assert_eq!(*services::get::<u32>().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallbackPanicPolicy {
+    /// Let the panic unwind into the registry call that emitted the event.
+    #[default]
+    Propagate,
+    /// Catch the panic and drop the event.
+    Swallow,
+    /// Catch the panic and drop the event; remove the callback after the given
+    /// number of panics since it was set.
+    Disable(u32),
+}
+
+/// Per-registry panic policy and failure count of the trace callback.
+#[derive(Debug, Default)]
+pub(crate) struct CallbackPanics {
+    // Wiring configuration rather than registry data: std types, see `crate::sync`.
+    policy: Mutex<CallbackPanicPolicy>,
+    failures: AtomicU32,
+}
+
+impl CallbackPanics {
+    pub(crate) fn set(&self, policy: CallbackPanicPolicy) {
+        *self.policy.lock().unwrap_or_else(|p| p.into_inner()) = policy;
+    }
+
+    pub(crate) fn get(&self) -> CallbackPanicPolicy {
+        *self.policy.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Start counting failures of a new callback from zero.
+    pub(crate) fn reset(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+    }
+
+    /// Run `callback` under the policy.
+    ///
+    /// Returns `true` if the callback panicked often enough to be removed.
+    pub(crate) fn call(&self, callback: impl FnOnce()) -> bool {
+        let limit = match self.get() {
+            CallbackPanicPolicy::Propagate => {
+                callback();
+                return false;
+            }
+            CallbackPanicPolicy::Swallow => None,
+            CallbackPanicPolicy::Disable(limit) => Some(limit.max(1)),
+        };
+        if panic::catch_unwind(AssertUnwindSafe(callback)).is_ok() {
+            return false;
+        }
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        limit.is_some_and(|limit| failures >= limit)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_default_propagates() {
+        define_registry!(reg);
+
+        assert_eq!(reg::callback_panic_policy(), CallbackPanicPolicy::Propagate);
+        reg::set_trace_callback(|_| panic!("boom"));
+        let result = panic::catch_unwind(reg::contains::<u8>);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_swallow_keeps_the_callback() {
+        define_registry!(reg);
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let seen = calls.clone();
+        reg::set_trace_callback(move |_| {
+            seen.fetch_add(1, Ordering::Relaxed);
+            panic!("boom");
+        });
+        reg::set_callback_panic_policy(CallbackPanicPolicy::Swallow);
+
+        reg::register(1u8);
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_disable_removes_a_failing_callback() {
+        define_registry!(reg);
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let seen = calls.clone();
+        reg::set_trace_callback(move |_| {
+            seen.fetch_add(1, Ordering::Relaxed);
+            panic!("boom");
+        });
+        reg::set_callback_panic_policy(CallbackPanicPolicy::Disable(2));
+
+        let _ = reg::contains::<u8>();
+        let _ = reg::contains::<u8>();
+        let _ = reg::contains::<u8>();
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+        // A new callback gets a fresh count
+        let seen = calls.clone();
+        reg::set_trace_callback(move |_| {
+            seen.fetch_add(1, Ordering::Relaxed);
+            panic!("boom");
+        });
+        let _ = reg::contains::<u8>();
+        let _ = reg::contains::<u8>();
+        let _ = reg::contains::<u8>();
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+    }
+}
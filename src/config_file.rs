@@ -0,0 +1,350 @@
+//! Loading configuration files into a registry.
+//!
+//! `load_config_file::<T>(path)` deserializes a whole TOML (feature `toml`) or
+//! JSON (feature `json`) file into `T` and registers it. A [`ConfigManifest`]
+//! splits one file into top-level sections, each deserialized into its own
+//! type, and registers them all or none. The format follows the file extension.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+
+use crate::{RegistryApi, RegistryError};
+
+type ParseError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Registers a deserialized section; runs only once every section parsed.
+type Registration = Box<dyn FnOnce()>;
+
+/// Deserializes a section into its type and prepares its registration.
+type SectionLoader = fn(Section) -> Result<Registration, ParseError>;
+
+/// A supported file format.
+#[derive(Clone, Copy)]
+enum Format {
+    #[cfg(feature = "toml")]
+    Toml,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+/// One top-level section of a configuration file.
+enum Section {
+    #[cfg(feature = "toml")]
+    Toml(toml::Value),
+    #[cfg(feature = "json")]
+    Json(serde_json::Value),
+}
+
+impl Section {
+    fn deserialize<T: DeserializeOwned>(self) -> Result<T, ParseError> {
+        match self {
+            #[cfg(feature = "toml")]
+            Section::Toml(value) => value.try_into().map_err(Into::into),
+            #[cfg(feature = "json")]
+            Section::Json(value) => serde_json::from_value(value).map_err(Into::into),
+        }
+    }
+}
+
+/// Read the file at `path` and determine its format from the extension.
+fn read(path: &Path) -> Result<(Format, String), RegistryError> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        #[cfg(feature = "toml")]
+        Some("toml") => Format::Toml,
+        #[cfg(feature = "json")]
+        Some("json") => Format::Json,
+        _ => {
+            return Err(RegistryError::custom(format!(
+                "unsupported config file format: {}",
+                path.display()
+            )))
+        }
+    };
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| RegistryError::from_source(format!("reading {}", path.display()), err))?;
+    Ok((format, text))
+}
+
+fn parse_error(path: &Path, what: &str, err: ParseError) -> RegistryError {
+    RegistryError::from_source(format!("parsing {} as {}", path.display(), what), err)
+}
+
+/// Deserialize the whole file at `path` into `T`.
+pub(crate) fn load<T: DeserializeOwned>(path: &Path) -> Result<T, RegistryError> {
+    let (format, text) = read(path)?;
+    let parsed: Result<T, ParseError> = match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::from_str(&text).map_err(Into::into),
+        #[cfg(feature = "json")]
+        Format::Json => serde_json::from_str(&text).map_err(Into::into),
+    };
+    parsed.map_err(|err| parse_error(path, std::any::type_name::<T>(), err))
+}
+
+/// The top-level sections of the file at `path`, by name.
+fn sections(path: &Path) -> Result<Vec<(String, Section)>, RegistryError> {
+    let (format, text) = read(path)?;
+    let parsed: Result<Vec<(String, Section)>, ParseError> = match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::from_str::<toml::Table>(&text)
+            .map(|table| {
+                table
+                    .into_iter()
+                    .map(|(name, value)| (name, Section::Toml(value)))
+                    .collect()
+            })
+            .map_err(Into::into),
+        #[cfg(feature = "json")]
+        Format::Json => serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&text)
+            .map(|object| {
+                object
+                    .into_iter()
+                    .map(|(name, value)| (name, Section::Json(value)))
+                    .collect()
+            })
+            .map_err(Into::into),
+    };
+    parsed.map_err(|err| parse_error(path, "a table of sections", err))
+}
+
+/// Which sections of a configuration file become which registry entries.
+///
+/// Every section is deserialized before anything is registered, so a file with
+/// one bad section leaves the registry untouched. Sections not listed are
+/// ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use singleton_registry::{define_registry, ConfigManifest};
+///
+/// define_registry!(app);
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Database {
+///     url: String,
+/// }
+///
+/// # #[cfg(feature = "toml")] {
+/// # let dir = std::env::temp_dir().join("singleton-registry-manifest-doc");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let path = dir.join("app.toml");
+/// std::fs::write(&path, "[server]\nport = 8080\n\n[database]\nurl = \"postgres://db\"\n").unwrap();
+///
+/// ConfigManifest::<app::Api>::new()
+///     .section::<Server>("server")
+///     .section::<Database>("database")
+///     .load(&path)
+///     .unwrap();
+///
+/// assert_eq!(app::get::<Server>().unwrap().port, 8080);
+/// assert_eq!(app::get::<Database>().unwrap().url, "postgres://db");
+/// # }
+/// ```
+pub struct ConfigManifest<R> {
+    sections: Vec<(String, SectionLoader)>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<R: RegistryApi + Default + 'static> ConfigManifest<R> {
+    /// A manifest without sections.
+    pub fn new() -> Self {
+        Self {
+            sections: Vec::new(),
+            _registry: PhantomData,
+        }
+    }
+
+    /// Deserialize the section `name` into `T` and register it.
+    pub fn section<T: DeserializeOwned + Send + Sync + 'static>(
+        mut self,
+        name: impl Into<String>,
+    ) -> Self {
+        self.sections.push((name.into(), load_section::<R, T>));
+        self
+    }
+
+    /// Load the file at `path` and register every listed section.
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::Custom` if the file cannot be read or parsed, has an
+    /// unsupported extension, or lacks a listed section. Nothing is registered
+    /// then.
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<(), RegistryError> {
+        let path = path.as_ref();
+        let mut found = sections(path)?;
+
+        let mut registrations = Vec::with_capacity(self.sections.len());
+        for (name, load) in &self.sections {
+            let index = found.iter().position(|(n, _)| n == name).ok_or_else(|| {
+                RegistryError::custom(format!("{}: missing section `{}`", path.display(), name))
+            })?;
+            let (_, section) = found.swap_remove(index);
+            let registration = load(section)
+                .map_err(|err| parse_error(path, &format!("section `{name}`"), err))?;
+            registrations.push(registration);
+        }
+
+        for register in registrations {
+            register();
+        }
+        Ok(())
+    }
+}
+
+fn load_section<R, T>(section: Section) -> Result<Registration, ParseError>
+where
+    R: RegistryApi + Default + 'static,
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    let value: T = section.deserialize()?;
+    Ok(Box::new(move || R::default().register(value)))
+}
+
+impl<R: RegistryApi + Default + 'static> Default for ConfigManifest<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> fmt::Debug for ConfigManifest<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.sections.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use serde::Deserialize;
+    use std::path::PathBuf;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Database {
+        url: String,
+    }
+
+    fn write(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("singleton-registry-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_config_file_toml() {
+        define_registry!(reg);
+
+        let path = write("server.toml", "host = \"localhost\"\nport = 8080\n");
+        reg::load_config_file::<Server>(&path).unwrap();
+        assert_eq!(reg::get::<Server>().unwrap().port, 8080);
+
+        let path = write("broken.toml", "port = \"eighty\"\n");
+        let err = reg::load_config_file::<Server>(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("parsing {}", path.display())));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_load_config_file_json() {
+        define_registry!(reg);
+
+        let path = write("server.json", r#"{ "host": "localhost", "port": 9090 }"#);
+        reg::load_config_file::<Server>(&path).unwrap();
+        assert_eq!(reg::get::<Server>().unwrap().port, 9090);
+    }
+
+    #[test]
+    fn test_unsupported_or_missing_file() {
+        define_registry!(reg);
+
+        let path = write("server.ini", "port = 1");
+        let err = reg::load_config_file::<Server>(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unsupported config file format"));
+
+        let extension = if cfg!(feature = "toml") {
+            "toml"
+        } else {
+            "json"
+        };
+        let missing = std::env::temp_dir().join(format!("singleton-registry-missing.{extension}"));
+        let err = reg::load_config_file::<Server>(missing).unwrap_err();
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_config_file_reports_rejection() {
+        define_registry!(reg, { registration: AppendOnly });
+
+        let path = write("append-only.toml", "host = \"localhost\"\nport = 8080\n");
+        reg::load_config_file::<Server>(&path).unwrap();
+        let path = write("append-only-2.toml", "host = \"localhost\"\nport = 9090\n");
+        assert!(matches!(
+            reg::load_config_file::<Server>(&path),
+            Err(crate::RegistryError::AlreadyRegistered { .. })
+        ));
+        assert_eq!(reg::get::<Server>().unwrap().port, 8080);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_manifest_registers_all_or_nothing() {
+        use super::ConfigManifest;
+
+        define_registry!(reg);
+
+        let manifest = ConfigManifest::<reg::Api>::new()
+            .section::<Server>("server")
+            .section::<Database>("database");
+
+        let path = write(
+            "partial.toml",
+            "[server]\nhost = \"localhost\"\nport = 8080\n",
+        );
+        let err = manifest.load(&path).unwrap_err();
+        assert!(err.to_string().ends_with("missing section `database`"));
+        assert!(!reg::contains::<Server>().unwrap());
+
+        let path = write(
+            "app.toml",
+            "[server]\nhost = \"localhost\"\nport = 8080\n\n\
+             [database]\nurl = \"postgres://db\"\n\n\
+             [unused]\nkey = 1\n",
+        );
+        manifest.load(&path).unwrap();
+        assert_eq!(
+            *reg::get::<Server>().unwrap(),
+            Server {
+                host: "localhost".to_string(),
+                port: 8080
+            }
+        );
+        assert_eq!(reg::get::<Database>().unwrap().url, "postgres://db");
+    }
+}
@@ -0,0 +1,118 @@
+//! Lock contention diagnostics (feature `contention-stats`).
+//!
+//! Every acquisition of the storage and trace locks first attempts a `try_lock`.
+//! If the lock is free the acquisition is counted as uncontended; otherwise the
+//! time spent blocking on `lock` is measured and added to the cumulative wait time.
+//!
+//! The numbers help decide whether a registry is a contention hotspot worth
+//! splitting into several registries or moving to a different lock backend.
+
+use std::sync::atomic::Ordering;
+use std::sync::{LockResult, TryLockError};
+use std::time::{Duration, Instant};
+
+use crate::sync::{AtomicU64, Mutex, MutexGuard};
+
+/// Snapshot of lock contention counters for a single lock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockStats {
+    /// Total number of lock acquisitions.
+    pub acquisitions: u64,
+    /// Number of acquisitions that had to wait because the lock was held.
+    pub contended: u64,
+    /// Cumulative time spent waiting for the lock.
+    pub total_wait: Duration,
+}
+
+/// Snapshot of lock contention counters for a registry.
+///
+/// Returned by `contention_stats()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContentionStats {
+    /// Counters for the storage lock (register, get, contains, clear).
+    pub storage: LockStats,
+    /// Counters for the trace callback lock.
+    pub trace: LockStats,
+}
+
+/// Live counters for a single lock.
+#[derive(Debug, Default)]
+pub(crate) struct LockCounters {
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    wait_nanos: AtomicU64,
+}
+
+impl LockCounters {
+    /// Acquire `mutex`, recording whether the acquisition had to wait.
+    pub(crate) fn acquire<'a, T>(&self, mutex: &'a Mutex<T>) -> LockResult<MutexGuard<'a, T>> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+
+        match mutex.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(TryLockError::Poisoned(poisoned)) => Err(poisoned),
+            Err(TryLockError::WouldBlock) => {
+                let start = Instant::now();
+                let result = mutex.lock();
+                let waited = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+                self.contended.fetch_add(1, Ordering::Relaxed);
+                self.wait_nanos.fetch_add(waited, Ordering::Relaxed);
+                result
+            }
+        }
+    }
+
+    /// Take a snapshot of the current counter values.
+    pub(crate) fn snapshot(&self) -> LockStats {
+        LockStats {
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            contended: self.contended.load(Ordering::Relaxed),
+            total_wait: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[test]
+    fn test_uncontended_acquisition() {
+        let counters = LockCounters::default();
+        let mutex = Mutex::new(0);
+
+        drop(counters.acquire(&mutex).unwrap());
+        drop(counters.acquire(&mutex).unwrap());
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.acquisitions, 2);
+        assert_eq!(stats.contended, 0);
+        assert_eq!(stats.total_wait, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_contended_acquisition() {
+        let counters = Arc::new(LockCounters::default());
+        let mutex = Arc::new(Mutex::new(0));
+
+        let guard = mutex.lock().unwrap();
+        let handle = {
+            let counters = counters.clone();
+            let mutex = mutex.clone();
+            thread::spawn(move || {
+                *counters.acquire(&mutex).unwrap() += 1;
+            })
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        drop(guard);
+        handle.join().unwrap();
+
+        let stats = counters.snapshot();
+        assert_eq!(stats.acquisitions, 1);
+        assert_eq!(stats.contended, 1);
+        assert!(stats.total_wait > Duration::ZERO);
+    }
+}
@@ -0,0 +1,114 @@
+//! Structural comparison of two registries.
+//!
+//! `contents_equal(&other)` checks that two registries wire the same types,
+//! e.g. that the production and the test bootstrap path agree. Values are
+//! compared only where both sides registered them with `register_comparable`,
+//! since the registry cannot call `PartialEq` on arbitrary erased values.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::RegistryEntry;
+
+/// How [`contents_equal_by`](crate::RegistryApi::contents_equal_by) compares
+/// the values of a type registered in both registries.
+///
+/// Registries holding different sets of types are never equal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentEquality {
+    /// Only the registered types are compared.
+    Types,
+    /// Both registries must hold the same `Arc`, e.g. after `mirror_to`.
+    Pointers,
+    /// Values registered with `register_comparable` on both sides must be
+    /// equal by `PartialEq`; other values only need to be of the same type.
+    #[default]
+    Values,
+}
+
+/// Compares two type-erased values as their concrete type `T`.
+pub(crate) type EqFn = fn(&(dyn Any + Send + Sync), &(dyn Any + Send + Sync)) -> bool;
+
+pub(crate) fn eq_as<T: PartialEq + 'static>(
+    a: &(dyn Any + Send + Sync),
+    b: &(dyn Any + Send + Sync),
+) -> bool {
+    match (a.downcast_ref::<T>(), b.downcast_ref::<T>()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Whether the entries `a` and `b` of two registries are equal under `equality`.
+pub(crate) fn contents_equal(
+    a: Vec<(TypeId, RegistryEntry)>,
+    b: Vec<(TypeId, RegistryEntry)>,
+    equality: ContentEquality,
+) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let b: HashMap<TypeId, RegistryEntry> = b.into_iter().collect();
+    a.iter().all(|(type_id, a)| {
+        let Some(b) = b.get(type_id) else {
+            return false;
+        };
+        match equality {
+            ContentEquality::Types => true,
+            ContentEquality::Pointers => Arc::ptr_eq(a.value(), b.value()),
+            ContentEquality::Values => match (a.value_eq(), b.value_eq()) {
+                (Some(eq), Some(_)) => eq(&**a.value(), &**b.value()),
+                _ => true,
+            },
+        }
+    })
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    #[test]
+    fn test_contents_equal() {
+        define_registry!(a);
+        define_registry!(b);
+
+        assert!(a::contents_equal(&b::Api));
+
+        a::register_comparable(Port(80));
+        a::register(String::from("a"));
+        assert!(!a::contents_equal(&b::Api));
+
+        b::register_comparable(Port(80));
+        b::register(String::from("b"));
+        assert!(a::contents_equal(&b::Api));
+        assert!(a::contents_equal_by(&b::Api, ContentEquality::Types));
+        assert!(!a::contents_equal_by(&b::Api, ContentEquality::Pointers));
+
+        b::register_comparable(Port(81));
+        assert!(!a::contents_equal(&b::Api));
+        assert!(a::contents_equal_by(&b::Api, ContentEquality::Types));
+
+        // Without a comparator on both sides only the type counts
+        b::register(Port(81));
+        assert!(a::contents_equal(&b::Api));
+    }
+
+    #[test]
+    fn test_pointers() {
+        define_registry!(a);
+        define_registry!(b);
+
+        let port = Arc::new(Port(80));
+        a::register_arc(port.clone());
+        b::register_arc(port);
+        assert!(a::contents_equal_by(&b::Api, ContentEquality::Pointers));
+        assert!(a::contents_equal_by(&a::Api, ContentEquality::Pointers));
+    }
+}
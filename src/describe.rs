@@ -0,0 +1,103 @@
+//! Runtime self-documentation of registry contents.
+//!
+//! `describe()` snapshots every entry into an [`EntryInfo`], to power admin and
+//! debug endpoints that show what is wired right now.
+
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::{Metadata, RegistryEntry};
+
+/// A snapshot of one registry entry, returned by `describe()`.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct EntryInfo {
+    /// The type name of the value
+    pub type_name: &'static str,
+    /// When the current value was registered
+    pub registered: SystemTime,
+    /// How many times the type has been registered since it was last absent,
+    /// counting the current value (`1` for a value that never got replaced)
+    pub generation: u64,
+    /// The metadata the value was registered with, if any
+    pub metadata: Option<Arc<Metadata>>,
+    /// Number of `Arc` handles to the value, including the registry's own
+    pub strong_count: usize,
+    /// Source location of the registering call, if recorded
+    pub registered_at: Option<&'static Location<'static>>,
+    /// Whether the value was registered as final
+    pub is_final: bool,
+    /// Whether the value was marked as protected
+    pub is_protected: bool,
+}
+
+impl From<&RegistryEntry> for EntryInfo {
+    fn from(entry: &RegistryEntry) -> Self {
+        Self {
+            type_name: entry.type_name(),
+            registered: entry.registration_time(),
+            generation: entry.generation(),
+            metadata: entry.metadata_arc().cloned(),
+            strong_count: Arc::strong_count(entry.value()),
+            registered_at: entry.registered_at(),
+            is_final: entry.is_final(),
+            is_protected: entry.is_protected(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, Metadata};
+
+    #[test]
+    fn test_describe() {
+        define_registry!(reg);
+
+        assert!(reg::describe().is_empty());
+
+        reg::register(1u8);
+        reg::register(2u8);
+        reg::register_with_meta("x".to_string(), Metadata::new("greeting"));
+        let _held = reg::get::<String>().unwrap();
+
+        let info = reg::describe();
+        assert_eq!(info.len(), 2);
+
+        // Sorted by type name
+        assert_eq!(info[0].type_name, "alloc::string::String");
+        assert_eq!(info[0].generation, 1);
+        assert_eq!(info[0].strong_count, 2);
+        assert_eq!(info[0].metadata.as_ref().unwrap().description, "greeting");
+        assert_eq!(info[0].registered_at.unwrap().file(), file!());
+
+        assert_eq!(info[1].type_name, "u8");
+        assert_eq!(info[1].generation, 2);
+        assert_eq!(info[1].strong_count, 1);
+        assert!(info[1].metadata.is_none());
+        assert!(info[0].registered >= info[1].registered);
+
+        // Removing the type restarts its generations
+        reg::remove::<u8>().unwrap();
+        reg::register(3u8);
+        assert_eq!(reg::describe()[1].generation, 1);
+    }
+
+    #[test]
+    fn test_for_each_entry() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        reg::register(String::from("hi"));
+
+        let mut seen = Vec::new();
+        reg::for_each_entry(|info, value| {
+            // The visitor runs without the lock, so it may use the registry
+            assert!(reg::contains::<u8>().unwrap());
+            seen.push((info.type_name, value.downcast_ref::<u8>().copied()));
+        });
+        assert_eq!(seen, [("alloc::string::String", None), ("u8", Some(1))]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Registries as trait objects.
+//!
+//! [`RegistryApi`] reaches its storage through associated functions and has
+//! generic methods, so it can't be used as `dyn RegistryApi`. [`DynRegistry`] is
+//! its object-safe counterpart: a handful of type-erased operations, implemented
+//! by every `define_registry!` registry and by the runtime [`Registry`], with the
+//! typed methods provided on `dyn DynRegistry` itself. Code written against
+//! `&dyn DynRegistry` can be handed any registry, or a mock in tests.
+
+use std::any::{Any, TypeId};
+use std::panic::Location;
+use std::sync::Arc;
+
+use crate::registry_trait::{contains_entry, get_entry, remove_entry, store_entry};
+#[cfg(doc)]
+use crate::Registry;
+use crate::{RegistryApi, RegistryEntry, RegistryError};
+
+/// Object-safe registry operations, for `Box<dyn DynRegistry>` and
+/// `&dyn DynRegistry`.
+///
+/// The methods work on type-erased values keyed by `TypeId`; use the typed
+/// methods of `dyn DynRegistry` (`register`, `get`, `contains`, ...) instead of
+/// calling them directly. Implement the trait to substitute a registry, e.g.
+/// with a mock in tests.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, DynRegistry, Registry};
+///
+/// define_registry!(app);
+///
+/// fn greeting(registry: &dyn DynRegistry) -> String {
+///     format!("hello, {}", registry.get::<String>().unwrap())
+/// }
+///
+/// app::register(String::from("app"));
+/// let tenant = Registry::new();
+/// tenant.register(String::from("tenant"));
+///
+/// let registries: Vec<Box<dyn DynRegistry>> = vec![Box::new(app::Api), Box::new(tenant)];
+/// let greetings: Vec<String> = registries.iter().map(|r| greeting(&**r)).collect();
+/// assert_eq!(greetings, ["hello, app", "hello, tenant"]);
+/// ```
+pub trait DynRegistry: Send + Sync {
+    /// Name of the registry, as reported in its events.
+    fn registry_name(&self) -> &'static str;
+
+    /// Store `entry` under `type_id`, replacing any previous entry.
+    ///
+    /// # Errors
+    ///
+    /// - The registered value is final
+    fn register_entry(&self, type_id: TypeId, entry: RegistryEntry) -> Result<(), RegistryError>;
+
+    /// The value stored under `type_id`.
+    ///
+    /// # Errors
+    ///
+    /// - No value is registered for the type
+    /// - Registry lock is poisoned
+    fn get_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError>;
+
+    /// Whether a value is registered under `type_id`.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned
+    fn contains_any(&self, type_id: TypeId, type_name: &'static str)
+        -> Result<bool, RegistryError>;
+
+    /// Remove the value stored under `type_id` and return it.
+    ///
+    /// # Errors
+    ///
+    /// - No value is registered for the type
+    /// - The registered value is final
+    fn remove_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError>;
+}
+
+impl<R: RegistryApi + Send + Sync> DynRegistry for R {
+    fn registry_name(&self) -> &'static str {
+        R::name()
+    }
+
+    fn register_entry(&self, type_id: TypeId, entry: RegistryEntry) -> Result<(), RegistryError> {
+        store_entry(self, type_id, entry, false)
+    }
+
+    fn get_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+        get_entry(self, type_id, type_name, None)
+    }
+
+    fn contains_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<bool, RegistryError> {
+        contains_entry(self, type_id, type_name)
+    }
+
+    fn remove_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+        remove_entry(self, type_id, type_name, None).map(|entry| entry.value().clone())
+    }
+}
+
+impl dyn DynRegistry + '_ {
+    /// Register a value, replacing any previous value of the same type.
+    ///
+    /// A final, protected or append-only rejection is reported via `RegisterRejected`.
+    #[track_caller]
+    pub fn register<T: Send + Sync + 'static>(&self, value: T) {
+        self.register_arc(Arc::new(value));
+    }
+
+    /// Register an Arc-wrapped value.
+    #[track_caller]
+    pub fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        let entry = RegistryEntry::new(value).at(Location::caller());
+        let _ = self.register_entry(TypeId::of::<T>(), entry);
+    }
+
+    /// Register a value built at runtime under its `TypeId`; see
+    /// [`RegistryApi::register_boxed`](crate::RegistryApi::register_boxed).
+    ///
+    /// # Errors
+    ///
+    /// - `value` is not of the type `type_id`
+    /// - The registered value is final
+    #[track_caller]
+    pub fn register_boxed(
+        &self,
+        type_id: TypeId,
+        value: Box<dyn Any + Send + Sync>,
+    ) -> Result<(), RegistryError> {
+        let entry = RegistryEntry::boxed(type_id, value)?.at(Location::caller());
+        self.register_entry(type_id, entry)
+    }
+
+    /// Retrieve a value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    #[track_caller]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        match self.get_any(TypeId::of::<T>(), type_name) {
+            Ok(value) => value
+                .downcast::<T>()
+                .map_err(|_| RegistryError::TypeMismatch { type_name }),
+            Err(RegistryError::TypeNotFound {
+                type_name,
+                location: None,
+                suggestions,
+            }) => Err(RegistryError::TypeNotFound {
+                type_name,
+                location: Some(Location::caller()),
+                suggestions,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Retrieve a cloned value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch
+    #[track_caller]
+    pub fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.get::<T>().map(|arc| (*arc).clone())
+    }
+
+    /// Retrieve a value from the registry, returning `None` if not registered.
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Check if a type is registered.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned
+    pub fn contains<T: Send + Sync + 'static>(&self) -> Result<bool, RegistryError> {
+        self.contains_any(TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// Remove the value of `T` from the registry and return it.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - `T` was registered as final
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        self.remove_any(TypeId::of::<T>(), type_name)?
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// A hand-written registry, as a test double would be.
+    #[derive(Default)]
+    struct Mock(Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>);
+
+    impl DynRegistry for Mock {
+        fn registry_name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn register_entry(
+            &self,
+            type_id: TypeId,
+            entry: RegistryEntry,
+        ) -> Result<(), RegistryError> {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(type_id, entry.value().clone());
+            Ok(())
+        }
+
+        fn get_any(
+            &self,
+            type_id: TypeId,
+            type_name: &'static str,
+        ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+            self.0
+                .lock()
+                .unwrap()
+                .get(&type_id)
+                .cloned()
+                .ok_or(RegistryError::TypeNotFound {
+                    type_name,
+                    location: None,
+                    suggestions: Vec::new(),
+                })
+        }
+
+        fn contains_any(&self, type_id: TypeId, _: &'static str) -> Result<bool, RegistryError> {
+            Ok(self.0.lock().unwrap().contains_key(&type_id))
+        }
+
+        fn remove_any(
+            &self,
+            type_id: TypeId,
+            type_name: &'static str,
+        ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+            let value = self.0.lock().unwrap().remove(&type_id);
+            value.ok_or(RegistryError::TypeNotFound {
+                type_name,
+                location: None,
+                suggestions: Vec::new(),
+            })
+        }
+    }
+
+    fn exercise(registry: &dyn DynRegistry) {
+        registry.register(7u32);
+        assert_eq!(*registry.get::<u32>().unwrap(), 7);
+        assert_eq!(registry.get_cloned::<u32>().unwrap(), 7);
+        assert_eq!(registry.contains::<u32>(), Ok(true));
+        assert_eq!(*registry.remove::<u32>().unwrap(), 7);
+        assert!(registry.try_get::<u32>().is_none());
+    }
+
+    #[test]
+    fn test_every_registry_as_trait_object() {
+        define_registry!(reg);
+
+        let registries: Vec<Box<dyn DynRegistry>> = vec![
+            Box::new(reg::Api),
+            Box::new(crate::Registry::named("runtime")),
+            Box::new(Mock::default()),
+        ];
+        for registry in &registries {
+            exercise(&**registry);
+        }
+        let names: Vec<_> = registries.iter().map(|r| r.registry_name()).collect();
+        assert_eq!(names, ["reg", "runtime", "mock"]);
+    }
+
+    #[test]
+    fn test_register_boxed() {
+        let registry: &dyn DynRegistry = &crate::Registry::new();
+        let value: Box<dyn Any + Send + Sync> = Box::new(String::from("built"));
+        registry
+            .register_boxed(TypeId::of::<String>(), value)
+            .unwrap();
+        assert_eq!(*registry.get::<String>().unwrap(), "built");
+    }
+
+    #[test]
+    fn test_not_found_reports_the_caller() {
+        define_registry!(reg);
+
+        let registry: &dyn DynRegistry = &reg::Api;
+        let line = line!() + 1;
+        let err = registry.get::<u8>().unwrap_err();
+        let RegistryError::TypeNotFound { location, .. } = err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(location.map(|l| l.line()), Some(line));
+    }
+
+    #[test]
+    fn test_lazy_providers_resolve_through_the_trait_object() {
+        define_registry!(reg);
+
+        reg::register_lazy(|| String::from("built"));
+        let registry: &dyn DynRegistry = &reg::Api;
+        assert_eq!(registry.contains::<String>(), Ok(true));
+        assert_eq!(*registry.get::<String>().unwrap(), "built");
+        assert!(crate::registry_trait::lookup::<reg::Api, String>().is_some());
+    }
+}
@@ -0,0 +1,228 @@
+//! Building configuration structs from environment variables.
+//!
+//! `register_from_env::<T>("APP_")` deserializes `T` with serde from the
+//! variables starting with the prefix and registers it, envy-style: field
+//! `port` is read from `APP_PORT`, field `database_url` from `APP_DATABASE_URL`.
+//!
+//! Values are parsed into the field's type (numbers, `bool`, `char`, strings,
+//! unit enum variants); `Option` fields are `None` when their variable is unset
+//! and sequences are comma-separated (`APP_HOSTS=a,b,c`). Unrelated variables
+//! are ignored unless `T` denies unknown fields.
+
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Deserialize `T` from the environment variables starting with `prefix`.
+pub(crate) fn from_env<T: DeserializeOwned>(prefix: &str) -> Result<T, Error> {
+    from_vars(
+        prefix,
+        std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?))),
+    )
+}
+
+/// Deserialize `T` from the `(name, value)` pairs whose name starts with `prefix`.
+fn from_vars<T: DeserializeOwned>(
+    prefix: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<T, Error> {
+    let fields = vars.into_iter().filter_map(|(name, value)| {
+        let field = name.strip_prefix(prefix)?.to_lowercase();
+        Some((field, Value(value)))
+    });
+    T::deserialize(MapDeserializer::new(fields))
+}
+
+/// The value of one variable, parsed on demand into the requested type.
+struct Value(String);
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.trim().parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(err) => Err(de::Error::custom(format_args!("invalid value {:?}: {}", self.0, err))),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = self
+            .0
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| Value(item.to_string()));
+        visitor.visit_seq(SeqDeserializer::new(items))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, RegistryError};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Dev,
+        Prod,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        port: u16,
+        database_url: String,
+        debug: bool,
+        mode: Mode,
+        hosts: Vec<String>,
+        timeout_ms: Option<u64>,
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_vars() {
+        let config: Config = from_vars(
+            "APP_",
+            vars(&[
+                ("APP_PORT", "8080"),
+                ("APP_DATABASE_URL", "postgres://localhost"),
+                ("APP_DEBUG", "true"),
+                ("APP_MODE", "prod"),
+                ("APP_HOSTS", "a, b,c"),
+                ("OTHER_PORT", "1"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                port: 8080,
+                database_url: "postgres://localhost".to_string(),
+                debug: true,
+                mode: Mode::Prod,
+                hosts: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                timeout_ms: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_vars_errors() {
+        let err = from_vars::<Config>("APP_", vars(&[("APP_PORT", "eighty")])).unwrap_err();
+        assert!(err.to_string().starts_with("invalid value \"eighty\""));
+
+        let err = from_vars::<Config>("APP_", vars(&[("APP_PORT", "80")])).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `database_url`");
+    }
+
+    #[test]
+    fn test_register_from_env() {
+        #[derive(Debug, Deserialize)]
+        struct Limits {
+            max_connections: u32,
+        }
+
+        define_registry!(reg);
+
+        // Variables unique to this test: the environment is process-wide
+        std::env::set_var("SR_ENV_TEST_MAX_CONNECTIONS", "64");
+        reg::register_from_env::<Limits>("SR_ENV_TEST_").unwrap();
+        assert_eq!(reg::get::<Limits>().unwrap().max_connections, 64);
+
+        let err = reg::register_from_env::<Limits>("SR_ENV_UNSET_").unwrap_err();
+        assert!(matches!(err, RegistryError::Custom { .. }));
+        assert!(err.to_string().contains("SR_ENV_UNSET_"));
+    }
+
+    #[test]
+    fn test_register_from_env_reports_rejection() {
+        #[derive(Debug, Deserialize)]
+        struct Timeout {
+            seconds: u32,
+        }
+
+        define_registry!(reg);
+
+        std::env::set_var("SR_ENV_FINAL_SECONDS", "5");
+        reg::register_final(Timeout { seconds: 1 }).unwrap();
+        assert_eq!(
+            reg::register_from_env::<Timeout>("SR_ENV_FINAL_"),
+            Err(RegistryError::FinalType {
+                type_name: std::any::type_name::<Timeout>()
+            })
+        );
+        assert_eq!(reg::get::<Timeout>().unwrap().seconds, 1);
+    }
+}
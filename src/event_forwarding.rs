@@ -0,0 +1,113 @@
+//! Forwarding of events from child registries to their parents.
+//!
+//! `child::forward_events_to::<root::Api>()` makes `child` pass every event it
+//! emits on to `root`'s trace callback and event subscribers, and from there
+//! to `root`'s own parent, so a single subscriber set on the root observes the
+//! whole tree. Forwarded events keep the child's name in `event.registry()`.
+
+use std::sync::Mutex;
+
+use crate::{RegistryEvent, RegistryState};
+
+/// The registry a child forwards its events to.
+#[derive(Clone, Copy)]
+pub(crate) struct ParentLink {
+    /// The parent's state, to walk the chain when checking for cycles.
+    pub(crate) state: fn() -> &'static RegistryState,
+    /// Hands an event to the parent's sinks.
+    pub(crate) deliver: fn(&RegistryEvent),
+}
+
+/// The parent link of one registry, unset by default.
+///
+/// Wiring configuration rather than registry data: a std lock, see `crate::sync`.
+#[derive(Default)]
+pub(crate) struct EventParent {
+    link: Mutex<Option<ParentLink>>,
+}
+
+impl std::fmt::Debug for EventParent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventParent")
+            .field("linked", &self.get().is_some())
+            .finish()
+    }
+}
+
+impl EventParent {
+    /// Forward to `link` from now on.
+    ///
+    /// # Panics
+    ///
+    /// If `link` leads back to `own`, since events would circle forever.
+    pub(crate) fn set(&self, own: &RegistryState, link: ParentLink) {
+        let mut next = Some(link);
+        while let Some(link) = next {
+            let state = (link.state)();
+            assert!(
+                !std::ptr::eq(state, own),
+                "forward_events_to: forwarding would form a cycle"
+            );
+            next = state.event_parent.get();
+        }
+        *self.link.lock().unwrap_or_else(|p| p.into_inner()) = Some(link);
+    }
+
+    /// Stop forwarding.
+    pub(crate) fn clear(&self) {
+        *self.link.lock().unwrap_or_else(|p| p.into_inner()) = None;
+    }
+
+    /// The current parent, if any.
+    pub(crate) fn get(&self) -> Option<ParentLink> {
+        *self.link.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_root_observes_the_tree() {
+        define_registry!(root);
+        define_registry!(child);
+        define_registry!(grandchild);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        root::set_trace_callback(move |e| {
+            sink.lock().unwrap().push(format!("{}: {e}", e.registry()))
+        });
+
+        child::forward_events_to::<root::Api>();
+        grandchild::forward_events_to::<child::Api>();
+
+        let _ = root::contains::<u8>();
+        let _ = child::contains::<u8>();
+        let _ = grandchild::contains::<u8>();
+        grandchild::stop_forwarding_events();
+        let _ = grandchild::contains::<u8>();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "root: contains { type_name: u8, found: false }",
+                "child: contains { type_name: u8, found: false }",
+                "grandchild: contains { type_name: u8, found: false }",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn test_cycles_are_rejected() {
+        define_registry!(a);
+        define_registry!(b);
+
+        a::forward_events_to::<b::Api>();
+        b::forward_events_to::<a::Api>();
+    }
+}
@@ -0,0 +1,130 @@
+//! Severity levels of registry events.
+//!
+//! Every [`RegistryEvent`](crate::RegistryEvent) has an [`EventLevel`]: routine
+//! hits are `Debug`, mutations `Info`, clears `Notice`, and misses, rejected
+//! registrations and leaks `Warn`. `set_trace_level(EventLevel::Info)` keeps only
+//! the meaningful events in production, while tests keep the default and
+//! capture everything.
+
+use std::sync::atomic::Ordering;
+
+use crate::sync::AtomicU8;
+
+/// Severity of a registry event, from least to most severe.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, EventLevel};
+///
+/// define_registry!(services);
+///
+/// services::set_trace_callback(|event| println!("[{}] {event}", event.level()));
+/// services::set_trace_level(EventLevel::Info);
+///
+/// services::register(42u32); // traced
+/// let _ = services::get::<u32>(); // hit: filtered out
+/// let _ = services::get::<u64>(); // miss: traced
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EventLevel {
+    /// Successful lookups and `contains` checks.
+    #[default]
+    Debug,
+    /// Registrations and removals.
+    Info,
+    /// Clearing the whole registry.
+    Notice,
+    /// Failed lookups, rejected registrations and leaked values.
+    Warn,
+}
+
+impl EventLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => EventLevel::Debug,
+            1 => EventLevel::Info,
+            2 => EventLevel::Notice,
+            _ => EventLevel::Warn,
+        }
+    }
+}
+
+impl std::fmt::Display for EventLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EventLevel::Debug => "debug",
+            EventLevel::Info => "info",
+            EventLevel::Notice => "notice",
+            EventLevel::Warn => "warn",
+        })
+    }
+}
+
+/// Per-registry minimum level of emitted events.
+#[derive(Debug, Default)]
+pub(crate) struct LevelFilter {
+    min: AtomicU8,
+}
+
+impl LevelFilter {
+    /// Emit only events at `level` or above.
+    pub(crate) fn set(&self, level: EventLevel) {
+        self.min.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// The current minimum level.
+    pub(crate) fn get(&self) -> EventLevel {
+        EventLevel::from_u8(self.min.load(Ordering::Relaxed))
+    }
+
+    /// Whether an event at `level` passes the filter.
+    pub(crate) fn admits(&self, level: EventLevel) -> bool {
+        level >= self.get()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_filter_round_trip() {
+        let filter = LevelFilter::default();
+        assert_eq!(filter.get(), EventLevel::Debug);
+        assert!(filter.admits(EventLevel::Debug));
+
+        filter.set(EventLevel::Notice);
+        assert_eq!(filter.get(), EventLevel::Notice);
+        assert!(!filter.admits(EventLevel::Info));
+        assert!(filter.admits(EventLevel::Warn));
+    }
+
+    #[test]
+    fn test_registry_drops_events_below_level() {
+        define_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        reg::set_trace_callback(move |e| sink.lock().unwrap().push(format!("[{}] {e}", e.level())));
+        reg::set_trace_level(EventLevel::Info);
+        assert_eq!(reg::trace_level(), EventLevel::Info);
+
+        reg::register(1u8);
+        let _ = reg::get::<u8>();
+        let _ = reg::contains::<u16>();
+        let _ = reg::get::<u16>();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "[info] register { type_name: u8 }",
+                "[info] register_completed { type_name: u8 }",
+                "[warn] get { type_name: u16, found: false }",
+            ]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Recording trace events for test assertions.
+//!
+//! Tests asserting on events used to push them into an
+//! `Arc<Mutex<Vec<String>>>` from a hand-written trace callback. An
+//! [`EventRecorder`] does the same and adds the queries such tests need.
+
+use std::any::type_name;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::RegistryEvent;
+
+/// A cloneable, thread-safe log of registry events.
+///
+/// `record_events()` (feature `test-util`) installs a recorder as the
+/// registry's trace callback, replacing any previous one. Other event sources,
+/// such as a runtime [`Registry`](crate::Registry) or the global trace sink,
+/// can feed one through [`record`](EventRecorder::record).
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// let events = app::record_events();
+/// app::register(8080u16);
+/// let _ = app::get::<u16>();
+/// let _ = app::get::<u32>();
+///
+/// assert_eq!(events.count_of("get"), 2);
+/// assert_eq!(events.events_for_type::<u16>().len(), 3);
+/// events.assert_sequence(&["register", "get", "get"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct EventRecorder {
+    // Test-side observer rather than registry data: std types, see `crate::sync`.
+    events: Arc<Mutex<Vec<RegistryEvent>>>,
+}
+
+impl EventRecorder {
+    /// An empty recorder, not attached to any registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `event` to the log.
+    pub fn record(&self, event: &RegistryEvent) {
+        self.lock().push(event.clone());
+    }
+
+    /// Every recorded event, oldest first.
+    pub fn events(&self) -> Vec<RegistryEvent> {
+        self.lock().clone()
+    }
+
+    /// The operation names of the recorded events, oldest first.
+    pub fn ops(&self) -> Vec<&'static str> {
+        self.lock().iter().map(RegistryEvent::op).collect()
+    }
+
+    /// Number of recorded events of the operation `op`, e.g. `"register"`.
+    pub fn count_of(&self, op: &str) -> usize {
+        self.lock().iter().filter(|e| e.op() == op).count()
+    }
+
+    /// The recorded events about `T`, oldest first.
+    pub fn events_for_type<T: ?Sized + 'static>(&self) -> Vec<RegistryEvent> {
+        self.lock()
+            .iter()
+            .filter(|e| e.type_name() == Some(type_name::<T>()))
+            .cloned()
+            .collect()
+    }
+
+    /// Assert that the operations `expected` were recorded in this order.
+    ///
+    /// Other events may come before, after or in between, so a sequence such
+    /// as `["register", "get"]` does not have to spell out `register_completed`.
+    ///
+    /// # Panics
+    ///
+    /// If `expected` is not a subsequence of the recorded operations.
+    #[track_caller]
+    pub fn assert_sequence(&self, expected: &[&str]) {
+        let ops = self.ops();
+        let mut remaining = ops.iter();
+        let found = expected
+            .iter()
+            .all(|op| remaining.by_ref().any(|recorded| recorded == op));
+        assert!(
+            found,
+            "expected the events {expected:?} in this order, recorded {ops:?}"
+        );
+    }
+
+    /// Number of recorded events.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no event was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget the recorded events.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<RegistryEvent>> {
+        self.events.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+impl fmt::Debug for EventRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventRecorder").field(&self.ops()).finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, Registry};
+    use std::panic;
+
+    #[test]
+    fn test_record_events() {
+        define_registry!(reg);
+
+        let events = reg::record_events();
+        assert!(events.is_empty());
+
+        reg::register(1u8);
+        reg::register(2u16);
+        let _ = reg::contains::<u8>();
+        let _ = reg::remove::<u8>();
+
+        assert_eq!(
+            events.ops(),
+            [
+                "register",
+                "register_completed",
+                "register",
+                "register_completed",
+                "contains",
+                "remove"
+            ]
+        );
+        assert_eq!(events.count_of("register"), 2);
+        assert_eq!(events.events_for_type::<u8>().len(), 4);
+        events.assert_sequence(&["register", "contains", "remove"]);
+        assert!(panic::catch_unwind(|| events.assert_sequence(&["remove", "contains"])).is_err());
+
+        events.clear();
+        assert_eq!(events.len(), 0);
+    }
+
+    #[test]
+    fn test_record_from_a_runtime_registry() {
+        let events = EventRecorder::new();
+        let recorder = events.clone();
+        let registry = Registry::new();
+        registry.set_trace_callback(move |e| recorder.record(e));
+
+        registry.register(String::from("x"));
+        assert_eq!(events.events_for_type::<String>().len(), 2);
+        assert_eq!(
+            format!("{events:?}"),
+            r#"EventRecorder(["register", "register_completed"])"#
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Sampling and rate limiting of lookup events.
+//!
+//! Per-request `get`s make a registry's trace stream very chatty. With
+//! `set_event_sampling(EventSampling::one_in(n))` only every `n`-th lookup event
+//! (`Get`, `Contains`) reaches the trace callback and event subscribers, and
+//! `max_lookups_per_second` caps what is left. Mutations (`Register`, `Clear`,
+//! ...) are always emitted, so the log still explains how the registry changed.
+
+use std::time::{Duration, Instant};
+
+use crate::sync::Mutex;
+
+/// Which lookup events (`Get`, `Contains`) a registry emits.
+///
+/// Mutation events are never sampled. The default emits every event.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, EventSampling};
+///
+/// define_registry!(services);
+///
+/// // One in 100 lookups, and never more than 10 per second
+/// services::set_event_sampling(EventSampling::one_in(100).with_max_lookups_per_second(10));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventSampling {
+    /// Emit one in every `lookup_every` lookup events; `1` (or `0`) emits all of them.
+    pub lookup_every: u32,
+    /// Upper bound on lookup events emitted per second after sampling; `None` is unbounded.
+    pub max_lookups_per_second: Option<u32>,
+}
+
+impl EventSampling {
+    /// Emit every event.
+    pub const fn all() -> Self {
+        Self {
+            lookup_every: 1,
+            max_lookups_per_second: None,
+        }
+    }
+
+    /// Emit one in every `n` lookup events.
+    pub const fn one_in(n: u32) -> Self {
+        Self {
+            lookup_every: n,
+            max_lookups_per_second: None,
+        }
+    }
+
+    /// Additionally cap emitted lookup events at `max` per second.
+    pub const fn with_max_lookups_per_second(mut self, max: u32) -> Self {
+        self.max_lookups_per_second = Some(max);
+        self
+    }
+}
+
+impl Default for EventSampling {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SamplerState {
+    config: EventSampling,
+    seen: u64,
+    window_start: Option<Instant>,
+    window_emitted: u32,
+}
+
+/// Per-registry sampling decisions for lookup events.
+#[derive(Debug, Default)]
+pub(crate) struct EventSampler {
+    state: Mutex<SamplerState>,
+}
+
+impl EventSampler {
+    /// Replace the sampling configuration and restart counting.
+    pub(crate) fn configure(&self, config: EventSampling) {
+        *self.state.lock().unwrap_or_else(|p| p.into_inner()) = SamplerState {
+            config,
+            ..SamplerState::default()
+        };
+    }
+
+    /// The current sampling configuration.
+    pub(crate) fn config(&self) -> EventSampling {
+        self.state.lock().unwrap_or_else(|p| p.into_inner()).config
+    }
+
+    /// Whether the next lookup event should be emitted.
+    pub(crate) fn admit(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+
+        let every = u64::from(state.config.lookup_every.max(1));
+        let sampled = state.seen % every == 0;
+        state.seen = state.seen.wrapping_add(1);
+        if !sampled {
+            return false;
+        }
+
+        let Some(max) = state.config.max_lookups_per_second else {
+            return true;
+        };
+        let now = Instant::now();
+        match state.window_start {
+            Some(start) if now.duration_since(start) < Duration::from_secs(1) => {}
+            _ => {
+                state.window_start = Some(now);
+                state.window_emitted = 0;
+            }
+        }
+        if state.window_emitted >= max {
+            return false;
+        }
+        state.window_emitted += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_default_admits_everything() {
+        let sampler = EventSampler::default();
+        assert!((0..10).all(|_| sampler.admit()));
+    }
+
+    #[test]
+    fn test_one_in_n() {
+        let sampler = EventSampler::default();
+        sampler.configure(EventSampling::one_in(3));
+
+        let admitted: Vec<bool> = (0..7).map(|_| sampler.admit()).collect();
+        assert_eq!(admitted, [true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let sampler = EventSampler::default();
+        sampler.configure(EventSampling::all().with_max_lookups_per_second(2));
+
+        assert_eq!((0..5).filter(|_| sampler.admit()).count(), 2);
+    }
+
+    #[test]
+    fn test_mutations_bypass_sampling() {
+        define_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        reg::set_trace_callback(move |e| sink.lock().unwrap().push(e.to_string()));
+        reg::set_event_sampling(EventSampling::one_in(1000));
+        assert_eq!(reg::event_sampling(), EventSampling::one_in(1000));
+
+        reg::register(1u8);
+        for _ in 0..5 {
+            let _ = reg::get::<u8>();
+        }
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            [
+                "register { type_name: u8 }",
+                "register_completed { type_name: u8 }",
+                "get { type_name: u8, found: true }",
+            ]
+        );
+    }
+}
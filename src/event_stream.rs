@@ -0,0 +1,102 @@
+//! Channel-based event export (feature `tokio`).
+//!
+//! `subscribe_events(capacity)` hands out a bounded `tokio::sync::mpsc::Receiver`
+//! that receives every event also passed to the trace callback. Observability
+//! pipelines can then do their work in a task instead of inside the synchronous
+//! callback. Wrap the receiver in `tokio_stream::wrappers::ReceiverStream` for a
+//! `Stream`.
+//!
+//! Emitting never blocks a registry operation: when a subscriber's buffer is
+//! full the event is dropped for that subscriber. Closed receivers are pruned on
+//! the next event.
+
+use crate::sync::{mpsc, Mutex};
+use crate::RegistryEvent;
+
+/// Senders of every live event subscription.
+#[derive(Debug, Default)]
+pub(crate) struct EventSubscribers {
+    senders: Mutex<Vec<mpsc::Sender<RegistryEvent>>>,
+}
+
+impl EventSubscribers {
+    /// Add a subscription buffering up to `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub(crate) fn subscribe(&self, capacity: usize) -> mpsc::Receiver<RegistryEvent> {
+        let (tx, rx) = mpsc::channel(capacity);
+        self.senders
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(tx);
+        rx
+    }
+
+    /// Forward `event` to every subscriber without waiting.
+    pub(crate) fn send(&self, event: &RegistryEvent) {
+        let mut senders = self.senders.lock().unwrap_or_else(|p| p.into_inner());
+        if senders.is_empty() {
+            return;
+        }
+
+        senders.retain(|tx| match tx.try_send(event.clone()) {
+            Ok(()) | Err(mpsc::error::TrySendError::Full(_)) => true,
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+
+    #[tokio::test]
+    async fn test_receives_events() {
+        define_registry!(reg);
+
+        let mut events = reg::subscribe_events(16);
+        reg::register(1u8);
+        let _ = reg::get::<u8>();
+
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(events.recv().await.unwrap().to_string());
+        }
+        assert_eq!(
+            received,
+            vec![
+                "register { type_name: u8 }",
+                "register_completed { type_name: u8 }",
+                "get { type_name: u8, found: true }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_full_buffer_drops_instead_of_blocking() {
+        define_registry!(reg);
+
+        let mut events = reg::subscribe_events(1);
+        reg::register(1u8); // Register is buffered, RegisterCompleted dropped
+
+        assert_eq!(
+            events.try_recv().unwrap().to_string(),
+            "register { type_name: u8 }"
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_closed_subscribers_are_pruned() {
+        define_registry!(reg);
+
+        drop(reg::subscribe_events(4));
+        let mut live = reg::subscribe_events(4);
+        reg::register(1u8);
+
+        assert!(live.try_recv().is_ok());
+    }
+}
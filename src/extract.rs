@@ -0,0 +1,95 @@
+//! Registry extractor shared by the web framework integrations
+//! (features `axum` and `actix-web`).
+//!
+//! [`FromRegistry<T, R>`] resolves `T` from the registry `R` (the `Api` type
+//! generated by `define_registry!`) on every request, so handlers see
+//! replacements without restarting the server. Each enabled framework module
+//! implements its extractor traits for this one type.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{RegistryApi, RegistryError};
+
+/// Extractor resolving `T` from the registry `R`.
+///
+/// Dereferences to `T`; use [`into_inner`](FromRegistry::into_inner) for the `Arc`.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, FromRegistry};
+///
+/// define_registry!(services);
+///
+/// struct Mailer;
+///
+/// impl Mailer {
+///     fn send(&self, to: &str) -> String {
+///         format!("sent to {to}")
+///     }
+/// }
+///
+/// async fn handler(mailer: FromRegistry<Mailer, services::Api>) -> String {
+///     mailer.send("ops@example.com")
+/// }
+/// ```
+pub struct FromRegistry<T, R> {
+    value: Arc<T>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<T: Send + Sync + 'static, R: RegistryApi + Default> FromRegistry<T, R> {
+    /// Look up `T` in `R`.
+    pub(crate) fn resolve() -> Result<Self, RegistryError> {
+        R::default().get::<T>().map(|value| Self {
+            value,
+            _registry: PhantomData,
+        })
+    }
+}
+
+impl<T, R> FromRegistry<T, R> {
+    /// The extracted `Arc<T>`.
+    pub fn into_inner(self) -> Arc<T> {
+        self.value
+    }
+}
+
+impl<T, R> Deref for FromRegistry<T, R> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, R> Clone for FromRegistry<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, R> fmt::Debug for FromRegistry<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FromRegistry").field(&self.value).finish()
+    }
+}
+
+/// Rejection returned when the requested type cannot be resolved.
+///
+/// Responds with `500 Internal Server Error`: a missing singleton is a wiring
+/// bug, not a client error.
+#[derive(Debug)]
+pub struct RegistryRejection(pub RegistryError);
+
+impl fmt::Display for RegistryRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
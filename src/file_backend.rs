@@ -0,0 +1,140 @@
+//! File-backed persistence of serializable entries (feature `file-backend`).
+//!
+//! `enable_file_backend(path)` restores the values saved in `path`, then
+//! rewrites the file with a binary snapshot (see `export_snapshot`) after every
+//! registration, replacement, removal and clear. Each write goes to a
+//! temporary file that is synced and renamed over `path`, so a crash leaves
+//! either the previous or the new contents, never a torn file.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sync::Mutex;
+use crate::{RegistryApi, RegistryError};
+
+/// Per-registry backing file, disabled until `enable_file_backend` is called.
+#[derive(Debug, Default)]
+pub(crate) struct FileBackend {
+    // Checked on every mutation without taking the lock
+    enabled: AtomicBool,
+    path: Mutex<Option<PathBuf>>,
+}
+
+impl FileBackend {
+    pub(crate) fn disable(&self) {
+        let mut path = self.path.lock().unwrap_or_else(|p| p.into_inner());
+        self.enabled.store(false, Ordering::Release);
+        *path = None;
+    }
+}
+
+/// Restore the values saved in `path` into `api` and keep the file up to date.
+pub(crate) fn enable<R: RegistryApi + ?Sized>(api: &R, path: &Path) -> Result<(), RegistryError> {
+    match fs::read(path) {
+        Ok(bytes) => api.import_snapshot(&bytes)?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(RegistryError::from_source(
+                format!("reading {}", path.display()),
+                err,
+            ))
+        }
+    }
+
+    let backend = &R::state().file_backend;
+    let mut slot = backend.path.lock().unwrap_or_else(|p| p.into_inner());
+    write(api, path)?;
+    *slot = Some(path.to_path_buf());
+    backend.enabled.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Rewrite the backing file of `api`, if it has one, after a mutation.
+///
+/// Write errors are dropped: mutations never fail because of the backend.
+pub(crate) fn save<R: RegistryApi + ?Sized>(api: &R) {
+    let backend = &R::state().file_backend;
+    if !backend.enabled.load(Ordering::Acquire) {
+        return;
+    }
+    // Held while writing, so concurrent saves can't overwrite newer contents
+    let slot = backend.path.lock().unwrap_or_else(|p| p.into_inner());
+    if let Some(path) = slot.as_deref() {
+        let _ = write(api, path);
+    }
+}
+
+/// Atomically replace `path` with a snapshot of `api`.
+fn write<R: RegistryApi + ?Sized>(api: &R, path: &Path) -> Result<(), RegistryError> {
+    let snapshot = api.export_snapshot()?;
+
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(&snapshot)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    written.map_err(|err| RegistryError::from_source(format!("writing {}", path.display()), err))
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "singleton-registry-{}-{}.bin",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_values_survive_a_restart() {
+        let path = temp_path("restart");
+
+        define_registry!(first);
+        first::register_serializable(String::from("default"));
+        first::enable_file_backend(&path).unwrap();
+        first::register_serializable(String::from("changed"));
+        first::register_serializable(7u16);
+        first::disable_file_backend();
+        first::register_serializable(String::from("not saved"));
+
+        // A new process registers its defaults, then restores the saved values
+        define_registry!(second);
+        second::register_serializable(String::from("default"));
+        second::register_serializable(0u16);
+        second::enable_file_backend(&path).unwrap();
+
+        assert_eq!(*second::get::<String>().unwrap(), "changed");
+        assert_eq!(*second::get::<u16>().unwrap(), 7);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_removal_is_saved() {
+        let path = temp_path("removal");
+
+        define_registry!(first);
+        first::register_serializable(7u16);
+        first::enable_file_backend(&path).unwrap();
+        first::remove::<u16>().unwrap();
+
+        define_registry!(second);
+        second::register_serializable(String::from("kept"));
+        second::enable_file_backend(&path).unwrap();
+        assert!(!second::contains::<u16>().unwrap());
+        assert_eq!(*second::get::<String>().unwrap(), "kept");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
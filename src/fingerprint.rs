@@ -0,0 +1,86 @@
+//! Content checksums for drift detection.
+//!
+//! Replicas wired from the same build can compare a single `u64` instead of
+//! their full contents to find out whether their effective configuration
+//! diverged: a missing type, an extra replacement or, for serializable entries,
+//! a different value all change the fingerprint.
+
+use std::any::TypeId;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::RegistryEntry;
+
+/// Hash the types and generations of `entries`, regardless of their order.
+///
+/// `value` may add a serialized form of an entry's value to the hash. Type ids
+/// are only stable within one build, so fingerprints are only comparable
+/// between processes running the same binary.
+pub(crate) fn fingerprint(
+    mut entries: Vec<(TypeId, RegistryEntry)>,
+    value: impl Fn(&RegistryEntry) -> Option<String>,
+) -> u64 {
+    entries.sort_by_key(|(type_id, _)| *type_id);
+
+    let mut hasher = DefaultHasher::new();
+    entries.len().hash(&mut hasher);
+    for (type_id, entry) in &entries {
+        type_id.hash(&mut hasher);
+        entry.generation().hash(&mut hasher);
+        value(entry).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// The JSON form of an entry registered with `register_serializable`.
+#[cfg(feature = "serde")]
+pub(crate) fn json_value(entry: &RegistryEntry) -> Option<String> {
+    let shim = entry.serde_shim()?;
+    shim.serialize(&**entry.value())
+        .ok()
+        .map(|value| value.to_string())
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+
+    #[test]
+    fn test_same_content_same_fingerprint() {
+        define_registry!(a);
+        define_registry!(b);
+
+        a::register(1u8);
+        a::register("config");
+        b::register("config");
+        b::register(1u8);
+        assert_eq!(a::fingerprint(), b::fingerprint());
+
+        // Same value, but registered twice
+        b::register(1u8);
+        assert_ne!(a::fingerprint(), b::fingerprint());
+    }
+
+    #[test]
+    fn test_missing_type_changes_fingerprint() {
+        define_registry!(a);
+        define_registry!(b);
+
+        assert_eq!(a::fingerprint(), b::fingerprint());
+        a::register(1u8);
+        assert_ne!(a::fingerprint(), b::fingerprint());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_fingerprint_with_values() {
+        define_registry!(a);
+        define_registry!(b);
+
+        a::register_serializable(String::from("eu-west-1"));
+        b::register_serializable(String::from("us-east-1"));
+        assert_eq!(a::fingerprint(), b::fingerprint());
+        assert_ne!(a::fingerprint_with_values(), b::fingerprint_with_values());
+    }
+}
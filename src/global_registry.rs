@@ -0,0 +1,25 @@
+//! The process-wide registry of the `global` feature.
+//!
+//! An ordinary `define_registry!` registry named `global`, defined once by the
+//! crate so small applications don't have to. Its most common functions are
+//! also re-exported at the crate root.
+
+crate::define_registry!(global);
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::RegistryApi;
+
+    struct Greeting(&'static str);
+
+    #[test]
+    fn test_crate_root_functions_use_the_global_registry() {
+        crate::register(Greeting("hello"));
+
+        assert_eq!(crate::get::<Greeting>().unwrap().0, "hello");
+        assert!(crate::contains::<Greeting>().unwrap());
+        assert!(super::global::contains::<Greeting>().unwrap());
+        assert_eq!(crate::global::Api::name(), "global");
+    }
+}
@@ -0,0 +1,92 @@
+//! Crate-wide trace sink.
+//!
+//! `set_global_trace_sink(callback)` receives the events of every registry
+//! implementing [`RegistryApi`](crate::RegistryApi), in addition to their own
+//! trace callbacks, so one observability hook also covers registries defined
+//! in dependency crates. Use `event.registry()` to tell them apart.
+
+use std::sync::{Arc, Mutex};
+
+use crate::RegistryEvent;
+
+type Sink = Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>;
+
+// Wiring configuration rather than registry data: a std lock, see `crate::sync`.
+static SINK: Mutex<Sink> = Mutex::new(None);
+
+/// Send the events of every registry to `callback`, replacing any previous sink.
+///
+/// Events pass each registry's trace level and sampling first, then reach the
+/// registry's own callback and the global sink alike. The callback is invoked
+/// without any registry lock held.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, set_global_trace_sink};
+///
+/// define_registry!(services);
+/// define_registry!(plugins);
+///
+/// set_global_trace_sink(|event| println!("[{}] {event}", event.registry()));
+///
+/// services::register(1u32); // [services] register { type_name: u32 } ...
+/// plugins::register("x"); // [plugins] register { type_name: &str } ...
+/// ```
+pub fn set_global_trace_sink(callback: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
+    *SINK.lock().unwrap_or_else(|p| p.into_inner()) = Some(Arc::new(callback));
+}
+
+/// Remove the global trace sink.
+pub fn clear_global_trace_sink() {
+    *SINK.lock().unwrap_or_else(|p| p.into_inner()) = None;
+}
+
+/// Pass `event` to the global sink, if one is set.
+pub(crate) fn emit(event: &RegistryEvent) {
+    // The lock is released here, before the sink is invoked
+    let sink = SINK.lock().unwrap_or_else(|p| p.into_inner()).clone();
+    if let Some(sink) = sink {
+        sink(event);
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_global_sink_sees_every_registry() {
+        define_registry!(sink_a);
+        define_registry!(sink_b);
+
+        static SEEN: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+        set_global_trace_sink(|e| {
+            if e.registry().starts_with("sink_") {
+                SEEN.lock().unwrap().push(format!("{}: {e}", e.registry()));
+            }
+        });
+
+        let own = Arc::new(std::sync::Mutex::new(0));
+        let counter = own.clone();
+        sink_a::set_trace_callback(move |_| *counter.lock().unwrap() += 1);
+
+        sink_a::register(1u8);
+        let _ = sink_b::contains::<u8>();
+        clear_global_trace_sink();
+        let _ = sink_b::get::<u8>();
+
+        assert_eq!(
+            *SEEN.lock().unwrap(),
+            [
+                "sink_a: register { type_name: u8 }",
+                "sink_a: register_completed { type_name: u8 }",
+                "sink_b: contains { type_name: u8, found: false }",
+            ]
+        );
+        // The registry's own callback still runs
+        assert_eq!(*own.lock().unwrap(), 2);
+    }
+}
@@ -0,0 +1,277 @@
+//! Async health checks (feature `tokio`).
+//!
+//! Services implementing [`AsyncHealthCheck`] are registered with
+//! `register_health_checked`. `health_report_async(timeout).await` probes all of
+//! them concurrently, bounding each probe by `timeout`, and returns a
+//! [`HealthReport`] suitable for a `/healthz` endpoint.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinSet;
+
+use crate::registry_trait::lookup;
+use crate::sync::Mutex;
+use crate::RegistryApi;
+
+/// A service that can probe its own health, driven by `health_report_async`.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, AsyncHealthCheck};
+/// use std::time::Duration;
+///
+/// define_registry!(services);
+///
+/// struct Db;
+///
+/// impl AsyncHealthCheck for Db {
+///     async fn check(&self) -> Result<(), String> {
+///         Ok(()) // e.g. `SELECT 1`
+///     }
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+/// services::register_health_checked(Db);
+///
+/// let report = services::health_report_async(Duration::from_secs(1)).await;
+/// assert!(report.is_healthy());
+/// # });
+/// ```
+pub trait AsyncHealthCheck: Send + Sync + 'static {
+    /// Probe the service; `Err` carries a human-readable reason.
+    fn check(&self) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+/// Result of a single probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The probe succeeded.
+    Healthy,
+    /// The probe failed with the given reason.
+    Unhealthy(String),
+    /// The probe did not finish within the timeout.
+    TimedOut,
+}
+
+/// Outcome of probing one service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheckResult {
+    /// The type name of the service
+    pub type_name: &'static str,
+    /// The probe's result
+    pub status: HealthStatus,
+    /// How long the probe took (capped at the timeout)
+    pub elapsed: Duration,
+}
+
+/// Outcome of `health_report_async`, one entry per probed service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Probe results in enrollment order.
+    pub checks: Vec<HealthCheckResult>,
+}
+
+impl HealthReport {
+    /// Whether every probed service is healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|c| c.status == HealthStatus::Healthy)
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            if self.is_healthy() {
+                "healthy"
+            } else {
+                "unhealthy"
+            }
+        )?;
+        for check in &self.checks {
+            write!(f, "\n  {}: ", check.type_name)?;
+            match &check.status {
+                HealthStatus::Healthy => write!(f, "ok")?,
+                HealthStatus::Unhealthy(reason) => write!(f, "unhealthy ({})", reason)?,
+                HealthStatus::TimedOut => write!(f, "timed out")?,
+            }
+            write!(f, " in {:?}", check.elapsed)?;
+        }
+        Ok(())
+    }
+}
+
+type BoxedCheck = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// An enrolled probe: builds the check future for the current value of its type.
+struct HealthProbe {
+    type_id: TypeId,
+    type_name: &'static str,
+    start: Box<dyn Fn() -> Option<BoxedCheck> + Send + Sync>,
+}
+
+/// Enrolled probes in registration order, one per type.
+#[derive(Default)]
+pub(crate) struct HealthProbes {
+    probes: Mutex<Vec<HealthProbe>>,
+}
+
+impl fmt::Debug for HealthProbes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let probes = self.probes.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_list()
+            .entries(probes.iter().map(|p| p.type_name))
+            .finish()
+    }
+}
+
+impl HealthProbes {
+    /// Enroll `T` in `R`, unless it is already enrolled.
+    pub(crate) fn add<R: RegistryApi + ?Sized, T: AsyncHealthCheck>(&self) {
+        let mut probes = self.probes.lock().unwrap_or_else(|p| p.into_inner());
+        if probes.iter().any(|p| p.type_id == TypeId::of::<T>()) {
+            return;
+        }
+
+        probes.push(HealthProbe {
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            start: Box::new(|| {
+                let value: Arc<T> = lookup::<R, T>()?;
+                Some(Box::pin(async move { value.check().await }))
+            }),
+        });
+    }
+
+    /// Probe every enrolled service that is still registered, each bounded by `timeout`.
+    pub(crate) async fn run(&self, timeout: Duration) -> HealthReport {
+        let (names, checks): (Vec<&'static str>, Vec<BoxedCheck>) = {
+            let probes = self.probes.lock().unwrap_or_else(|p| p.into_inner());
+            probes
+                .iter()
+                .filter_map(|p| (p.start)().map(|fut| (p.type_name, fut)))
+                .unzip()
+        };
+
+        let mut tasks = JoinSet::new();
+        let mut order = HashMap::new();
+        for (index, check) in checks.into_iter().enumerate() {
+            let task = tasks.spawn(async move {
+                let start = Instant::now();
+                let status = match tokio::time::timeout(timeout, check).await {
+                    Ok(Ok(())) => HealthStatus::Healthy,
+                    Ok(Err(reason)) => HealthStatus::Unhealthy(reason),
+                    Err(_) => HealthStatus::TimedOut,
+                };
+                (status, start.elapsed())
+            });
+            order.insert(task.id(), index);
+        }
+
+        let mut outcomes = vec![None; names.len()];
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (index, outcome) = match joined {
+                Ok((id, outcome)) => (order[&id], outcome),
+                Err(err) => (
+                    order[&err.id()],
+                    (
+                        HealthStatus::Unhealthy("health check panicked".into()),
+                        Duration::ZERO,
+                    ),
+                ),
+            };
+            outcomes[index] = Some(outcome);
+        }
+
+        HealthReport {
+            checks: names
+                .into_iter()
+                .zip(outcomes)
+                .map(|(type_name, outcome)| {
+                    let (status, elapsed) = outcome.expect("every probe task is joined");
+                    HealthCheckResult {
+                        type_name,
+                        status,
+                        elapsed,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    struct Db;
+
+    impl AsyncHealthCheck for Db {
+        async fn check(&self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    struct Cache;
+
+    impl AsyncHealthCheck for Cache {
+        async fn check(&self) -> Result<(), String> {
+            Err("connection refused".into())
+        }
+    }
+
+    struct Slow;
+
+    impl AsyncHealthCheck for Slow {
+        async fn check(&self) -> Result<(), String> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_in_enrollment_order() {
+        define_registry!(reg);
+
+        reg::register_health_checked(Slow);
+        reg::register_health_checked(Db);
+        reg::register_health_checked(Cache);
+
+        let report = reg::health_report_async(Duration::from_millis(50)).await;
+        let statuses: Vec<_> = report.checks.iter().map(|c| c.status.clone()).collect();
+
+        assert_eq!(
+            statuses,
+            vec![
+                HealthStatus::TimedOut,
+                HealthStatus::Healthy,
+                HealthStatus::Unhealthy("connection refused".into()),
+            ]
+        );
+        assert!(!report.is_healthy());
+        assert!(report.to_string().starts_with("unhealthy"));
+    }
+
+    #[tokio::test]
+    async fn test_healthy_report() {
+        define_registry!(reg);
+
+        reg::register_health_checked(Db);
+        let report = reg::health_report_async(Duration::from_secs(1)).await;
+
+        assert!(report.is_healthy());
+        assert_eq!(report.checks[0].type_name, std::any::type_name::<Db>());
+    }
+}
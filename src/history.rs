@@ -0,0 +1,184 @@
+//! Bounded history of replaced values.
+//!
+//! When a configuration hot-swap breaks production, the value that was there
+//! before is the first thing to look at. `enable_history::<T>(n)` keeps the last
+//! `n` values a registration of `T` replaced, newest first, readable through
+//! `get_previous::<T>(k)`.
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use crate::sync::Mutex;
+use crate::RegistryEntry;
+
+/// The retained entries of one type, newest first.
+#[derive(Default)]
+struct TypeHistory {
+    depth: usize,
+    entries: VecDeque<RegistryEntry>,
+}
+
+/// Replaced entries per type, for the types with history enabled.
+#[derive(Default)]
+pub(crate) struct History {
+    table: Mutex<HashMap<TypeId, TypeHistory>>,
+}
+
+impl std::fmt::Debug for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("History")
+            .field("types", &table.len())
+            .finish()
+    }
+}
+
+impl History {
+    /// Keep the last `depth` replaced entries of `type_id`; `0` disables the
+    /// history. Returns the entries that no longer fit, to be dropped without
+    /// the lock held.
+    pub(crate) fn enable(&self, type_id: TypeId, depth: usize) -> Vec<RegistryEntry> {
+        let mut table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        if depth == 0 {
+            return table
+                .remove(&type_id)
+                .map(|history| history.entries.into())
+                .unwrap_or_default();
+        }
+        let history = table.entry(type_id).or_default();
+        history.depth = depth;
+        history
+            .entries
+            .split_off(depth.min(history.entries.len()))
+            .into()
+    }
+
+    /// Retain `replaced` if history is enabled for its type.
+    ///
+    /// Returns the entry to drop: `replaced` itself without history, otherwise
+    /// the oldest entry if the history was full.
+    pub(crate) fn record(&self, type_id: TypeId, replaced: RegistryEntry) -> Option<RegistryEntry> {
+        let mut table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(history) = table.get_mut(&type_id) else {
+            return Some(replaced);
+        };
+        history.entries.push_front(replaced);
+        if history.entries.len() > history.depth {
+            history.entries.pop_back()
+        } else {
+            None
+        }
+    }
+
+    /// Take the most recently replaced entry of `type_id`.
+    pub(crate) fn pop(&self, type_id: TypeId) -> Option<RegistryEntry> {
+        let mut table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        table.get_mut(&type_id)?.entries.pop_front()
+    }
+
+    /// Put back an entry taken with [`pop`](History::pop).
+    pub(crate) fn restore(&self, type_id: TypeId, entry: RegistryEntry) {
+        let mut table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(history) = table.get_mut(&type_id) {
+            history.entries.push_front(entry);
+        }
+    }
+
+    /// The value replaced `k + 1` registrations ago.
+    pub(crate) fn get(&self, type_id: TypeId, k: usize) -> Option<Arc<dyn Any + Send + Sync>> {
+        let table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        table
+            .get(&type_id)
+            .and_then(|history| history.entries.get(k))
+            .map(|entry| entry.value().clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, Metadata, RegistryError, RegistryEvent};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_get_previous() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        reg::register(2u8);
+        assert_eq!(reg::get_previous::<u8>(0), None);
+
+        reg::enable_history::<u8>(2);
+        reg::register(3u8);
+        reg::register(4u8);
+        reg::register(5u8);
+        assert_eq!(*reg::get::<u8>().unwrap(), 5);
+        assert_eq!(reg::get_previous::<u8>(0).as_deref(), Some(&4));
+        assert_eq!(reg::get_previous::<u8>(1).as_deref(), Some(&3));
+        assert_eq!(reg::get_previous::<u8>(2), None);
+        assert_eq!(reg::get_previous::<u16>(0), None);
+    }
+
+    #[test]
+    fn test_shrinking_and_disabling() {
+        define_registry!(reg);
+
+        reg::enable_history::<u8>(3);
+        for value in 0..4u8 {
+            reg::register(value);
+        }
+        reg::enable_history::<u8>(1);
+        assert_eq!(reg::get_previous::<u8>(0).as_deref(), Some(&2));
+        assert_eq!(reg::get_previous::<u8>(1), None);
+
+        reg::enable_history::<u8>(0);
+        reg::register(9u8);
+        assert_eq!(reg::get_previous::<u8>(0), None);
+    }
+
+    #[test]
+    fn test_revert() {
+        define_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = events.clone();
+        reg::set_trace_callback(move |event| {
+            if let RegistryEvent::RegisterCompleted { rollback, .. } = event {
+                seen.lock().unwrap().push(*rollback);
+            }
+        });
+
+        reg::enable_history::<String>(3);
+        reg::register_with_meta("v1".to_string(), Metadata::new("first"));
+        reg::register("v2".to_string());
+        reg::register("v3".to_string());
+
+        assert_eq!(*reg::revert::<String>().unwrap(), "v2");
+        assert_eq!(*reg::revert::<String>().unwrap(), "v1");
+        assert_eq!(*reg::get::<String>().unwrap(), "v1");
+        assert_eq!(reg::metadata::<String>().unwrap().description, "first");
+        assert_eq!(
+            reg::revert::<String>().unwrap_err(),
+            RegistryError::NoPreviousValue {
+                type_name: "alloc::string::String"
+            }
+        );
+        assert_eq!(*events.lock().unwrap(), [false, false, false, true, true]);
+    }
+
+    #[test]
+    fn test_revert_keeps_history_of_final_types() {
+        define_registry!(reg);
+
+        reg::enable_history::<u8>(2);
+        reg::register(1u8);
+        reg::register_final(2u8).unwrap();
+
+        assert_eq!(
+            reg::revert::<u8>().unwrap_err(),
+            RegistryError::FinalType { type_name: "u8" }
+        );
+        assert_eq!(reg::get_previous::<u8>(0).as_deref(), Some(&1));
+    }
+}
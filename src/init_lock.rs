@@ -0,0 +1,109 @@
+//! Per-type initialization locks for `get_or_register`.
+//!
+//! Two threads racing `get_or_register::<T>(factory)` must not both run their
+//! factories. Each type gets its own lock, so the loser blocks until the winner
+//! has registered its value and then receives it, while initializations of
+//! other types proceed in parallel.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::sync::Mutex;
+
+/// Initialization locks per type, created on first use.
+#[derive(Debug, Default)]
+pub(crate) struct InitLocks {
+    locks: Mutex<HashMap<TypeId, Arc<Mutex<()>>>>,
+}
+
+impl InitLocks {
+    /// The initialization lock for `type_id`.
+    pub(crate) fn for_type(&self, type_id: TypeId) -> Arc<Mutex<()>> {
+        self.locks
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(type_id)
+            .or_default()
+            .clone()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_returns_existing_value() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        let value = reg::get_or_register(|| -> u8 { panic!("must not run") }).unwrap();
+        assert_eq!(*value, 1);
+    }
+
+    #[test]
+    fn test_registers_factory_result() {
+        define_registry!(reg);
+
+        let value = reg::get_or_register(|| "built".to_string()).unwrap();
+        assert_eq!(&*value, "built");
+        assert!(Arc::ptr_eq(&value, &reg::get::<String>().unwrap()));
+    }
+
+    #[test]
+    fn test_racing_callers_run_one_factory() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let barrier = Arc::new(Barrier::new(8));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    reg::get_or_register(move || {
+                        CALLS.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        i as u64
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+        let values: Vec<Arc<u64>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+        assert!(values.iter().all(|v| Arc::ptr_eq(v, &values[0])));
+    }
+
+    #[test]
+    fn test_panicking_factory_does_not_wedge_type() {
+        define_registry!(reg);
+
+        let result = thread::spawn(|| reg::get_or_register(|| -> u32 { panic!("boom") })).join();
+        assert!(result.is_err());
+
+        assert_eq!(*reg::get_or_register(|| 5u32).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_append_only_returns_registered_value() {
+        define_registry!(reg, { registration: AppendOnly });
+
+        // The factory registers `T` itself, so storing its result is rejected
+        let value = reg::get_or_register(|| {
+            reg::register(1u16);
+            2u16
+        })
+        .unwrap();
+        assert_eq!(*value, 1);
+        assert!(Arc::ptr_eq(&value, &reg::get::<u16>().unwrap()));
+    }
+}
@@ -0,0 +1,309 @@
+//! Synchronous lazy providers.
+//!
+//! `register_lazy::<T>(factory)` wires an expensive singleton at startup without
+//! constructing it. The factory runs once, on the first `get::<T>()`, outside the
+//! storage lock; concurrent callers block on the same `OnceLock` until it is done.
+//! The result is then registered like any other value.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::panic::Location;
+use std::sync::{Arc, OnceLock};
+
+use crate::registry_trait::{store_entry, RegistryCore};
+use crate::sync::Mutex;
+use crate::RegistryEntry;
+
+type BoxedFactory<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// Resolves a type-erased `Lazy<T>`, returning the value and, if this call
+/// built it, the entry to register.
+type ResolveFn =
+    fn(&(dyn Any + Send + Sync)) -> (Arc<dyn Any + Send + Sync>, Option<RegistryEntry>);
+
+/// A pending provider for `T`.
+struct Lazy<T> {
+    cell: OnceLock<Arc<T>>,
+    factory: Mutex<Option<BoxedFactory<T>>>,
+}
+
+impl<T: Send + Sync + 'static> Lazy<T> {
+    /// Resolve the value, running the factory if no caller has done so yet.
+    ///
+    /// Returns the value and whether this call was the one that built it.
+    fn resolve(&self) -> (Arc<T>, bool) {
+        let mut built_here = false;
+        let value = self
+            .cell
+            .get_or_init(|| {
+                let factory = self
+                    .factory
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .take()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "lazy factory for {} panicked on an earlier access",
+                            std::any::type_name::<T>()
+                        )
+                    });
+                built_here = true;
+                Arc::new(factory())
+            })
+            .clone();
+        (value, built_here)
+    }
+}
+
+fn resolve_as<T: Send + Sync + 'static>(
+    lazy: &(dyn Any + Send + Sync),
+) -> (Arc<dyn Any + Send + Sync>, Option<RegistryEntry>) {
+    let lazy = lazy
+        .downcast_ref::<Lazy<T>>()
+        .expect("lazy provider stored under its own TypeId");
+    let (value, built_here) = lazy.resolve();
+    let entry = built_here.then(|| RegistryEntry::new(value.clone()));
+    (value, entry)
+}
+
+/// A pending provider, stored type-erased as `Arc<Lazy<T>>`.
+#[derive(Clone)]
+struct Provider {
+    lazy: Arc<dyn Any + Send + Sync>,
+    resolve: ResolveFn,
+    /// The `register_lazy` call, reported as the built value's registration site.
+    location: &'static Location<'static>,
+}
+
+/// Pending providers per type.
+#[derive(Default)]
+pub(crate) struct LazyProviders {
+    providers: Mutex<HashMap<TypeId, Provider>>,
+}
+
+impl std::fmt::Debug for LazyProviders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let providers = self.providers.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("LazyProviders")
+            .field("pending", &providers.len())
+            .finish()
+    }
+}
+
+impl LazyProviders {
+    /// Install a provider for `T` registered at `location`, replacing any pending one.
+    pub(crate) fn insert<T: Send + Sync + 'static>(
+        &self,
+        factory: impl FnOnce() -> T + Send + 'static,
+        location: &'static Location<'static>,
+    ) {
+        let lazy = Lazy {
+            cell: OnceLock::new(),
+            factory: Mutex::new(Some(Box::new(factory))),
+        };
+
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(
+                TypeId::of::<T>(),
+                Provider {
+                    lazy: Arc::new(lazy),
+                    resolve: resolve_as::<T>,
+                    location,
+                },
+            );
+    }
+
+    /// Whether a provider is pending for `type_id`.
+    pub(crate) fn contains(&self, type_id: TypeId) -> bool {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .contains_key(&type_id)
+    }
+
+    /// Drop the pending provider for `type_id`, if any.
+    ///
+    /// Called on every registration: an explicitly registered value supersedes a
+    /// provider that has not run yet.
+    pub(crate) fn discard(&self, type_id: TypeId) {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&type_id);
+    }
+
+    /// Drop every pending provider, so a cleared registry doesn't rebuild values.
+    pub(crate) fn clear(&self) {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clear();
+    }
+
+    fn get(&self, type_id: TypeId) -> Option<Provider> {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&type_id)
+            .cloned()
+    }
+}
+
+/// Build the value of `type_id` from its pending provider in `api`, registering
+/// the result.
+///
+/// Returns `None` if no provider is pending for the type. Must be called without
+/// the storage lock held.
+pub(crate) fn resolve_erased<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+) -> Option<Arc<dyn Any + Send + Sync>> {
+    let providers = &api.core_state().lazy;
+    let provider = providers.get(type_id)?;
+    let (value, entry) = (provider.resolve)(&*provider.lazy);
+
+    // Only the builder registers, and only if nothing superseded the provider meanwhile
+    if let Some(entry) = entry {
+        if providers
+            .get(type_id)
+            .is_some_and(|p| Arc::ptr_eq(&p.lazy, &provider.lazy))
+        {
+            let _ = store_entry(api, type_id, entry.at(provider.location), false);
+        }
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_factory_runs_once_on_first_access() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        reg::register_lazy(|| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            "expensive".to_string()
+        });
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+        assert!(reg::contains::<String>().unwrap());
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+        assert_eq!(&*reg::get::<String>().unwrap(), "expensive");
+        assert_eq!(reg::get_cloned::<String>().unwrap(), "expensive");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_concurrent_first_access_builds_once() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        reg::register_lazy(|| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(std::time::Duration::from_millis(20));
+            7u64
+        });
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| thread::spawn(|| reg::get::<u64>().unwrap()))
+            .collect();
+        let values: Vec<Arc<u64>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+        assert!(values.iter().all(|v| Arc::ptr_eq(v, &values[0])));
+    }
+
+    #[test]
+    fn test_explicit_register_supersedes_pending_provider() {
+        define_registry!(reg);
+
+        reg::register_lazy(|| -> u32 { panic!("must not run") });
+        reg::register(2u32);
+
+        assert_eq!(*reg::get::<u32>().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_final_type_rejects_provider() {
+        define_registry!(reg);
+
+        reg::register_final(1i8).unwrap();
+        reg::register_lazy(|| -> i8 { panic!("must not run") });
+
+        assert_eq!(*reg::get::<i8>().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_provider_replaces_registered_value() {
+        define_registry!(reg);
+
+        reg::register(1u32);
+        reg::register_lazy(|| 2u32);
+
+        assert_eq!(*reg::get::<u32>().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_protected_and_append_only_reject_provider() {
+        define_registry!(reg);
+        define_registry!(append_only, { registration: AppendOnly });
+
+        reg::register(1u8);
+        reg::protect::<u8>().unwrap();
+        reg::register_lazy(|| -> u8 { panic!("must not run") });
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+
+        append_only::register(1u8);
+        append_only::register_lazy(|| -> u8 { panic!("must not run") });
+        assert_eq!(*append_only::get::<u8>().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_factory_can_use_registry() {
+        define_registry!(reg);
+
+        reg::register(3usize);
+        reg::register_lazy(|| vec![0u8; *reg::get::<usize>().unwrap()]);
+
+        assert_eq!(reg::get::<Vec<u8>>().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_built_value_reports_register_lazy_call() {
+        define_registry!(reg);
+
+        let line = line!() + 1;
+        reg::register_lazy(|| 1u8);
+        assert_eq!(reg::registered_at::<u8>(), None);
+
+        let _ = reg::get::<u8>();
+        let location = reg::registered_at::<u8>().unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), line));
+    }
+
+    #[test]
+    fn test_clear_drops_pending_provider() {
+        define_registry!(reg);
+
+        reg::register_lazy(|| -> u32 { panic!("must not run") });
+        reg::clear();
+
+        assert!(!reg::contains::<u32>().unwrap());
+        assert!(reg::get::<u32>().is_err());
+
+        reg::register_lazy(|| -> u16 { panic!("must not run") });
+        reg::clear_with_leak_report();
+        assert!(!reg::contains::<u16>().unwrap());
+    }
+}
@@ -0,0 +1,199 @@
+//! Async lazy providers (features `async` and `tokio`).
+//!
+//! `register_lazy_async::<T>(factory)` wires a service whose construction needs
+//! I/O without running it. The factory's future runs on the first
+//! `get_async::<T>()`; concurrent callers await the same in-flight initialization
+//! through an async `OnceCell` instead of racing each other. Once built, the
+//! value is registered like any other, so plain `get()` sees it too.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::sync::{AsyncOnceCell, Mutex};
+
+type BoxedFactory<T> = Box<dyn Fn() -> Pin<Box<dyn Future<Output = T> + Send>> + Send + Sync>;
+
+/// A pending async provider for `T`.
+pub(crate) struct AsyncLazy<T> {
+    cell: AsyncOnceCell<Arc<T>>,
+    factory: BoxedFactory<T>,
+}
+
+impl<T: Send + Sync + 'static> AsyncLazy<T> {
+    /// Resolve the value, running the factory if no caller has done so yet.
+    ///
+    /// Returns the value and whether this call was the one that built it.
+    pub(crate) async fn resolve(&self) -> (Arc<T>, bool) {
+        let mut built_here = false;
+        let value = self
+            .cell
+            .get_or_init(|| async {
+                built_here = true;
+                Arc::new((self.factory)().await)
+            })
+            .await
+            .clone();
+        (value, built_here)
+    }
+}
+
+/// Pending async providers per type, stored type-erased as `Arc<AsyncLazy<T>>`.
+#[derive(Default)]
+pub(crate) struct AsyncLazyProviders {
+    providers: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl std::fmt::Debug for AsyncLazyProviders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let providers = self.providers.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("AsyncLazyProviders")
+            .field("pending", &providers.len())
+            .finish()
+    }
+}
+
+impl AsyncLazyProviders {
+    /// Install a provider for `T`, replacing any pending one.
+    pub(crate) fn insert<T, F, Fut>(&self, factory: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let lazy = AsyncLazy {
+            cell: AsyncOnceCell::new(),
+            factory: Box::new(move || Box::pin(factory())),
+        };
+
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(TypeId::of::<T>(), Arc::new(lazy));
+    }
+
+    /// The pending provider for `T`, if any.
+    pub(crate) fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<AsyncLazy<T>>> {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|p| p.downcast::<AsyncLazy<T>>().ok())
+    }
+
+    /// Whether `lazy` is still the pending provider for `T`.
+    pub(crate) fn is_current<T: Send + Sync + 'static>(&self, lazy: &Arc<AsyncLazy<T>>) -> bool {
+        self.get::<T>().is_some_and(|p| Arc::ptr_eq(&p, lazy))
+    }
+
+    /// Drop the pending provider for `type_id`, if any.
+    ///
+    /// Called on every registration: an explicitly registered value supersedes a
+    /// provider that has not run yet.
+    pub(crate) fn discard(&self, type_id: TypeId) {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&type_id);
+    }
+
+    /// Drop every pending provider, so a cleared registry doesn't rebuild values.
+    pub(crate) fn clear(&self) {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_factory_runs_on_first_get_async() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        reg::register_lazy_async(|| async {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            "connected".to_string()
+        });
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+        assert!(!reg::contains::<String>().unwrap());
+
+        assert_eq!(&*reg::get_async::<String>().await.unwrap(), "connected");
+        assert_eq!(&*reg::get_async::<String>().await.unwrap(), "connected");
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        // The built value is registered, so synchronous lookups see it too
+        assert_eq!(&*reg::get::<String>().unwrap(), "connected");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_callers_share_initialization() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        reg::register_lazy_async(|| async {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            42u64
+        });
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| tokio::spawn(reg::get_async::<u64>()))
+            .collect();
+
+        let mut values = Vec::new();
+        for handle in handles {
+            values.push(handle.await.unwrap().unwrap());
+        }
+
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+        assert!(values.iter().all(|v| Arc::ptr_eq(v, &values[0])));
+    }
+
+    #[tokio::test]
+    async fn test_explicit_register_supersedes_pending_provider() {
+        define_registry!(reg);
+
+        reg::register_lazy_async(|| async { 1u32 });
+        reg::register(2u32);
+
+        assert_eq!(*reg::get_async::<u32>().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_clear_drops_pending_provider() {
+        define_registry!(reg);
+
+        reg::register_lazy_async(|| async { 1u32 });
+        reg::clear();
+
+        assert!(reg::get_async::<u32>().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_async_without_provider() {
+        define_registry!(reg);
+
+        assert!(matches!(
+            reg::get_async::<u16>().await.unwrap_err(),
+            RegistryError::TypeNotFound {
+                type_name: "u16",
+                ..
+            }
+        ));
+
+        reg::register(7u16);
+        assert_eq!(*reg::get_async::<u16>().await.unwrap(), 7);
+    }
+}
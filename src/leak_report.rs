@@ -0,0 +1,160 @@
+//! Reports produced on teardown: what was cleared, and what outlived it.
+
+use std::fmt;
+
+/// A registered value that was still referenced outside the registry when it was cleared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakedEntry {
+    /// The type name of the value
+    pub type_name: &'static str,
+    /// Number of `Arc` handles still alive outside the registry
+    pub outstanding: usize,
+}
+
+/// Entries that outlived a registry teardown.
+///
+/// Returned by `clear_with_leak_report()`. Tests can assert `is_empty()` to verify
+/// that no stale handles survive teardown.
+///
+/// # Examples
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// app::register(1u32);
+/// let held = app::get::<u32>().unwrap();
+///
+/// let report = app::clear_with_leak_report();
+/// assert_eq!(report.entries.len(), 1);
+/// assert_eq!(report.entries[0].outstanding, 1);
+///
+/// drop(held);
+/// assert!(app::clear_with_leak_report().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeakReport {
+    /// Entries whose values were still referenced, in no particular order
+    pub entries: Vec<LeakedEntry>,
+}
+
+impl LeakReport {
+    /// Returns `true` if no entry outlived the teardown.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.entries.is_empty() {
+            return write!(f, "no outstanding references");
+        }
+
+        write!(
+            f,
+            "{} entries with outstanding references:",
+            self.entries.len()
+        )?;
+        for entry in &self.entries {
+            write!(f, " {} ({})", entry.type_name, entry.outstanding)?;
+        }
+        Ok(())
+    }
+}
+
+/// The entries removed by `clear()` or `clear_all(force)`.
+///
+/// Lets test teardown and admin tooling log exactly what was wiped. Each
+/// removed entry is also announced with a `RegistryEvent::Remove` trace event.
+///
+/// # Examples
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// app::register(1u32);
+/// app::register(String::from("x"));
+///
+/// let report = app::clear();
+/// assert_eq!(report.removed, ["alloc::string::String", "u32"]);
+/// assert!(app::clear().is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClearReport {
+    /// Type names of the removed entries, sorted
+    pub removed: Vec<&'static str>,
+}
+
+impl ClearReport {
+    /// Number of removed entries.
+    pub fn len(&self) -> usize {
+        self.removed.len()
+    }
+
+    /// Returns `true` if there was nothing to clear.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for ClearReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.removed.is_empty() {
+            return write!(f, "nothing to clear");
+        }
+        write!(
+            f,
+            "cleared {} entries: {}",
+            self.removed.len(),
+            self.removed.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_clear_report() {
+        assert_eq!(ClearReport::default().to_string(), "nothing to clear");
+        let report = ClearReport {
+            removed: vec!["i32", "u8"],
+        };
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.to_string(), "cleared 2 entries: i32, u8");
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert_eq!(
+            LeakReport::default().to_string(),
+            "no outstanding references"
+        );
+    }
+
+    #[test]
+    fn test_display_entries() {
+        let report = LeakReport {
+            entries: vec![
+                LeakedEntry {
+                    type_name: "i32",
+                    outstanding: 2,
+                },
+                LeakedEntry {
+                    type_name: "u8",
+                    outstanding: 1,
+                },
+            ],
+        };
+        assert!(!report.is_empty());
+        assert_eq!(
+            report.to_string(),
+            "2 entries with outstanding references: i32 (2) u8 (1)"
+        );
+    }
+}
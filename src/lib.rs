@@ -50,15 +50,27 @@
 //! - [`RegistryApi`] - Trait defining registry operations (for advanced usage)
 //! - [`RegistryEvent`] - Events emitted during operations (for tracing)
 //! - [`RegistryError`] - Error type for registry operations
+//! - [`RegistryEntry`] - A stored value with its bookkeeping data (for manual implementations)
+//! - [`RegistryState`] - Auxiliary per-registry state (for manual implementations)
 //!
 //! ## Tracing
 //!
 //! The tracing callback system allows you to monitor registry operations:
 //!
 //! - Callbacks are invoked for `register`, `get`, and `contains` operations
-//! - Callbacks survive registry `clear()` operations (test-only method)
+//! - Callbacks survive registry `clear()` operations (test-only method, feature `test-util`)
 //! - Use `clear_trace_callback()` to remove the callback
-//! - If a callback panics, the panic propagates (registry lock is not held)
+//! - Use `set_event_sampling(EventSampling::one_in(n))` to emit only every n-th
+//!   `Get`/`Contains` event on chatty registries; mutations are always emitted
+//! - Use `set_trace_level(EventLevel::Info)` to drop routine events below a
+//!   severity; see [`RegistryEvent::level`]
+//! - Wrap work in `with_trace_context("request-42", || ...)` (or futures in
+//!   `with_trace_context_async`) to attach a correlation id to every event's `meta`
+//! - Use `set_global_trace_sink(callback)` to receive the events of every
+//!   registry, including those defined in dependency crates
+//! - For the typed new value, use `on_change::<T>(|value| ...)` listeners instead
+//! - If a callback panics, the panic propagates (registry lock is not held);
+//!   `set_callback_panic_policy` can catch it and drop the event instead
 //!
 //! ## Error Handling
 //!
@@ -67,19 +79,248 @@
 //! - `TypeNotFound` - The requested type is not registered
 //! - `TypeMismatch` - Internal type mismatch (should never happen)
 //! - `RegistryLock` - Lock poisoning (automatically recovered)
+//! - `Timeout` - `wait_for_blocking` gave up waiting for a registration
+//! - `FinalType` - The type was registered with `register_final` and cannot be replaced
+//! - `DependencyCycle` - `resolve` found a type among its own providers' dependencies
+//! - `ProvidedAsync` - `resolve` hit a provider that only `resolve_async` can run
+//! - `MissingDependencies` - `resolve_all` lists every dependency it could not find
+//! - `NoPreviousValue` - `revert` found no replaced value in the type's history
+//! - `ProtectedType` - The type was marked with `protect` and cannot be replaced or removed
+//! - `AlreadyRegistered` - The type already has a value in an append-only registry
+//! - `StillShared` - `take_owned` found other handles to the value
+//! - `MergeConflict` - `merge_from` with `ConflictPolicy::Error` found types registered on both sides
+//! - `Custom` - A provider's own failure, or any error wrapped with `with_context`
 //!
 //! Lock poisoning is automatically recovered by extracting the inner value.
 //! This is safe because registry operations are idempotent.
+//!
+//! ## Cargo Features
+//!
+//! - `contention-stats` - Record lock wait counts and cumulative wait time,
+//!   queryable via `contention_stats()`
+//! - `stats` - Per-type `get` hit/miss counters via `stats::<T>()` and `stats_all()`
+//! - `audit` - `enable_audit_log(path)` appends every registration, replacement,
+//!   removal and clear to a JSON-lines file, with a configurable `FsyncPolicy`
+//! - `async` - Async registries via `define_async_registry!` and `AsyncRegistryApi`,
+//!   backed by the executor-agnostic `async-lock` (async-std, smol, ...), and
+//!   `register_lazy_async` / `get_async` and `register_provider_async` /
+//!   `resolve_async` on regular registries
+//! - `tokio` - Same async registries, backed by `tokio::sync::RwLock`, plus
+//!   `subscribe::<T>()` watch channels on regular registries and task-local
+//!   registries for request scopes via `define_task_local_registry!` and
+//!   `subscribe_events()` channels for async event consumers, and graceful
+//!   teardown of `AsyncShutdown` services via `shutdown_with_timeout()`, and
+//!   concurrent `AsyncHealthCheck` probes via `health_report_async()`
+//! - `axum` - `FromRegistry<T, R>` extractor resolving handler parameters from a registry
+//! - `actix-web` - The same extractor for actix-web, plus `RegistryAppData` to copy
+//!   registry entries into an app as `web::Data<T>` at startup
+//! - `tower` - `RegistryLayer` middleware injecting registry `Arc`s into request extensions
+//! - `file-backend` - `enable_file_backend(path)` restores serializable entries from
+//!   a file and atomically rewrites it after every mutation; implies `bincode`
+//! - `unix` - `reload_on_sighup(reload)` runs a configuration reload closure on
+//!   every `SIGHUP`, announced with `RegistryEvent::Reload` (Unix only)
+//! - `anymap` - `import_anymap` / `export_anymap` move values between a registry
+//!   and an `anymap3` map
 
+#[cfg(feature = "actix-web")]
+mod actix;
+#[cfg(feature = "anymap")]
+mod anymap;
+#[cfg(any(feature = "async", feature = "tokio"))]
+mod async_registry;
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "axum")]
+mod axum;
+mod callback_panic;
+#[cfg(any(feature = "toml", feature = "json"))]
+mod config_file;
+#[cfg(feature = "contention-stats")]
+mod contention_stats;
+mod contents_eq;
+mod describe;
+mod dyn_registry;
+#[cfg(feature = "env")]
+mod env_config;
+mod event_forwarding;
+mod event_level;
+#[cfg(any(test, feature = "test-util"))]
+mod event_recorder;
+mod event_sampling;
+#[cfg(feature = "tokio")]
+mod event_stream;
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+mod extract;
+#[cfg(feature = "file-backend")]
+mod file_backend;
+mod fingerprint;
+#[cfg(feature = "global")]
+mod global_registry;
+mod global_sink;
+#[cfg(feature = "tokio")]
+mod health;
+mod history;
+mod init_lock;
+mod lazy;
+#[cfg(any(feature = "async", feature = "tokio"))]
+mod lazy_async;
+#[cfg(any(test, feature = "test-util"))]
+mod leak_report;
+mod limited;
+mod local_registry;
+#[cfg(feature = "log")]
+mod log_bridge;
 mod macros;
+mod metadata;
+mod mirror;
+mod on_change;
+#[cfg(feature = "bincode")]
+mod persist;
+mod pool;
+mod provides;
+mod read_only;
+mod registry_config;
+mod registry_entry;
 mod registry_error;
 mod registry_event;
+mod registry_handle;
+mod registry_index;
+mod registry_key;
+mod registry_state;
 mod registry_trait;
+mod resolver;
+mod runtime_registry;
+mod scope;
+#[cfg(feature = "serde")]
+mod serializable;
+#[cfg(feature = "tokio")]
+mod shutdown;
+#[cfg(all(unix, feature = "unix"))]
+mod sighup;
+#[cfg(any(test, feature = "test-util"))]
+mod snapshot;
+mod stale;
+#[cfg(feature = "stats")]
+mod stats;
+mod suggest;
+#[doc(hidden)]
+pub mod sync;
+#[cfg(feature = "tokio")]
+mod task_local_registry;
+#[cfg(any(test, feature = "test-util"))]
+mod test_guard;
+mod timeline;
+#[cfg(feature = "tower")]
+mod tower;
+mod trace_context;
+#[cfg(feature = "tracing")]
+mod tracing_bridge;
+mod transfer;
+mod wait_for;
+#[cfg(feature = "tokio")]
+mod watch;
 
 // Re-export the public API
-pub use registry_error::RegistryError;
-pub use registry_event::RegistryEvent;
+#[cfg(feature = "actix-web")]
+pub use actix::RegistryAppData;
+#[cfg(feature = "anymap")]
+pub use anymap::{AnyMap, AnyMapExport, AnyMapImport};
+#[cfg(any(feature = "async", feature = "tokio"))]
+pub use async_registry::AsyncRegistryApi;
+#[cfg(feature = "audit")]
+pub use audit::FsyncPolicy;
+pub use callback_panic::CallbackPanicPolicy;
+#[cfg(any(feature = "toml", feature = "json"))]
+pub use config_file::ConfigManifest;
+#[cfg(feature = "contention-stats")]
+pub use contention_stats::{ContentionStats, LockStats};
+pub use contents_eq::ContentEquality;
+pub use describe::EntryInfo;
+pub use dyn_registry::DynRegistry;
+pub use event_level::EventLevel;
+#[cfg(any(test, feature = "test-util"))]
+pub use event_recorder::EventRecorder;
+pub use event_sampling::EventSampling;
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+pub use extract::{FromRegistry, RegistryRejection};
+/// The process-wide registry (feature `global`).
+///
+/// For applications that need a single registry: the common functions are
+/// available at the crate root, the rest here, and `global::Api` names the
+/// registry where a type is expected.
+///
+/// ```rust
+/// use singleton_registry as registry;
+///
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// registry::register(Config { port: 8080 });
+/// assert_eq!(registry::get::<Config>().unwrap().port, 8080);
+/// assert_eq!(registry::global::name(), "global");
+/// ```
+#[cfg(feature = "global")]
+pub use global_registry::global;
+#[cfg(feature = "global")]
+pub use global_registry::global::{
+    contains, describe, get, get_cloned, get_expect, get_or_register, register, register_arc,
+    register_lazy, register_provider, remove, resolve, set_trace_callback, try_get,
+};
+pub use global_sink::{clear_global_trace_sink, set_global_trace_sink};
+#[cfg(feature = "tokio")]
+pub use health::{AsyncHealthCheck, HealthCheckResult, HealthReport, HealthStatus};
+#[cfg(any(test, feature = "test-util"))]
+pub use leak_report::{ClearReport, LeakReport, LeakedEntry};
+pub use limited::Permit;
+pub use local_registry::{LocalRegistryApi, LocalStorage};
+pub use metadata::Metadata;
+pub use on_change::ChangeListenerGuard;
+pub use pool::{Pool, PoolGuard};
+pub use provides::Provides;
+pub use read_only::ReadOnlyRegistry;
+pub use registry_config::{PoisonPolicy, RegistrationMode, RegistryConfig};
+#[doc(hidden)]
+pub use registry_entry::__seed;
+pub use registry_entry::{DebugValue, RegistryEntry};
+pub use registry_error::{MissingDependency, RegistryError};
+pub use registry_event::{EventMeta, RegistryEvent};
+pub use registry_handle::RegistryHandle;
+#[doc(hidden)]
+pub use registry_index::__index_registry;
+pub use registry_index::{registries, RegistryInfo};
+pub use registry_key::RegistryKey;
+pub use registry_state::RegistryState;
 pub use registry_trait::RegistryApi;
+pub use resolver::{Dependencies, ResolveCtx};
+pub use runtime_registry::Registry;
+pub use scope::Scope;
+#[cfg(any(feature = "env", feature = "serde", feature = "toml", feature = "json"))]
+#[doc(hidden)]
+pub use serde as __serde;
+#[cfg(feature = "tokio")]
+pub use shutdown::{AsyncShutdown, ShutdownReport};
+#[cfg(all(unix, feature = "unix"))]
+pub use sighup::SighupGuard;
+#[cfg(feature = "macros")]
+pub use singleton_registry_macros::{isolated_registry, singleton};
+#[cfg(any(test, feature = "test-util"))]
+pub use snapshot::{RegistrySnapshot, SnapshotDiff};
+pub use stale::StaleEntry;
+#[cfg(feature = "stats")]
+pub use stats::TypeStats;
+#[cfg(feature = "tokio")]
+pub use task_local_registry::{TaskLocalRegistryApi, TaskLocalStorage};
+#[cfg(any(test, feature = "test-util"))]
+pub use test_guard::{TestGuard, TestLockGuard};
+pub use timeline::{TimelineOp, TimelineRecord};
+#[cfg(feature = "tower")]
+pub use tower::{RegistryLayer, RegistryService};
+pub use trace_context::{
+    trace_context, with_trace_context, with_trace_context_async, WithTraceContext,
+};
+pub use transfer::{copy_type, move_type, ConflictPolicy};
+pub use wait_for::WaitFor;
 
 // Macros are exported via #[macro_export] in macros.rs
 // They are automatically available at crate root
@@ -0,0 +1,317 @@
+//! Concurrency limits for singletons.
+//!
+//! `register_limited::<T>(value, max_concurrent)` registers `T` together with a
+//! per-type semaphore. `get_permit::<T>()` (async) and `get_permit_blocking::<T>()`
+//! wait for one of the `max_concurrent` permits and hand out the value inside a
+//! [`Permit`] that releases it on drop, e.g. to bound concurrent calls through a
+//! rate-limited API client. Like `wait_for`, the async side is executor-agnostic:
+//! waiting tasks park their `Waker` in the semaphore.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use crate::sync::{Condvar, Mutex};
+
+#[derive(Debug)]
+struct SemaphoreState {
+    limit: usize,
+    available: usize,
+    /// Permits still out after the limit was lowered below them; returning
+    /// one pays this down instead of freeing it.
+    excess: usize,
+    wakers: Vec<Waker>,
+}
+
+/// A counting semaphore usable from threads and tasks alike.
+#[derive(Debug)]
+pub(crate) struct Semaphore {
+    state: Mutex<SemaphoreState>,
+    released: Condvar,
+}
+
+impl Semaphore {
+    fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(SemaphoreState {
+                limit: permits,
+                available: permits,
+                excess: 0,
+                wakers: Vec::new(),
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Take a permit, blocking the current thread until one is free.
+    pub(crate) fn acquire_blocking(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+        while state.available == 0 {
+            state = self.released.wait(state).unwrap_or_else(|p| p.into_inner());
+        }
+        state.available -= 1;
+    }
+
+    /// Take a permit if one is free, otherwise park the task's waker.
+    pub(crate) fn poll_acquire(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+        if state.available > 0 {
+            state.available -= 1;
+            return Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// Return a permit and wake everyone waiting for it.
+    ///
+    /// Every parked task is woken, not just one: a woken task may have been
+    /// dropped in the meantime, and the permit must not be lost with it.
+    pub(crate) fn release(&self) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+            if state.excess > 0 {
+                state.excess -= 1;
+                return;
+            }
+            state.available += 1;
+            std::mem::take(&mut state.wakers)
+        };
+
+        self.released.notify_one();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Change the number of permits, counting the ones currently out against
+    /// the new limit.
+    fn resize(&self, permits: usize) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap_or_else(|p| p.into_inner());
+            if permits >= state.limit {
+                let mut added = permits - state.limit;
+                let paid = added.min(state.excess);
+                state.excess -= paid;
+                added -= paid;
+                state.available += added;
+            } else {
+                let removed = state.limit - permits;
+                let taken = removed.min(state.available);
+                state.available -= taken;
+                state.excess += removed - taken;
+            }
+            state.limit = permits;
+            std::mem::take(&mut state.wakers)
+        };
+
+        self.released.notify_all();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// Concurrency limits per type, installed by `register_limited`.
+#[derive(Debug, Default)]
+pub(crate) struct Limits {
+    semaphores: Mutex<HashMap<TypeId, Arc<Semaphore>>>,
+}
+
+impl Limits {
+    /// Limit `type_id` to `max_concurrent` permits.
+    ///
+    /// An existing limit is resized rather than replaced, so permits already
+    /// handed out keep counting against it.
+    pub(crate) fn set(&self, type_id: TypeId, max_concurrent: usize) {
+        let mut semaphores = self.semaphores.lock().unwrap_or_else(|p| p.into_inner());
+        match semaphores.get(&type_id) {
+            Some(semaphore) => semaphore.resize(max_concurrent),
+            None => {
+                semaphores.insert(type_id, Arc::new(Semaphore::new(max_concurrent)));
+            }
+        }
+    }
+
+    /// The semaphore limiting `type_id`, if any.
+    pub(crate) fn get(&self, type_id: TypeId) -> Option<Arc<Semaphore>> {
+        self.semaphores
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&type_id)
+            .cloned()
+    }
+}
+
+/// A value handed out under a concurrency limit; the permit is released on drop.
+///
+/// Returned by `get_permit` and `get_permit_blocking`.
+#[must_use = "dropping the permit releases it immediately"]
+pub struct Permit<T> {
+    value: Arc<T>,
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+impl<T> Permit<T> {
+    pub(crate) fn new(value: Arc<T>, semaphore: Option<Arc<Semaphore>>) -> Self {
+        Self { value, semaphore }
+    }
+
+    /// The value, as a shared handle that is not bound by the permit.
+    pub fn value(&self) -> &Arc<T> {
+        &self.value
+    }
+}
+
+impl<T> Deref for Permit<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Drop for Permit<T> {
+    fn drop(&mut self) {
+        if let Some(semaphore) = self.semaphore.take() {
+            semaphore.release();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Permit<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Permit").field(&self.value).finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    struct Client;
+
+    #[test]
+    fn test_blocking_permits_bound_concurrency() {
+        define_registry!(reg);
+
+        static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+        static PEAK: AtomicUsize = AtomicUsize::new(0);
+        reg::register_limited(Client, 2);
+
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                thread::spawn(|| {
+                    let _permit = reg::get_permit_blocking::<Client>().unwrap();
+                    let active = ACTIVE.fetch_add(1, Ordering::SeqCst) + 1;
+                    PEAK.fetch_max(active, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    ACTIVE.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(PEAK.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_async_permit_waits_for_release() {
+        define_registry!(reg);
+
+        reg::register_limited(5u8, 1);
+        let held = reg::get_permit_blocking::<u8>().unwrap();
+
+        let waiter = thread::spawn(|| {
+            let permit = futures_lite::future::block_on(reg::get_permit::<u8>()).unwrap();
+            *permit
+        });
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(held);
+        assert_eq!(waiter.join().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_unlimited_and_missing_types() {
+        define_registry!(reg);
+
+        reg::register(1u16);
+        let a = reg::get_permit_blocking::<u16>().unwrap();
+        let b = reg::get_permit_blocking::<u16>().unwrap();
+        assert!(Arc::ptr_eq(a.value(), b.value()));
+
+        assert!(matches!(
+            reg::get_permit_blocking::<u32>().unwrap_err(),
+            RegistryError::TypeNotFound {
+                type_name: "u32",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_limit_survives_replacement() {
+        define_registry!(reg);
+
+        reg::register_limited(1u8, 1);
+        let _held = reg::get_permit_blocking::<u8>().unwrap();
+        reg::register(2u8);
+
+        let waiter = thread::spawn(|| *reg::get_permit_blocking::<u8>().unwrap());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(_held);
+        assert_eq!(waiter.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rejected_registration_keeps_limit() {
+        define_registry!(reg);
+
+        reg::register_limited(1u8, 1);
+        reg::protect::<u8>().unwrap();
+        let held = reg::get_permit_blocking::<u8>().unwrap();
+        reg::register_limited(2u8, 4);
+
+        let waiter = thread::spawn(|| *reg::get_permit_blocking::<u8>().unwrap());
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(held);
+        assert_eq!(waiter.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_lowered_limit_counts_outstanding_permits() {
+        define_registry!(reg);
+
+        reg::register_limited(1u8, 2);
+        let first = reg::get_permit_blocking::<u8>().unwrap();
+        let second = reg::get_permit_blocking::<u8>().unwrap();
+        reg::register_limited(2u8, 1);
+
+        let waiter = thread::spawn(|| *reg::get_permit_blocking::<u8>().unwrap());
+        drop(first);
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(second);
+        assert_eq!(waiter.join().unwrap(), 2);
+    }
+}
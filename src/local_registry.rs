@@ -0,0 +1,282 @@
+//! Single-threaded registries for `!Send` types.
+//!
+//! GUI and main-thread objects (winit handles, `Rc`-based state) cannot satisfy
+//! the `Send + Sync` bounds of a regular registry. A registry created with
+//! [`define_local_registry!`](crate::define_local_registry) relaxes the bounds to
+//! `'static` and stores values as `Rc<T>` in a thread-local `RefCell` map.
+//!
+//! Every thread sees its own, independent registry. Handles are `Rc`s, so the
+//! compiler rejects moving them to another thread. Values still registered when
+//! the thread exits are dropped with its thread-locals; a `Drop` impl that uses
+//! the registry at that point panics; tests can `clear()` such values beforehand
+//! (feature `test-util`).
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::Location;
+use std::rc::Rc;
+use std::thread::LocalKey;
+use std::time::Instant;
+
+use crate::{EventMeta, RegistryError, RegistryEvent};
+
+/// The trace callback of a local registry; needs neither `Send` nor `Sync`.
+type LocalTraceCallback = Rc<dyn Fn(&RegistryEvent)>;
+
+/// The thread-local state of a local registry: its values and trace callback.
+#[derive(Default)]
+pub struct LocalStorage {
+    values: RefCell<HashMap<TypeId, Rc<dyn Any>>>,
+    trace: RefCell<Option<LocalTraceCallback>>,
+}
+
+impl std::fmt::Debug for LocalStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalStorage")
+            .field("len", &self.values.borrow().len())
+            .finish()
+    }
+}
+
+/// Core trait defining single-threaded registry behavior.
+///
+/// Mirrors [`RegistryApi`](crate::RegistryApi) with `'static` bounds and `Rc`
+/// handles. Only the `storage` accessor has to be implemented.
+pub trait LocalRegistryApi {
+    /// Access the thread-local storage.
+    fn storage() -> &'static LocalKey<LocalStorage>;
+
+    /// Name of the registry, reported in the `registry` field of every event.
+    ///
+    /// `define_local_registry!` returns the module name; defaults to the implementing
+    /// type's name.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Tracing
+    // -------------------------------------------------------------------------------------------------
+
+    /// Set a tracing callback for registry operations on the current thread.
+    fn set_trace_callback(&self, callback: impl Fn(&RegistryEvent) + 'static) {
+        Self::storage().with(|s| *s.trace.borrow_mut() = Some(Rc::new(callback)));
+    }
+
+    /// Clear the tracing callback of the current thread.
+    fn clear_trace_callback(&self) {
+        Self::storage().with(|s| *s.trace.borrow_mut() = None);
+    }
+
+    /// Emit a registry event using the current callback.
+    ///
+    /// The callback is invoked with no `RefCell` borrowed, so it may use the registry.
+    fn emit_event(&self, event: &RegistryEvent) {
+        let event = &event.with_meta(EventMeta::capture(Self::name()));
+        #[cfg(feature = "tracing")]
+        crate::tracing_bridge::emit(event);
+        let callback = Self::storage().with(|s| s.trace.borrow().clone());
+        if let Some(cb) = callback {
+            cb(event);
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Registry
+    // -------------------------------------------------------------------------------------------------
+
+    /// Register a value, replacing any previous value of the same type.
+    fn register<T: 'static>(&self, value: T) {
+        self.register_rc(Rc::new(value));
+    }
+
+    /// Register an Rc-wrapped value.
+    fn register_rc<T: 'static>(&self, value: Rc<T>) {
+        self.emit_event(&RegistryEvent::Register {
+            type_name: std::any::type_name::<T>(),
+            meta: EventMeta::default(),
+        });
+
+        let started = Instant::now();
+
+        let previous = Self::storage().with(|s| {
+            s.values
+                .borrow_mut()
+                .insert(TypeId::of::<T>(), value as Rc<dyn Any>)
+        });
+        let replaced = previous.is_some();
+        // Drop the old value only after the borrow ends; its `Drop` may use the registry
+        drop(previous);
+
+        self.emit_event(&RegistryEvent::RegisterCompleted {
+            type_name: std::any::type_name::<T>(),
+            replaced,
+            rollback: false,
+            meta: EventMeta::timed(started),
+        });
+    }
+
+    /// Retrieve a value registered on the current thread.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered on the current thread
+    /// - Type mismatch (extremely rare)
+    #[track_caller]
+    fn get<T: 'static>(&self) -> Result<Rc<T>, RegistryError> {
+        let started = Instant::now();
+        let any_rc_opt =
+            Self::storage().with(|s| s.values.borrow().get(&TypeId::of::<T>()).cloned());
+
+        let result = match any_rc_opt {
+            Some(any_rc) => any_rc
+                .downcast::<T>()
+                .map_err(|_| RegistryError::TypeMismatch {
+                    type_name: std::any::type_name::<T>(),
+                }),
+            None => Err(RegistryError::TypeNotFound {
+                type_name: std::any::type_name::<T>(),
+                location: Some(Location::caller()),
+                suggestions: Vec::new(),
+            }),
+        };
+
+        self.emit_event(&RegistryEvent::Get {
+            type_name: std::any::type_name::<T>(),
+            found: result.is_ok(),
+            meta: EventMeta::timed(started),
+        });
+
+        result
+    }
+
+    /// Retrieve a cloned value registered on the current thread.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered on the current thread
+    /// - Type mismatch
+    #[track_caller]
+    fn get_cloned<T: Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.get::<T>().map(|rc| (*rc).clone())
+    }
+
+    /// Retrieve a value, returning `None` if not registered on the current thread.
+    fn try_get<T: 'static>(&self) -> Option<Rc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Check if a type is registered on the current thread.
+    fn contains<T: 'static>(&self) -> bool {
+        let found = Self::storage().with(|s| s.values.borrow().contains_key(&TypeId::of::<T>()));
+
+        self.emit_event(&RegistryEvent::Contains {
+            type_name: std::any::type_name::<T>(),
+            found,
+            meta: EventMeta::default(),
+        });
+
+        found
+    }
+
+    /// Clear all values registered on the current thread.
+    ///
+    /// Intended for testing and only available with the `test-util` feature;
+    /// already-retrieved `Rc<T>` handles remain valid.
+    #[cfg(any(test, feature = "test-util"))]
+    fn clear(&self) {
+        self.emit_event(&RegistryEvent::Clear {
+            meta: EventMeta::default(),
+        });
+        let values = Self::storage().with(|s| std::mem::take(&mut *s.values.borrow_mut()));
+        drop(values);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_local_registry;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::thread;
+
+    #[test]
+    fn test_register_non_send_value() {
+        define_local_registry!(ui);
+
+        let shared = Rc::new(Cell::new(1));
+        ui::register(shared.clone());
+        shared.set(2);
+
+        let stored: Rc<Rc<Cell<i32>>> = ui::get().unwrap();
+        assert_eq!(stored.get(), 2);
+        assert!(ui::contains::<Rc<Cell<i32>>>());
+        assert!(ui::try_get::<u8>().is_none());
+    }
+
+    #[test]
+    fn test_threads_have_separate_registries() {
+        define_local_registry!(ui);
+
+        ui::register(1u8);
+        thread::spawn(|| assert!(!ui::contains::<u8>()))
+            .join()
+            .unwrap();
+        assert_eq!(ui::get_cloned::<u8>().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_trace_events() {
+        define_local_registry!(ui);
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events_clone = events.clone();
+        ui::set_trace_callback(move |e| events_clone.borrow_mut().push(e.to_string()));
+
+        ui::register(1u8);
+        ui::clear_trace_callback();
+        let _ = ui::get::<u8>();
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                "register { type_name: u8 }",
+                "register_completed { type_name: u8 }"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replaced_value_drop_may_use_registry() {
+        define_local_registry!(ui);
+
+        struct Window;
+        impl Drop for Window {
+            fn drop(&mut self) {
+                ui::register(true);
+            }
+        }
+
+        ui::register(Window);
+        ui::register(Window);
+        assert!(ui::contains::<bool>());
+
+        // Drop the remaining window before the thread-local itself is torn down
+        ui::clear();
+    }
+
+    #[test]
+    fn test_clear() {
+        define_local_registry!(ui);
+
+        ui::register("x");
+        ui::clear();
+        assert!(ui::get::<&str>().is_err());
+    }
+}
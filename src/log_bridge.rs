@@ -0,0 +1,104 @@
+//! `log` facade integration (feature `log`).
+//!
+//! `enable_log_bridge(EventLevel::Info)` forwards every event of the registry
+//! at that level or above to the `log` facade, with target
+//! `singleton_registry`, for applications that haven't adopted `tracing`.
+//! Event levels map to `log` levels as `Debug` → `debug`, `Info` and `Notice` →
+//! `info`, `Warn` → `warn`.
+
+use std::sync::atomic::Ordering;
+
+use crate::sync::AtomicU8;
+use crate::{EventLevel, RegistryEvent};
+
+const DISABLED: u8 = u8::MAX;
+
+/// Per-registry forwarding of events to `log`, disabled by default.
+#[derive(Debug)]
+pub(crate) struct LogBridge {
+    min: AtomicU8,
+}
+
+impl Default for LogBridge {
+    fn default() -> Self {
+        Self {
+            min: AtomicU8::new(DISABLED),
+        }
+    }
+}
+
+impl LogBridge {
+    /// Forward events at `level` or above.
+    pub(crate) fn enable(&self, level: EventLevel) {
+        self.min.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// Stop forwarding.
+    pub(crate) fn disable(&self) {
+        self.min.store(DISABLED, Ordering::Relaxed);
+    }
+
+    /// Log `event` if the bridge is enabled for its level.
+    pub(crate) fn forward(&self, event: &RegistryEvent) {
+        let level = event.level();
+        if (level as u8) < self.min.load(Ordering::Relaxed) {
+            return;
+        }
+        let level = match level {
+            EventLevel::Debug => log::Level::Debug,
+            EventLevel::Info | EventLevel::Notice => log::Level::Info,
+            EventLevel::Warn => log::Level::Warn,
+        };
+        log::log!(target: "singleton_registry", level, "[{}] {}", event.registry(), event);
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::Mutex;
+
+    static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+        fn log(&self, record: &log::Record<'_>) {
+            let line = format!("{} {}", record.level(), record.args());
+            if record.target() == "singleton_registry" && line.contains("[log_reg]") {
+                LOGGED.lock().unwrap().push(line);
+            }
+        }
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_events_are_forwarded_to_log() {
+        define_registry!(log_reg);
+
+        log::set_logger(&Capture).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        log_reg::register(0i8); // bridge not enabled yet
+        log_reg::enable_log_bridge(EventLevel::Info);
+        log_reg::register(1u8);
+        let _ = log_reg::get::<u8>();
+        let _ = log_reg::get::<u16>();
+        log_reg::disable_log_bridge();
+        let _ = log_reg::get::<u32>();
+
+        assert_eq!(
+            *LOGGED.lock().unwrap(),
+            [
+                "INFO [log_reg] register { type_name: u8 }",
+                "INFO [log_reg] register_completed { type_name: u8 }",
+                "WARN [log_reg] get { type_name: u16, found: false }",
+            ]
+        );
+    }
+}
@@ -3,12 +3,14 @@
 //! Macros for creating singleton registries.
 //!
 //! This module provides a simple macro-based approach to create type-safe,
-//! thread-safe singleton registries with zero external dependencies.
+//! thread-safe singleton registries. The default build has no required
+//! dependencies; integrations such as tokio, serde or axum are behind features.
 
 /// Creates a singleton registry module with ergonomic free functions.
 ///
 /// The macro generates a module containing storage, tracing infrastructure,
-/// and a private `Api` struct implementing `RegistryApi`.
+/// and a zero-sized `Api` struct implementing `RegistryApi`. `Api` names the
+/// registry wherever a type is expected, e.g. in framework extractors.
 ///
 /// # Example
 ///
@@ -30,31 +32,1513 @@
 /// assert_eq!(*num, 42);
 /// assert_eq!(&**msg, "redis");
 /// ```
+///
+/// Options in braces after the name configure the registry (see
+/// [`RegistryConfig`](crate::RegistryConfig)); all are optional:
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app, { poison_policy: Recover, trace: disabled, capacity: 64 });
+/// ```
+///
+/// - `poison_policy: Error | Recover` - whether lookups fail or recover on a poisoned lock
+/// - `trace: enabled | disabled` - whether the registry emits events
+/// - `capacity: <expr>` - initial capacity of the storage map
+/// - `registration: Override | AppendOnly` - whether re-registering a type
+///   replaces its value or fails with `AlreadyRegistered`
+///
+/// Expressions in braces directly after the name are registered as initial
+/// values when the registry is first used, so defaults exist before any `get`:
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app { 8080u16, String::from("localhost") });
+///
+/// assert_eq!(*app::get::<u16>().unwrap(), 8080);
+/// ```
+#[macro_export]
+macro_rules! define_registry {
+    ($name:ident) => {
+        $crate::define_registry!(@define $name, {}, []);
+    };
+    ($name:ident, { $($options:tt)* }) => {
+        $crate::define_registry!(@define $name, { $($options)* }, []);
+    };
+    ($name:ident { $($init:expr),* $(,)? }) => {
+        $crate::define_registry!(@define $name, {}, [$($init),*]);
+    };
+    (@define $name:ident, { $($options:tt)* }, [$($init:expr),*]) => {
+        pub mod $name {
+            use std::sync::Arc;
+            use std::collections::HashMap;
+            use std::any::TypeId;
+            use $crate::sync::{LazyLock, Mutex};
+
+            // Options given to the macro (module-private)
+            const CONFIG: $crate::RegistryConfig =
+                $crate::__registry_config!($crate::RegistryConfig::new(); $($options)*);
+
+            // Storage for registered values (module-private), holding the
+            // initial values from the first use on
+            static STORAGE: LazyLock<Mutex<HashMap<TypeId, $crate::RegistryEntry>>> =
+                LazyLock::new(|| {
+                    #[allow(unused_mut)]
+                    let mut map = HashMap::with_capacity(CONFIG.capacity);
+                    $($crate::__seed(&mut map, $init);)*
+                    Mutex::new(map)
+                });
+
+            // Trace callback storage (module-private)
+            // Note: This type matches TraceCallback in registry_trait.rs - keep in sync
+            type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&$crate::RegistryEvent) + Send + Sync>>>>;
+            static TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
+
+            // Auxiliary state (module-private); initializing it lists the
+            // registry in `registries()`
+            static STATE: LazyLock<$crate::RegistryState> = LazyLock::new(|| {
+                $crate::__index_registry::<Api>();
+                $crate::RegistryState::new()
+            });
+
+            /// Zero-sized type that implements the registry API.
+            ///
+            /// All registry operations are provided by the `RegistryApi` trait's
+            /// default implementations. This struct only provides access to the statics.
+            #[derive(Debug, Clone, Copy, Default)]
+            pub struct Api;
+
+            impl $crate::RegistryApi for Api {
+                fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, $crate::RegistryEntry>>> {
+                    &STORAGE
+                }
+
+                fn trace() -> &'static TraceCallback {
+                    &TRACE
+                }
+
+                fn state() -> &'static $crate::RegistryState {
+                    LazyLock::force(&STATE)
+                }
+
+                fn name() -> &'static str {
+                    stringify!($name)
+                }
+
+                fn config() -> &'static $crate::RegistryConfig {
+                    &CONFIG
+                }
+
+                // All other methods (register, get, contains, etc.) are provided by
+                // the trait's default implementations!
+            }
+
+            /// Convenient constant for accessing the registry API.
+            const API: Api = Api;
+
+            // Free functions for ergonomic usage - they delegate to API
+
+            /// Name of the registry, as reported in its events.
+            pub fn name() -> &'static str {
+                stringify!($name)
+            }
+
+            /// Options the registry was defined with.
+            pub fn config() -> &'static $crate::RegistryConfig {
+                &CONFIG
+            }
+
+            /// A namespaced view of the registry, isolated from its other values.
+            pub fn scope(name: &'static str) -> $crate::Scope<Api> {
+                use $crate::RegistryApi;
+                API.scope(name)
+            }
+
+            /// A cloneable handle to the registry, for code that can't name this module.
+            pub fn handle() -> $crate::RegistryHandle {
+                use $crate::RegistryApi;
+                API.handle()
+            }
+
+            /// A cloneable view of the registry that can only look values up.
+            pub fn read_only() -> $crate::ReadOnlyRegistry {
+                use $crate::RegistryApi;
+                API.read_only()
+            }
+
+            /// Register a value in the registry.
+            #[track_caller]
+            pub fn register<T: Send + Sync + 'static>(value: T) {
+                use $crate::RegistryApi;
+                API.register(value)
+            }
+
+            /// Register an Arc-wrapped value in the registry.
+            #[track_caller]
+            pub fn register_arc<T: Send + Sync + 'static>(value: Arc<T>) {
+                use $crate::RegistryApi;
+                API.register_arc(value)
+            }
+
+            /// Register a value unless the registered value of its type is final or
+            /// protected, or the registry is append-only.
+            #[track_caller]
+            pub fn try_register<T: Send + Sync + 'static>(value: T) -> Result<(), $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.try_register(value)
+            }
+
+            /// Register a value together with descriptive metadata.
+            #[track_caller]
+            pub fn register_with_meta<T: Send + Sync + 'static>(value: T, metadata: $crate::Metadata) {
+                use $crate::RegistryApi;
+                API.register_with_meta(value, metadata)
+            }
+
+            /// Register a value whose `Debug` output is included in `dump()`.
+            #[track_caller]
+            pub fn register_debuggable<T: std::fmt::Debug + Send + Sync + 'static>(value: T) {
+                use $crate::RegistryApi;
+                API.register_debuggable(value)
+            }
+
+            /// Register a value built at runtime under its `TypeId`.
+            #[track_caller]
+            pub fn register_boxed(
+                type_id: std::any::TypeId,
+                value: Box<dyn std::any::Any + Send + Sync>,
+            ) -> Result<(), $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.register_boxed(type_id, value)
+            }
+
+            /// Register a value whose `PartialEq` is used by `contents_equal()`.
+            #[track_caller]
+            pub fn register_comparable<T: PartialEq + Send + Sync + 'static>(value: T) {
+                use $crate::RegistryApi;
+                API.register_comparable(value)
+            }
+
+            /// Register a value that can never be replaced.
+            #[track_caller]
+            pub fn register_final<T: Send + Sync + 'static>(value: T) -> Result<(), $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.register_final(value)
+            }
+
+            /// Protect the registered value of `T` from being replaced, removed or cleared.
+            pub fn protect<T: Send + Sync + 'static>() -> Result<(), $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.protect::<T>()
+            }
+
+            /// Retrieve the value of `T`, registering `factory()` first if it is absent.
+            #[track_caller]
+            pub fn get_or_register<T: Send + Sync + 'static>(
+                factory: impl FnOnce() -> T,
+            ) -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_or_register(factory)
+            }
+
+            /// Register a factory for `T` that runs on first access.
+            #[track_caller]
+            pub fn register_lazy<T: Send + Sync + 'static>(factory: impl FnOnce() -> T + Send + 'static) {
+                use $crate::RegistryApi;
+                API.register_lazy::<T>(factory)
+            }
+
+            /// Register a provider that builds `T` from other registry entries.
+            pub fn register_provider<T: Send + Sync + 'static>(
+                provider: impl Fn(&$crate::ResolveCtx<Api>) -> Result<T, $crate::RegistryError> + Send + Sync + 'static,
+            ) {
+                use $crate::RegistryApi;
+                API.register_provider::<T>(provider)
+            }
+
+            /// Retrieve `T`, building it and its dependencies from providers if needed.
+            pub fn resolve<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.resolve::<T>()
+            }
+
+            /// Resolve a tuple of types, reporting every missing one at once.
+            pub fn resolve_all<D: $crate::Dependencies<Api>>() -> Result<D::Output, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.resolve_all::<D>()
+            }
+
+            /// Retrieve a value from the registry.
+            #[track_caller]
+            pub fn get<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get()
+            }
+
+            /// Check out an item from the registered `Pool<T>`, blocking until one is available.
+            pub fn checkout<T: Send + 'static>() -> Result<$crate::PoolGuard<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.checkout::<T>()
+            }
+
+            /// Retrieve a cloned value from the registry.
+            #[track_caller]
+            pub fn get_cloned<T: Send + Sync + Clone + 'static>() -> Result<T, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_cloned()
+            }
+
+            /// Retrieve a cloned value, or `T::default()` if none is registered.
+            pub fn get_cloned_or_default<T: Send + Sync + Clone + Default + 'static>() -> T {
+                use $crate::RegistryApi;
+                API.get_cloned_or_default()
+            }
+
+            /// Retrieve a value from the registry, panicking with a descriptive message if unavailable.
+            #[track_caller]
+            pub fn get_expect<T: Send + Sync + 'static>() -> Arc<T> {
+                use $crate::RegistryApi;
+                API.get_expect()
+            }
+
+            /// Retrieve a value from the registry, returning None if not registered.
+            pub fn try_get<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+                use $crate::RegistryApi;
+                API.try_get()
+            }
+
+            /// Register a value under the key type `K`.
+            #[track_caller]
+            pub fn register_key<K: $crate::RegistryKey>(value: K::Value) {
+                use $crate::RegistryApi;
+                API.register_key::<K>(value)
+            }
+
+            /// Retrieve the value registered under the key type `K`.
+            #[track_caller]
+            pub fn get_key<K: $crate::RegistryKey>() -> Result<Arc<K::Value>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_key::<K>()
+            }
+
+            /// Retrieve the value registered under the key type `K`, or `None` if there is none.
+            pub fn try_get_key<K: $crate::RegistryKey>() -> Option<Arc<K::Value>> {
+                use $crate::RegistryApi;
+                API.try_get_key::<K>()
+            }
+
+            /// Check if a value is registered under the key type `K`.
+            pub fn contains_key<K: $crate::RegistryKey>() -> Result<bool, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.contains_key::<K>()
+            }
+
+            /// Remove the value registered under the key type `K` and return it.
+            #[track_caller]
+            pub fn remove_key<K: $crate::RegistryKey>() -> Result<Arc<K::Value>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.remove_key::<K>()
+            }
+
+            /// Listen for registrations and replacements of `T` until the guard is dropped.
+            pub fn on_change<T: Send + Sync + 'static>(
+                callback: impl Fn(&Arc<T>) + Send + Sync + 'static,
+            ) -> $crate::ChangeListenerGuard {
+                use $crate::RegistryApi;
+                API.on_change::<T>(callback)
+            }
+
+            /// Wait until a value of type `T` is registered.
+            pub fn wait_for<T: Send + Sync + 'static>() -> impl std::future::Future<Output = Arc<T>> + Send {
+                use $crate::RegistryApi;
+                API.wait_for::<T>()
+            }
+
+            /// Block until a value of type `T` is registered or `timeout` elapses.
+            pub fn wait_for_blocking<T: Send + Sync + 'static>(
+                timeout: std::time::Duration,
+            ) -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.wait_for_blocking::<T>(timeout)
+            }
+
+            /// Retrieve a value, retrying with doubling backoff while it is not registered yet.
+            pub fn get_eventually<T: Send + Sync + 'static>(
+                retries: u32,
+                backoff: std::time::Duration,
+            ) -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_eventually::<T>(retries, backoff)
+            }
+
+            /// Register a value whose concurrent users are bounded by `max_concurrent` permits.
+            #[track_caller]
+            pub fn register_limited<T: Send + Sync + 'static>(value: T, max_concurrent: usize) {
+                use $crate::RegistryApi;
+                API.register_limited(value, max_concurrent)
+            }
+
+            /// Wait for a permit to use `T` and return the value inside it.
+            pub async fn get_permit<T: Send + Sync + 'static>() -> Result<$crate::Permit<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_permit::<T>().await
+            }
+
+            /// Block until a permit to use `T` is free and return the value inside it.
+            pub fn get_permit_blocking<T: Send + Sync + 'static>() -> Result<$crate::Permit<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.get_permit_blocking::<T>()
+            }
+
+            /// Check if a type is registered in the registry.
+            pub fn contains<T: Send + Sync + 'static>() -> Result<bool, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.contains::<T>()
+            }
+
+            /// The type names in `types` that are not registered, checked under one lock.
+            ///
+            /// Use [`contains_all!`](crate::contains_all) or
+            /// [`contains_any!`](crate::contains_any) rather than calling this directly.
+            pub fn missing_types(
+                types: &[(std::any::TypeId, &'static str)],
+            ) -> Result<Vec<&'static str>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.missing_types(types)
+            }
+
+            /// Source location of the call that registered the current value of `T`.
+            pub fn registered_at<T: Send + Sync + 'static>(
+            ) -> Option<&'static std::panic::Location<'static>> {
+                use $crate::RegistryApi;
+                API.registered_at::<T>()
+            }
+
+            /// The metadata the current value of `T` was registered with.
+            pub fn metadata<T: Send + Sync + 'static>() -> Option<Arc<$crate::Metadata>> {
+                use $crate::RegistryApi;
+                API.metadata::<T>()
+            }
+
+            /// Keep the last `depth` replaced values of `T` (`0` turns history off).
+            pub fn enable_history<T: Send + Sync + 'static>(depth: usize) {
+                use $crate::RegistryApi;
+                API.enable_history::<T>(depth)
+            }
+
+            /// A replaced value of `T`: `0` is the one the current value replaced.
+            pub fn get_previous<T: Send + Sync + 'static>(k: usize) -> Option<Arc<T>> {
+                use $crate::RegistryApi;
+                API.get_previous::<T>(k)
+            }
+
+            /// Register the value the current one of `T` replaced again, undoing
+            /// the last registration.
+            #[track_caller]
+            pub fn revert<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.revert::<T>()
+            }
+
+            /// Record every mutation from now on, discarding earlier records.
+            pub fn start_timeline() {
+                use $crate::RegistryApi;
+                API.start_timeline()
+            }
+
+            /// Stop recording mutations and take the records.
+            pub fn stop_timeline() -> Vec<$crate::TimelineRecord> {
+                use $crate::RegistryApi;
+                API.stop_timeline()
+            }
+
+            /// The mutations recorded since `start_timeline()`, oldest first.
+            pub fn timeline() -> Vec<$crate::TimelineRecord> {
+                use $crate::RegistryApi;
+                API.timeline()
+            }
+
+            /// Apply the mutations of another registry's timeline records in order.
+            pub fn replay_timeline(
+                records: &[$crate::TimelineRecord],
+            ) -> Result<(), $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.replay_timeline(records)
+            }
+
+            /// Stamp entries with the time of their last retrieval (off by default).
+            pub fn set_access_tracking(enabled: bool) {
+                use $crate::RegistryApi;
+                API.set_access_tracking(enabled)
+            }
+
+            /// Whether `get` records access times.
+            pub fn access_tracking() -> bool {
+                use $crate::RegistryApi;
+                API.access_tracking()
+            }
+
+            /// Entries not retrieved for at least `older_than`.
+            pub fn stale_entries(older_than: std::time::Duration) -> Vec<$crate::StaleEntry> {
+                use $crate::RegistryApi;
+                API.stale_entries(older_than)
+            }
+
+            /// Snapshot every entry (type name, registration time, generation, metadata, ...).
+            pub fn describe() -> Vec<$crate::EntryInfo> {
+                use $crate::RegistryApi;
+                API.describe()
+            }
+
+            /// Visit every entry's `EntryInfo` and type-erased value, sorted by type name.
+            pub fn for_each_entry(
+                visit: impl FnMut(&$crate::EntryInfo, &Arc<dyn std::any::Any + Send + Sync>),
+            ) {
+                use $crate::RegistryApi;
+                API.for_each_entry(visit)
+            }
+
+            /// List every entry with the `Debug` output of debuggable values.
+            pub fn dump() -> String {
+                use $crate::RegistryApi;
+                API.dump()
+            }
+
+            /// A checksum of the registered types and their generations.
+            pub fn fingerprint() -> u64 {
+                use $crate::RegistryApi;
+                API.fingerprint()
+            }
+
+            /// Whether `other` holds the same types, with equal comparable values.
+            pub fn contents_equal<O: $crate::RegistryApi>(other: &O) -> bool {
+                use $crate::RegistryApi;
+                API.contents_equal(other)
+            }
+
+            /// Whether `other` holds the same types, comparing values as `equality` says.
+            pub fn contents_equal_by<O: $crate::RegistryApi>(
+                other: &O,
+                equality: $crate::ContentEquality,
+            ) -> bool {
+                use $crate::RegistryApi;
+                API.contents_equal_by(other, equality)
+            }
+
+            $crate::__cfg_anymap! {
+                /// Move the values of the types listed in `L` from an `anymap` into the registry.
+                #[track_caller]
+                pub fn import_anymap<L: $crate::AnyMapImport>(map: &mut $crate::AnyMap) -> usize {
+                    use $crate::RegistryApi;
+                    API.import_anymap::<L>(map)
+                }
+
+                /// An `anymap` holding clones of the registered values of the types listed in `L`.
+                pub fn export_anymap<L: $crate::AnyMapExport>() -> $crate::AnyMap {
+                    use $crate::RegistryApi;
+                    API.export_anymap::<L>()
+                }
+            }
+
+            /// Number of strong `Arc` references to the value registered for `T`.
+            pub fn strong_count<T: Send + Sync + 'static>() -> Option<usize> {
+                use $crate::RegistryApi;
+                API.strong_count::<T>()
+            }
+
+            /// Number of entries the registry can hold without reallocating.
+            pub fn capacity() -> usize {
+                use $crate::RegistryApi;
+                API.capacity()
+            }
+
+            /// Approximate number of heap bytes owned by the registry.
+            pub fn approx_heap_bytes() -> usize {
+                use $crate::RegistryApi;
+                API.approx_heap_bytes()
+            }
+
+            /// Reserve room for at least `additional` more types.
+            pub fn reserve(additional: usize) {
+                use $crate::RegistryApi;
+                API.reserve(additional)
+            }
+
+            /// Shrink the registry's capacity as much as possible.
+            pub fn shrink_to_fit() {
+                use $crate::RegistryApi;
+                API.shrink_to_fit()
+            }
+
+            /// Set a tracing callback for registry operations.
+            pub fn set_trace_callback(callback: impl Fn(&$crate::RegistryEvent) + Send + Sync + 'static) {
+                use $crate::RegistryApi;
+                API.set_trace_callback(callback)
+            }
+
+            /// Clear the tracing callback.
+            pub fn clear_trace_callback() {
+                use $crate::RegistryApi;
+                API.clear_trace_callback()
+            }
+
+            /// Decide what happens when the trace callback panics.
+            pub fn set_callback_panic_policy(policy: $crate::CallbackPanicPolicy) {
+                use $crate::RegistryApi;
+                API.set_callback_panic_policy(policy)
+            }
+
+            /// The current trace callback panic policy.
+            pub fn callback_panic_policy() -> $crate::CallbackPanicPolicy {
+                use $crate::RegistryApi;
+                API.callback_panic_policy()
+            }
+
+            /// Forward every event to the parent registry `P` (e.g. `root::Api`).
+            pub fn forward_events_to<P: $crate::RegistryApi>() {
+                use $crate::RegistryApi;
+                API.forward_events_to::<P>()
+            }
+
+            /// Stop forwarding events to the parent registry.
+            pub fn stop_forwarding_events() {
+                use $crate::RegistryApi;
+                API.stop_forwarding_events()
+            }
+
+            /// Copy every entry into `target` and keep repeating registrations
+            /// and removals there.
+            pub fn mirror_into<P: $crate::RegistryApi + Send + Sync + 'static>(target: P) {
+                use $crate::RegistryApi;
+                API.mirror_into(target)
+            }
+
+            /// Stop mirroring into every target of `mirror_into`.
+            pub fn stop_mirroring() {
+                use $crate::RegistryApi;
+                API.stop_mirroring()
+            }
+
+            /// Import every entry of `other`, resolving conflicts by `policy`.
+            pub fn merge_from<O: $crate::RegistryApi>(
+                other: &O,
+                policy: $crate::ConflictPolicy,
+            ) -> Result<usize, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.merge_from(other, policy)
+            }
+
+            /// Sample lookup events (`Get`, `Contains`); mutations are always emitted.
+            pub fn set_event_sampling(sampling: $crate::EventSampling) {
+                use $crate::RegistryApi;
+                API.set_event_sampling(sampling)
+            }
+
+            /// The current lookup event sampling configuration.
+            pub fn event_sampling() -> $crate::EventSampling {
+                use $crate::RegistryApi;
+                API.event_sampling()
+            }
+
+            /// Emit only events at `level` or above.
+            pub fn set_trace_level(level: $crate::EventLevel) {
+                use $crate::RegistryApi;
+                API.set_trace_level(level)
+            }
+
+            /// The minimum level of emitted events.
+            pub fn trace_level() -> $crate::EventLevel {
+                use $crate::RegistryApi;
+                API.trace_level()
+            }
+
+            /// Record the emitting thread in every event (off by default).
+            pub fn set_event_threads(enabled: bool) {
+                use $crate::RegistryApi;
+                API.set_event_threads(enabled)
+            }
+
+            /// Whether events record the emitting thread.
+            pub fn event_threads() -> bool {
+                use $crate::RegistryApi;
+                API.event_threads()
+            }
+
+            /// Remove the value of `T` from the registry and return it.
+            #[track_caller]
+            pub fn remove<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.remove::<T>()
+            }
+
+            /// Remove the value of `T` and return it by value, unless it is still shared.
+            #[track_caller]
+            pub fn take_owned<T: Send + Sync + 'static>() -> Result<T, $crate::RegistryError> {
+                use $crate::RegistryApi;
+                API.take_owned::<T>()
+            }
+
+            /// Remove every entry whose type name `keep` rejects, returning the removed names.
+            #[track_caller]
+            pub fn retain(keep: impl FnMut(&str) -> bool) -> Vec<&'static str> {
+                use $crate::RegistryApi;
+                API.retain(keep)
+            }
+
+            $crate::__cfg_test_util! {
+                /// Clear the registry and report what was removed (feature `test-util`).
+                pub fn clear() -> $crate::ClearReport {
+                    use $crate::RegistryApi;
+                    API.clear()
+                }
+
+                /// Clear the registry, including protected values if `force` is set
+                /// (feature `test-util`).
+                pub fn clear_all(force: bool) -> $crate::ClearReport {
+                    use $crate::RegistryApi;
+                    API.clear_all(force)
+                }
+
+                /// Clear the registry and report values still referenced elsewhere
+                /// (feature `test-util`).
+                pub fn clear_with_leak_report() -> $crate::LeakReport {
+                    use $crate::RegistryApi;
+                    API.clear_with_leak_report()
+                }
+
+                /// Capture the registry's entries for diffing (feature `test-util`).
+                pub fn snapshot() -> $crate::RegistrySnapshot {
+                    use $crate::RegistryApi;
+                    API.snapshot()
+                }
+
+                /// Record the registry's events for assertions, replacing the trace
+                /// callback (feature `test-util`).
+                pub fn record_events() -> $crate::EventRecorder {
+                    use $crate::RegistryApi;
+                    API.record_events()
+                }
+
+                /// Render the registry as deterministic text for snapshot tests
+                /// (feature `test-util`).
+                pub fn golden_snapshot() -> String {
+                    use $crate::RegistryApi;
+                    API.golden_snapshot()
+                }
+
+                /// A guard restoring the registry's current entries when dropped
+                /// (feature `test-util`).
+                pub fn test_guard() -> $crate::TestGuard<Api> {
+                    use $crate::RegistryApi;
+                    API.test_guard()
+                }
+
+                /// Serialize the tests touching this registry (feature `test-util`).
+                pub fn lock_for_test() -> $crate::TestLockGuard {
+                    use $crate::RegistryApi;
+                    API.lock_for_test()
+                }
+            }
+
+            $crate::__cfg_async! {
+                /// Register an async provider for `T`, run on the first `get_async::<T>()`.
+                pub fn register_lazy_async<T, F, Fut>(factory: F)
+                where
+                    T: Send + Sync + 'static,
+                    F: Fn() -> Fut + Send + Sync + 'static,
+                    Fut: std::future::Future<Output = T> + Send + 'static,
+                {
+                    use $crate::RegistryApi;
+                    API.register_lazy_async::<T, F, Fut>(factory)
+                }
+
+                /// Retrieve a value, running its async provider first if one is pending.
+                pub async fn get_async<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.get_async::<T>().await
+                }
+
+                /// Register an async provider that builds `T` from other registry entries.
+                pub fn register_provider_async<T, F, Fut>(provider: F)
+                where
+                    T: Send + Sync + 'static,
+                    F: Fn($crate::ResolveCtx<Api>) -> Fut + Send + Sync + 'static,
+                    Fut: std::future::Future<Output = Result<T, $crate::RegistryError>> + Send + 'static,
+                {
+                    use $crate::RegistryApi;
+                    API.register_provider_async::<T, F, Fut>(provider)
+                }
+
+                /// Retrieve `T`, awaiting its providers and those of its dependencies if needed.
+                pub async fn resolve_async<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.resolve_async::<T>().await
+                }
+            }
+
+            $crate::__cfg_tokio! {
+                /// Subscribe to the value of `T` and every later replacement.
+                pub fn subscribe<T: Send + Sync + 'static>(
+                ) -> Result<$crate::sync::watch::Receiver<Arc<T>>, $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.subscribe::<T>()
+                }
+
+                /// Register a service whose async `shutdown()` runs on `shutdown_with_timeout`.
+                #[track_caller]
+                pub fn register_shutdown<T: $crate::AsyncShutdown>(value: T) {
+                    use $crate::RegistryApi;
+                    API.register_shutdown(value)
+                }
+
+                /// Call every enrolled service's `shutdown()` concurrently, each bounded by `timeout`.
+                pub async fn shutdown_with_timeout(timeout: std::time::Duration) -> $crate::ShutdownReport {
+                    use $crate::RegistryApi;
+                    API.shutdown_with_timeout(timeout).await
+                }
+
+                /// Register a service whose async `check()` is probed by `health_report_async`.
+                #[track_caller]
+                pub fn register_health_checked<T: $crate::AsyncHealthCheck>(value: T) {
+                    use $crate::RegistryApi;
+                    API.register_health_checked(value)
+                }
+
+                /// Probe every enrolled service concurrently, each bounded by `timeout`.
+                pub async fn health_report_async(timeout: std::time::Duration) -> $crate::HealthReport {
+                    use $crate::RegistryApi;
+                    API.health_report_async(timeout).await
+                }
+
+                /// Receive every registry event through a bounded channel.
+                pub fn subscribe_events(
+                    capacity: usize,
+                ) -> $crate::sync::mpsc::Receiver<$crate::RegistryEvent> {
+                    use $crate::RegistryApi;
+                    API.subscribe_events(capacity)
+                }
+            }
+
+            $crate::__cfg_stats! {
+                /// Hit and miss counts of `get` lookups of `T`.
+                pub fn stats<T: 'static>() -> $crate::TypeStats {
+                    use $crate::RegistryApi;
+                    API.stats::<T>()
+                }
+
+                /// Lookup counters of every type looked up so far.
+                pub fn stats_all() -> Vec<$crate::TypeStats> {
+                    use $crate::RegistryApi;
+                    API.stats_all()
+                }
+
+                /// Reset all lookup counters.
+                pub fn reset_stats() {
+                    use $crate::RegistryApi;
+                    API.reset_stats()
+                }
+            }
+
+            $crate::__cfg_env! {
+                /// Build `T` from the environment variables starting with `prefix` and register it.
+                #[track_caller]
+                pub fn register_from_env<T>(prefix: &str) -> Result<(), $crate::RegistryError>
+                where
+                    T: $crate::__serde::de::DeserializeOwned + Send + Sync + 'static,
+                {
+                    use $crate::RegistryApi;
+                    API.register_from_env::<T>(prefix)
+                }
+            }
+
+            $crate::__cfg_config_file! {
+                /// Deserialize the configuration file at `path` into `T` and register it.
+                #[track_caller]
+                pub fn load_config_file<T>(
+                    path: impl AsRef<std::path::Path>,
+                ) -> Result<(), $crate::RegistryError>
+                where
+                    T: $crate::__serde::de::DeserializeOwned + Send + Sync + 'static,
+                {
+                    use $crate::RegistryApi;
+                    API.load_config_file::<T>(path)
+                }
+            }
+
+            $crate::__cfg_serde! {
+                /// Register a value that is included in `export_json()`.
+                #[track_caller]
+                pub fn register_serializable<T>(value: T)
+                where
+                    T: $crate::__serde::Serialize
+                        + $crate::__serde::de::DeserializeOwned
+                        + Send
+                        + Sync
+                        + 'static,
+                {
+                    use $crate::RegistryApi;
+                    API.register_serializable(value)
+                }
+
+                /// Serialize every serializable entry into a JSON object keyed by type name.
+                pub fn export_json() -> String {
+                    use $crate::RegistryApi;
+                    API.export_json()
+                }
+
+                /// A checksum of the registered types, generations and serializable values.
+                pub fn fingerprint_with_values() -> u64 {
+                    use $crate::RegistryApi;
+                    API.fingerprint_with_values()
+                }
+            }
+
+            $crate::__cfg_bincode! {
+                /// Encode every serializable entry into a binary snapshot.
+                pub fn export_snapshot() -> Result<Vec<u8>, $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.export_snapshot()
+                }
+
+                /// Register the values of a snapshot made by `export_snapshot()`.
+                pub fn import_snapshot(bytes: &[u8]) -> Result<(), $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.import_snapshot(bytes)
+                }
+            }
+
+            $crate::__cfg_unix! {
+                /// Run `reload` whenever the process receives `SIGHUP`, until the guard is dropped.
+                pub fn reload_on_sighup(
+                    reload: impl Fn() + Send + 'static,
+                ) -> std::io::Result<$crate::SighupGuard> {
+                    use $crate::RegistryApi;
+                    API.reload_on_sighup(reload)
+                }
+            }
+
+            $crate::__cfg_file_backend! {
+                /// Restore serializable entries from `path` and rewrite it after every mutation.
+                pub fn enable_file_backend(
+                    path: impl AsRef<std::path::Path>,
+                ) -> Result<(), $crate::RegistryError> {
+                    use $crate::RegistryApi;
+                    API.enable_file_backend(path)
+                }
+
+                /// Stop writing the backing file.
+                pub fn disable_file_backend() {
+                    use $crate::RegistryApi;
+                    API.disable_file_backend()
+                }
+            }
+
+            $crate::__cfg_log! {
+                /// Forward events at `level` or above to the `log` facade.
+                pub fn enable_log_bridge(level: $crate::EventLevel) {
+                    use $crate::RegistryApi;
+                    API.enable_log_bridge(level)
+                }
+
+                /// Stop forwarding events to the `log` facade.
+                pub fn disable_log_bridge() {
+                    use $crate::RegistryApi;
+                    API.disable_log_bridge()
+                }
+            }
+
+            $crate::__cfg_audit! {
+                /// Append every mutation to the audit log at `path` (JSON lines).
+                pub fn enable_audit_log(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+                    use $crate::RegistryApi;
+                    API.enable_audit_log(path)
+                }
+
+                /// Append every mutation to the audit log at `path`, fsync'ed per `policy`.
+                pub fn enable_audit_log_with(
+                    path: impl AsRef<std::path::Path>,
+                    policy: $crate::FsyncPolicy,
+                ) -> std::io::Result<()> {
+                    use $crate::RegistryApi;
+                    API.enable_audit_log_with(path, policy)
+                }
+
+                /// Stop writing the audit log.
+                pub fn disable_audit_log() -> std::io::Result<()> {
+                    use $crate::RegistryApi;
+                    API.disable_audit_log()
+                }
+            }
+
+            $crate::__cfg_contention_stats! {
+                /// Snapshot the lock contention counters of this registry.
+                pub fn contention_stats() -> $crate::ContentionStats {
+                    use $crate::RegistryApi;
+                    API.contention_stats()
+                }
+            }
+        }
+    };
+}
+
+/// Creates several isolated registries in one declaration.
+///
+/// `define_registries!(app, cache, metrics)` is shorthand for one
+/// [`define_registry!`] per name. Naming a group after `=>` also generates a
+/// unit struct of that name whose `set_trace_callback` installs one shared
+/// callback on every registry of the group.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registries;
+///
+/// define_registries!(app, cache, metrics => all);
+///
+/// all::set_trace_callback(|event| println!("[{}] {event}", event.registry()));
+///
+/// app::register(1u32);
+/// cache::register("redis".to_string());
+/// assert!(!metrics::contains::<u32>().unwrap());
+/// assert_eq!(all::names(), ["app", "cache", "metrics"]);
+/// ```
+#[macro_export]
+macro_rules! define_registries {
+    ($($name:ident),+ $(,)?) => {
+        $($crate::define_registry!($name);)+
+    };
+    ($($name:ident),+ => $group:ident) => {
+        $($crate::define_registry!($name);)+
+
+        // A struct rather than a module: unlike a nested module, its methods
+        // can name the registries when the group is declared inside a function.
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct $group;
+
+        impl $group {
+            /// Names of the registries in the group.
+            pub fn names() -> &'static [&'static str] {
+                &[$(stringify!($name)),+]
+            }
+
+            /// Set one tracing callback on every registry of the group.
+            pub fn set_trace_callback(
+                callback: impl Fn(&$crate::RegistryEvent) + Send + Sync + 'static,
+            ) {
+                let callback = std::sync::Arc::new(callback);
+                $({
+                    let callback = callback.clone();
+                    $name::set_trace_callback(move |event| callback(event));
+                })+
+            }
+
+            /// Clear the tracing callback of every registry of the group.
+            pub fn clear_trace_callback() {
+                $($name::clear_trace_callback();)+
+            }
+        }
+    };
+}
+
+/// Creates a strongly-typed accessor for one type of a registry.
+///
+/// `define_singleton!(CONFIG: AppConfig in app)` generates a unit struct
+/// `CONFIG` with `set`, `get`, `try_get` and `is_set`, backed by the registry
+/// `app`, so the most common one-type usage needs no turbofish at call sites.
+/// `get` panics when the value is missing, pointing at the caller.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, define_singleton};
+///
+/// define_registry!(app);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct AppConfig {
+///     port: u16,
+/// }
+///
+/// define_singleton!(CONFIG: AppConfig in app);
+///
+/// assert!(!CONFIG::is_set());
+/// CONFIG::set(AppConfig { port: 8080 });
+/// assert_eq!(CONFIG::get().port, 8080);
+/// ```
+#[macro_export]
+macro_rules! define_singleton {
+    // `in` may not follow a `ty` fragment, so collect the type's tokens first
+    ($(#[$attr:meta])* $vis:vis $singleton:ident : $($rest:tt)+) => {
+        $crate::define_singleton!(@ty [$(#[$attr])*] [$vis] $singleton [] $($rest)+);
+    };
+    (@ty [$($attrs:tt)*] [$vis:vis] $singleton:ident [$($ty:tt)+] in $($registry:ident)::+) => {
+        $crate::define_singleton!(@emit [$($attrs)*] [$vis] $singleton ($($ty)+) $($registry)::+);
+    };
+    (@ty [$($attrs:tt)*] [$vis:vis] $singleton:ident [$($ty:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::define_singleton!(@ty [$($attrs)*] [$vis] $singleton [$($ty)* $next] $($rest)*);
+    };
+    (@emit [$($attrs:tt)*] [$vis:vis] $singleton:ident ($ty:ty) $($registry:ident)::+) => {
+        $($attrs)*
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $singleton;
+
+        impl $singleton {
+            /// Register the value, replacing any previous one.
+            #[track_caller]
+            pub fn set(value: $ty) {
+                $($registry)::+::register::<$ty>(value)
+            }
+
+            /// The registered value.
+            ///
+            /// # Panics
+            ///
+            /// If no value is registered.
+            #[track_caller]
+            pub fn get() -> std::sync::Arc<$ty> {
+                match $($registry)::+::get::<$ty>() {
+                    Ok(value) => value,
+                    Err(err) => panic!("{}: {}", stringify!($singleton), err),
+                }
+            }
+
+            /// The registered value, or `None` if there is none.
+            pub fn try_get() -> Option<std::sync::Arc<$ty>> {
+                $($registry)::+::try_get::<$ty>()
+            }
+
+            /// Whether a value is registered.
+            pub fn is_set() -> bool {
+                $($registry)::+::contains::<$ty>().unwrap_or(false)
+            }
+        }
+    };
+}
+
+/// Registers a throwaway implementation of a contract trait.
+///
+/// `register_noop!(app, dyn Logger { fn log(&self, _: &str) {} })` declares a
+/// unit struct, implements `Logger` for it with the given items, and registers
+/// it as `Arc<dyn Logger>`, so a test can satisfy a lookup without writing a
+/// mock by hand. Items can return canned values to act as a stub, and may be
+/// left out for methods with a default body. The trait needs `Send + Sync`
+/// supertraits, like any contract stored as `Arc<dyn Trait>`. Requires the
+/// `test-util` feature.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, register_noop};
+/// use std::sync::Arc;
+///
+/// trait Logger: Send + Sync {
+///     fn log(&self, message: &str);
+/// }
+///
+/// trait Clock: Send + Sync {
+///     fn now(&self) -> u64;
+/// }
+///
+/// define_registry!(app);
+///
+/// register_noop!(app, dyn Logger { fn log(&self, _: &str) {} });
+/// register_noop!(app, dyn Clock { fn now(&self) -> u64 { 42 } });
+///
+/// app::get::<Arc<dyn Logger>>().unwrap().log("discarded");
+/// assert_eq!(app::get::<Arc<dyn Clock>>().unwrap().now(), 42);
+/// ```
+#[cfg(any(test, feature = "test-util"))]
+#[macro_export]
+macro_rules! register_noop {
+    ($($registry:ident)::+, dyn $contract:path { $($items:tt)* }) => {{
+        struct Noop;
+
+        impl $contract for Noop {
+            $($items)*
+        }
+
+        $($registry)::+::register(::std::sync::Arc::new(Noop) as ::std::sync::Arc<dyn $contract>)
+    }};
+}
+
+/// Checks that every one of several types is registered, under one lock.
+///
+/// `contains_all!(app, Config, Database)` returns
+/// `Result<Vec<&'static str>, RegistryError>` with the type names that are
+/// missing, so an empty list means the precondition holds. All types are
+/// checked against the same state, which makes it suitable for asserting the
+/// wiring once at startup.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{contains_all, define_registry};
+///
+/// define_registry!(app);
+///
+/// struct Config;
+/// struct Database;
+///
+/// app::register(Config);
+///
+/// let missing = contains_all!(app, Config, Database).unwrap();
+/// assert_eq!(missing, [std::any::type_name::<Database>()]);
+///
+/// app::register(Database);
+/// assert!(contains_all!(app, Config, Database).unwrap().is_empty());
+/// ```
+#[macro_export]
+macro_rules! contains_all {
+    ($($registry:ident)::+, $($ty:ty),+ $(,)?) => {
+        $($registry)::+::missing_types(&[
+            $((::std::any::TypeId::of::<$ty>(), ::std::any::type_name::<$ty>())),+
+        ])
+    };
+}
+
+/// Checks that at least one of several types is registered, under one lock.
+///
+/// `contains_any!(app, FileLogger, StdoutLogger)` returns
+/// `Result<Vec<&'static str>, RegistryError>`: empty if any of the types is
+/// registered, otherwise every type name, all of them being missing. For
+/// alternatives where one implementation of several must be wired.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{contains_any, define_registry};
+///
+/// define_registry!(app);
+///
+/// struct FileLogger;
+/// struct StdoutLogger;
+///
+/// assert_eq!(contains_any!(app, FileLogger, StdoutLogger).unwrap().len(), 2);
+///
+/// app::register(StdoutLogger);
+/// assert!(contains_any!(app, FileLogger, StdoutLogger).unwrap().is_empty());
+/// ```
+#[macro_export]
+macro_rules! contains_any {
+    ($($registry:ident)::+, $($ty:ty),+ $(,)?) => {
+        $($registry)::+::missing_types(&[
+            $((::std::any::TypeId::of::<$ty>(), ::std::any::type_name::<$ty>())),+
+        ])
+        .map(|missing| {
+            if missing.len() == [$(::std::any::type_name::<$ty>()),+].len() {
+                missing
+            } else {
+                ::std::vec::Vec::new()
+            }
+        })
+    };
+}
+
+/// Creates a single-threaded registry module for `!Send` types.
+///
+/// Works like [`define_registry!`], but values only need to be `'static`: they are
+/// stored as `Rc<T>` in a thread-local map, so `Rc`-based state and main-thread
+/// handles can be registered. Each thread sees its own registry, and the `Rc`
+/// handles returned by `get` cannot be sent to another thread.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_local_registry;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// define_local_registry!(ui);
+///
+/// struct AppState {
+///     clicks: RefCell<u32>,
+/// }
+///
+/// ui::register(AppState { clicks: RefCell::new(0) });
+///
+/// let state: Rc<AppState> = ui::get().unwrap();
+/// *state.clicks.borrow_mut() += 1;
+/// assert_eq!(*ui::get::<AppState>().unwrap().clicks.borrow(), 1);
+/// ```
+#[macro_export]
+macro_rules! define_local_registry {
+    ($name:ident) => {
+        pub mod $name {
+            use std::rc::Rc;
+
+            std::thread_local! {
+                // Storage for registered values and the trace callback (module-private)
+                static STORAGE: $crate::LocalStorage = $crate::LocalStorage::default();
+            }
+
+            /// Zero-sized type that implements the local registry API.
+            struct Api;
+
+            impl $crate::LocalRegistryApi for Api {
+                fn storage() -> &'static std::thread::LocalKey<$crate::LocalStorage> {
+                    &STORAGE
+                }
+
+                fn name() -> &'static str {
+                    stringify!($name)
+                }
+            }
+
+            /// Convenient constant for accessing the registry API.
+            const API: Api = Api;
+
+            /// Name of the registry, as reported in its events.
+            pub fn name() -> &'static str {
+                stringify!($name)
+            }
+
+            /// Register a value on the current thread.
+            pub fn register<T: 'static>(value: T) {
+                use $crate::LocalRegistryApi;
+                API.register(value)
+            }
+
+            /// Register an Rc-wrapped value on the current thread.
+            pub fn register_rc<T: 'static>(value: Rc<T>) {
+                use $crate::LocalRegistryApi;
+                API.register_rc(value)
+            }
+
+            /// Retrieve a value registered on the current thread.
+            #[track_caller]
+            pub fn get<T: 'static>() -> Result<Rc<T>, $crate::RegistryError> {
+                use $crate::LocalRegistryApi;
+                API.get()
+            }
+
+            /// Retrieve a cloned value registered on the current thread.
+            #[track_caller]
+            pub fn get_cloned<T: Clone + 'static>() -> Result<T, $crate::RegistryError> {
+                use $crate::LocalRegistryApi;
+                API.get_cloned()
+            }
+
+            /// Retrieve a value, returning None if not registered on the current thread.
+            pub fn try_get<T: 'static>() -> Option<Rc<T>> {
+                use $crate::LocalRegistryApi;
+                API.try_get()
+            }
+
+            /// Check if a type is registered on the current thread.
+            pub fn contains<T: 'static>() -> bool {
+                use $crate::LocalRegistryApi;
+                API.contains::<T>()
+            }
+
+            /// Set a tracing callback for registry operations on the current thread.
+            pub fn set_trace_callback(callback: impl Fn(&$crate::RegistryEvent) + 'static) {
+                use $crate::LocalRegistryApi;
+                API.set_trace_callback(callback)
+            }
+
+            /// Clear the tracing callback of the current thread.
+            pub fn clear_trace_callback() {
+                use $crate::LocalRegistryApi;
+                API.clear_trace_callback()
+            }
+
+            $crate::__cfg_test_util! {
+                /// Clear the values registered on the current thread (feature `test-util`).
+                pub fn clear() {
+                    use $crate::LocalRegistryApi;
+                    API.clear()
+                }
+            }
+        }
+    };
+}
+
+/// Creates an async registry module with `await`-able free functions (features `async` / `tokio`).
+///
+/// Works like [`define_registry!`], but storage is guarded by an async `RwLock`
+/// and `register`, `get`, `contains` and friends are `async fn`s.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_async_registry;
+/// use std::sync::Arc;
+///
+/// define_async_registry!(services);
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// services::register(42i32).await;
+///
+/// let num: Arc<i32> = services::get().await.unwrap();
+/// assert_eq!(*num, 42);
+/// # });
+/// ```
+#[cfg(any(feature = "async", feature = "tokio"))]
+#[macro_export]
+macro_rules! define_async_registry {
+    ($name:ident) => {
+        pub mod $name {
+            use std::any::TypeId;
+            use std::collections::HashMap;
+            use std::sync::Arc;
+            use $crate::sync::{AsyncRwLock, LazyLock, Mutex};
+
+            // Storage for registered values (module-private)
+            static STORAGE: LazyLock<AsyncRwLock<HashMap<TypeId, $crate::RegistryEntry>>> =
+                LazyLock::new(|| AsyncRwLock::new(HashMap::new()));
+
+            // Trace callback storage (module-private)
+            // Note: This type matches TraceCallback in async_registry.rs - keep in sync
+            type TraceCallback =
+                LazyLock<Mutex<Option<Arc<dyn Fn(&$crate::RegistryEvent) + Send + Sync>>>>;
+            static TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
+
+            /// Zero-sized type that implements the async registry API.
+            struct Api;
+
+            impl $crate::AsyncRegistryApi for Api {
+                fn storage(
+                ) -> &'static LazyLock<AsyncRwLock<HashMap<TypeId, $crate::RegistryEntry>>>
+                {
+                    &STORAGE
+                }
+
+                fn trace() -> &'static TraceCallback {
+                    &TRACE
+                }
+
+                fn name() -> &'static str {
+                    stringify!($name)
+                }
+            }
+
+            /// Convenient constant for accessing the registry API.
+            const API: Api = Api;
+
+            /// Name of the registry, as reported in its events.
+            pub fn name() -> &'static str {
+                stringify!($name)
+            }
+
+            /// Register a value in the registry.
+            pub async fn register<T: Send + Sync + 'static>(value: T) {
+                use $crate::AsyncRegistryApi;
+                API.register(value).await
+            }
+
+            /// Register an Arc-wrapped value in the registry.
+            pub async fn register_arc<T: Send + Sync + 'static>(value: Arc<T>) {
+                use $crate::AsyncRegistryApi;
+                API.register_arc(value).await
+            }
+
+            /// Retrieve a value from the registry.
+            pub async fn get<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
+                use $crate::AsyncRegistryApi;
+                API.get().await
+            }
+
+            /// Retrieve a cloned value from the registry.
+            pub async fn get_cloned<T: Send + Sync + Clone + 'static>(
+            ) -> Result<T, $crate::RegistryError> {
+                use $crate::AsyncRegistryApi;
+                API.get_cloned().await
+            }
+
+            /// Retrieve a value from the registry, returning None if not registered.
+            pub async fn try_get<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+                use $crate::AsyncRegistryApi;
+                API.try_get().await
+            }
+
+            /// Check if a type is registered in the registry.
+            pub async fn contains<T: Send + Sync + 'static>() -> bool {
+                use $crate::AsyncRegistryApi;
+                API.contains::<T>().await
+            }
+
+            /// Set a tracing callback for registry operations.
+            pub fn set_trace_callback(
+                callback: impl Fn(&$crate::RegistryEvent) + Send + Sync + 'static,
+            ) {
+                use $crate::AsyncRegistryApi;
+                API.set_trace_callback(callback)
+            }
+
+            /// Clear the tracing callback.
+            pub fn clear_trace_callback() {
+                use $crate::AsyncRegistryApi;
+                API.clear_trace_callback()
+            }
+
+            $crate::__cfg_test_util! {
+                /// Clear the registry (feature `test-util`).
+                pub async fn clear() {
+                    use $crate::AsyncRegistryApi;
+                    API.clear().await
+                }
+            }
+        }
+    };
+}
+
+/// Creates a task-local registry module for async request scopes (feature `tokio`).
+///
+/// Storage lives in a `tokio` task-local: every `name::scope(future)` runs the
+/// future with its own empty registry, so per-request values never leak across
+/// concurrent requests. `register`, `get` and friends are plain functions that
+/// panic when called outside of a scope.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_task_local_registry;
+///
+/// define_task_local_registry!(request);
+///
+/// struct TenantId(&'static str);
+///
+/// async fn handle() -> &'static str {
+///     request::get::<TenantId>().unwrap().0
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let tenant = request::scope(async {
+///     request::register(TenantId("acme"));
+///     handle().await
+/// })
+/// .await;
+///
+/// assert_eq!(tenant, "acme");
+/// # });
+/// ```
+#[cfg(feature = "tokio")]
 #[macro_export]
-macro_rules! define_registry {
+macro_rules! define_task_local_registry {
     ($name:ident) => {
         pub mod $name {
-            use std::sync::{Arc, LazyLock, Mutex};
-            use std::collections::HashMap;
-            use std::any::{TypeId, Any};
+            use std::sync::Arc;
+            use $crate::sync::{LazyLock, Mutex};
 
-            // Storage for registered values (module-private)
-            static STORAGE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
-                LazyLock::new(|| Mutex::new(HashMap::new()));
+            // Storage for registered values, one map per scope (module-private)
+            $crate::sync::task_local! {
+                static STORAGE: $crate::TaskLocalStorage;
+            }
 
             // Trace callback storage (module-private)
-            // Note: This type matches TraceCallback in registry_trait.rs - keep in sync
-            type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&$crate::RegistryEvent) + Send + Sync>>>>;
+            // Note: This type matches TraceCallback in task_local_registry.rs - keep in sync
+            type TraceCallback =
+                LazyLock<Mutex<Option<Arc<dyn Fn(&$crate::RegistryEvent) + Send + Sync>>>>;
             static TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
 
-            /// Zero-sized type that implements the registry API.
-            ///
-            /// All registry operations are provided by the `RegistryApi` trait's
-            /// default implementations. This struct only provides access to the statics.
+            /// Zero-sized type that implements the task-local registry API.
             struct Api;
 
-            impl $crate::RegistryApi for Api {
-                fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> {
+            impl $crate::TaskLocalRegistryApi for Api {
+                fn storage() -> &'static $crate::sync::LocalKey<$crate::TaskLocalStorage> {
                     &STORAGE
                 }
 
@@ -62,73 +1546,321 @@ macro_rules! define_registry {
                     &TRACE
                 }
 
-                // All other methods (register, get, contains, etc.) are provided by
-                // the trait's default implementations!
+                fn name() -> &'static str {
+                    stringify!($name)
+                }
             }
 
             /// Convenient constant for accessing the registry API.
             const API: Api = Api;
 
-            // Free functions for ergonomic usage - they delegate to API
+            /// Name of the registry, as reported in its events.
+            pub fn name() -> &'static str {
+                stringify!($name)
+            }
 
-            /// Register a value in the registry.
+            /// Run `future` with a fresh, empty registry.
+            pub fn scope<F: std::future::Future>(
+                future: F,
+            ) -> $crate::sync::TaskLocalFuture<$crate::TaskLocalStorage, F> {
+                use $crate::TaskLocalRegistryApi;
+                API.scope(future)
+            }
+
+            /// Run the closure `f` with a fresh, empty registry.
+            pub fn sync_scope<R>(f: impl FnOnce() -> R) -> R {
+                use $crate::TaskLocalRegistryApi;
+                API.sync_scope(f)
+            }
+
+            /// Register a value in the current scope.
             pub fn register<T: Send + Sync + 'static>(value: T) {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.register(value)
             }
 
-            /// Register an Arc-wrapped value in the registry.
+            /// Register an Arc-wrapped value in the current scope.
             pub fn register_arc<T: Send + Sync + 'static>(value: Arc<T>) {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.register_arc(value)
             }
 
-            /// Retrieve a value from the registry.
+            /// Retrieve a value from the current scope.
+            #[track_caller]
             pub fn get<T: Send + Sync + 'static>() -> Result<Arc<T>, $crate::RegistryError> {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.get()
             }
 
-            /// Retrieve a cloned value from the registry.
+            /// Retrieve a cloned value from the current scope.
+            #[track_caller]
             pub fn get_cloned<T: Send + Sync + Clone + 'static>() -> Result<T, $crate::RegistryError> {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.get_cloned()
             }
 
-            /// Retrieve a value from the registry, returning None if not registered.
+            /// Retrieve a value from the current scope, returning None if not registered.
             pub fn try_get<T: Send + Sync + 'static>() -> Option<Arc<T>> {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.try_get()
             }
 
-            /// Check if a type is registered in the registry.
-            pub fn contains<T: Send + Sync + 'static>() -> Result<bool, $crate::RegistryError> {
-                use $crate::RegistryApi;
+            /// Check if a type is registered in the current scope.
+            pub fn contains<T: Send + Sync + 'static>() -> bool {
+                use $crate::TaskLocalRegistryApi;
                 API.contains::<T>()
             }
 
-            /// Set a tracing callback for registry operations.
-            pub fn set_trace_callback(callback: impl Fn(&$crate::RegistryEvent) + Send + Sync + 'static) {
-                use $crate::RegistryApi;
+            /// Set a tracing callback for registry operations in every scope.
+            pub fn set_trace_callback(
+                callback: impl Fn(&$crate::RegistryEvent) + Send + Sync + 'static,
+            ) {
+                use $crate::TaskLocalRegistryApi;
                 API.set_trace_callback(callback)
             }
 
             /// Clear the tracing callback.
             pub fn clear_trace_callback() {
-                use $crate::RegistryApi;
+                use $crate::TaskLocalRegistryApi;
                 API.clear_trace_callback()
             }
-
-            /// Clear the registry.
-            #[doc(hidden)]
-            pub fn clear() {
-                use $crate::RegistryApi;
-                API.clear()
-            }
         }
     };
 }
 
+// -------------------------------------------------------------------------------------------------
+// Feature gates for macro expansions
+// -------------------------------------------------------------------------------------------------
+//
+// A `#[cfg(feature = "...")]` written inside `define_registry!` would be evaluated against the
+// features of the crate invoking the macro, not this one. These helpers are defined according to
+// this crate's features and either emit or swallow the items passed to them.
+
+/// Build the `RegistryConfig` of `define_registry!` from its options.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __registry_config {
+    ($config:expr; $(,)?) => {
+        $config
+    };
+    ($config:expr; poison_policy: $policy:ident $(, $($rest:tt)*)?) => {
+        $crate::__registry_config!(
+            $config.with_poison_policy($crate::PoisonPolicy::$policy); $($($rest)*)?
+        )
+    };
+    ($config:expr; trace: enabled $(, $($rest:tt)*)?) => {
+        $crate::__registry_config!($config.with_trace(true); $($($rest)*)?)
+    };
+    ($config:expr; trace: disabled $(, $($rest:tt)*)?) => {
+        $crate::__registry_config!($config.with_trace(false); $($($rest)*)?)
+    };
+    ($config:expr; capacity: $capacity:expr $(, $($rest:tt)*)?) => {
+        $crate::__registry_config!($config.with_capacity($capacity); $($($rest)*)?)
+    };
+    ($config:expr; registration: $mode:ident $(, $($rest:tt)*)?) => {
+        $crate::__registry_config!(
+            $config.with_registration($crate::RegistrationMode::$mode); $($($rest)*)?
+        )
+    };
+}
+
+// Test utilities are also available to this crate's own unit tests
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_test_util {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(any(test, feature = "test-util")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_test_util {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "contention-stats")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_contention_stats {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "contention-stats"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_contention_stats {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "stats")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_stats {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "stats"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_stats {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "env")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_env {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "env"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_env {
+    ($($item:item)*) => {};
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_config_file {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(any(feature = "toml", feature = "json")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_config_file {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_serde {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_serde {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "bincode")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_bincode {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "bincode"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_bincode {
+    ($($item:item)*) => {};
+}
+
+#[cfg(all(unix, feature = "unix"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_unix {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(all(unix, feature = "unix")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_unix {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "file-backend")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_file_backend {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "file-backend"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_file_backend {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "anymap")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_anymap {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "anymap"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_anymap {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "log")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_log {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "log"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_log {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "audit")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_audit {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "audit"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_audit {
+    ($($item:item)*) => {};
+}
+
+#[cfg(feature = "tokio")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_tokio {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "tokio"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_tokio {
+    ($($item:item)*) => {};
+}
+
+#[cfg(any(feature = "async", feature = "tokio"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_async {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[cfg(not(any(feature = "async", feature = "tokio")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_async {
+    ($($item:item)*) => {};
+}
+
 #[cfg(test)]
 mod tests {
     // use crate::RegistryApi;
@@ -148,6 +1880,162 @@ mod tests {
         assert!(!test_reg::contains::<f64>().unwrap());
     }
 
+    #[test]
+    fn test_initial_values() {
+        define_registry!(seeded {
+            100i32,
+            String::from("default"),
+        });
+
+        // Present before anything was registered
+        assert!(seeded::contains::<String>().unwrap());
+        assert_eq!(*seeded::get::<i32>().unwrap(), 100);
+
+        // Replaceable like any other value
+        seeded::register(200i32);
+        assert_eq!(*seeded::get::<i32>().unwrap(), 200);
+        assert_eq!(seeded::describe()[1].generation, 2);
+    }
+
+    #[test]
+    fn test_get_expect() {
+        define_registry!(expect_reg);
+
+        expect_reg::register(7u8);
+        assert_eq!(*expect_reg::get_expect::<u8>(), 7);
+
+        let err = std::panic::catch_unwind(|| expect_reg::get_expect::<u16>()).unwrap_err();
+        let line = line!() - 1;
+        let message = err.downcast::<String>().unwrap();
+        assert!(message.starts_with(&format!(
+            "expect_reg::get_expect::<u16>() at {}:{line}:",
+            file!()
+        )));
+        assert!(message.ends_with(": Type not found in registry: u16\nregistered types: [u8]"));
+    }
+
+    #[test]
+    fn test_define_registries() {
+        use std::sync::Mutex;
+
+        define_registries!(group_a, group_b => group);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        group::set_trace_callback(move |e| sink.lock().unwrap().push(e.registry()));
+
+        group_a::register(1u8);
+        assert!(!group_b::contains::<u8>().unwrap());
+        group::clear_trace_callback();
+        let _ = group_a::contains::<u8>();
+
+        assert_eq!(group::names(), ["group_a", "group_b"]);
+        assert_eq!(*events.lock().unwrap(), ["group_a", "group_a", "group_b"]);
+    }
+
+    #[test]
+    fn test_define_singleton() {
+        define_registry!(singleton_reg);
+        define_singleton!(PORT: u16 in singleton_reg);
+
+        assert!(!PORT::is_set());
+        assert_eq!(PORT::try_get(), None);
+        let missing = std::panic::catch_unwind(|| PORT::get()).unwrap_err();
+        let message = missing.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("PORT: Type not found in registry: u16 (at src/macros.rs:"));
+
+        PORT::set(8080);
+        assert!(PORT::is_set());
+        assert_eq!(*PORT::get(), 8080);
+        assert_eq!(*singleton_reg::get::<u16>().unwrap(), 8080);
+    }
+
+    #[test]
+    fn test_register_noop() {
+        trait Logger: Send + Sync {
+            fn log(&self, message: &str);
+
+            fn level(&self) -> u8 {
+                0
+            }
+        }
+
+        trait Repository<T>: Send + Sync {
+            fn find(&self, id: u32) -> Option<T>;
+        }
+
+        define_registry!(noop_reg);
+
+        register_noop!(noop_reg, dyn Logger { fn log(&self, _: &str) {} });
+        register_noop!(noop_reg, dyn Repository<String> {
+            fn find(&self, id: u32) -> Option<String> {
+                Some(id.to_string())
+            }
+        });
+
+        let logger = noop_reg::get::<Arc<dyn Logger>>().unwrap();
+        logger.log("ignored");
+        assert_eq!(logger.level(), 0);
+        let repository = noop_reg::get::<Arc<dyn Repository<String>>>().unwrap();
+        assert_eq!(repository.find(7).as_deref(), Some("7"));
+    }
+
+    #[test]
+    #[cfg(feature = "macros")]
+    fn test_singleton_attribute() {
+        define_registry!(attr_reg);
+
+        #[crate::singleton(registry = attr_reg)]
+        #[derive(Debug)]
+        struct Settings {
+            port: u16,
+        }
+
+        assert!(std::panic::catch_unwind(|| Settings::instance()).is_err());
+
+        Settings { port: 8080 }.install();
+        assert_eq!(Settings::instance().port, 8080);
+        assert!(attr_reg::contains::<Settings>().unwrap());
+    }
+
+    #[cfg(feature = "macros")]
+    #[crate::isolated_registry(crate = crate)]
+    #[test]
+    fn test_isolated_registry_attribute(registry: crate::Registry) {
+        assert_eq!(registry.name(), "test_isolated_registry_attribute");
+        assert!(registry.is_empty());
+        registry.register(1u8);
+        assert_eq!(*registry.get::<u8>().unwrap(), 1);
+    }
+
+    #[cfg(feature = "macros")]
+    const ISOLATED_ATTRIBUTE_LINE: u32 = line!() + 2;
+
+    #[cfg(feature = "macros")]
+    #[crate::isolated_registry(crate = crate)]
+    #[test]
+    fn test_isolated_registry_keeps_body_spans(registry: crate::Registry) {
+        // The lookup miss is reported at its own line, not at the attribute
+        let err = registry.get::<u16>().unwrap_err();
+        assert!(matches!(
+            err,
+            crate::RegistryError::TypeNotFound { location: Some(location), .. }
+                if location.line() == ISOLATED_ATTRIBUTE_LINE + 5
+        ));
+    }
+
+    #[cfg(all(feature = "macros", feature = "tokio"))]
+    #[crate::isolated_registry(crate = crate)]
+    #[tokio::test]
+    async fn test_isolated_registry_attribute_async(
+        registry: crate::RegistryHandle,
+    ) -> Result<(), crate::RegistryError> {
+        registry.register(2u8);
+        tokio::task::yield_now().await;
+        assert_eq!(*registry.get::<u8>()?, 2);
+        Ok(())
+    }
+
     #[test]
     fn test_multiple_registries() {
         define_registry!(reg_a);
@@ -201,6 +2089,10 @@ mod tests {
         let cloned: i32 = extra_test::get_cloned().unwrap();
         assert_eq!(cloned, 99);
 
+        // Test strong_count
+        assert_eq!(extra_test::strong_count::<i32>(), Some(1));
+        assert_eq!(extra_test::strong_count::<u8>(), None);
+
         // Test clear_trace_callback
         extra_test::set_trace_callback(|_| {});
         extra_test::clear_trace_callback(); // Just verify it doesn't panic
@@ -215,4 +2107,55 @@ mod tests {
         let val = try_get_test::try_get::<i32>().expect("should be Some after register");
         assert_eq!(*val, 77);
     }
+
+    #[test]
+    #[cfg(feature = "contention-stats")]
+    fn test_contention_stats() {
+        define_registry!(contention_test);
+
+        contention_test::register(1i32);
+        let _ = contention_test::contains::<i32>();
+
+        let stats = contention_test::contention_stats();
+        assert_eq!(stats.storage.acquisitions, 2);
+        assert_eq!(stats.storage.contended, 0);
+    }
+
+    #[test]
+    fn test_capacity_functions() {
+        define_registry!(capacity_test);
+
+        assert_eq!(capacity_test::capacity(), 0);
+        capacity_test::register(5u64);
+        assert!(capacity_test::capacity() >= 1);
+        assert!(capacity_test::approx_heap_bytes() >= std::mem::size_of::<u64>());
+
+        capacity_test::shrink_to_fit();
+        assert!(capacity_test::capacity() >= 1);
+
+        capacity_test::reserve(200);
+        assert!(capacity_test::capacity() >= 201);
+
+        define_registry!(presized, { capacity: 300 });
+        assert!(presized::capacity() >= 300);
+    }
+
+    #[test]
+    fn test_contains_all_and_any() {
+        define_registry!(preconditions);
+
+        preconditions::register(1u8);
+        preconditions::register_lazy(|| 2u16);
+
+        assert!(contains_all!(preconditions, u8, u16).unwrap().is_empty());
+        assert_eq!(
+            contains_all!(preconditions, u8, u32, u64,).unwrap(),
+            ["u32", "u64"]
+        );
+        assert!(contains_any!(preconditions, u32, u8).unwrap().is_empty());
+        assert_eq!(
+            contains_any!(preconditions, u32, u64).unwrap(),
+            ["u32", "u64"]
+        );
+    }
 }
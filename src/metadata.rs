@@ -0,0 +1,99 @@
+//! Descriptive metadata attached to registrations.
+//!
+//! `register_with_meta(value, Metadata::new("...").with_owner("..."))` records
+//! what a singleton is for and which team owns it next to the value, so a large
+//! codebase can document its wiring at runtime; `metadata::<T>()` reads it back.
+//! Metadata belongs to a registration: replacing the value without metadata
+//! drops it.
+
+/// What a registered value is and who owns it.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, Metadata};
+///
+/// define_registry!(services);
+///
+/// struct Mailer;
+///
+/// services::register_with_meta(
+///     Mailer,
+///     Metadata::new("Sends transactional email")
+///         .with_owner("team-notifications")
+///         .with_tag("io"),
+/// );
+///
+/// let meta = services::metadata::<Mailer>().unwrap();
+/// assert_eq!(meta.owner, "team-notifications");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// What the value is for.
+    pub description: String,
+    /// The team or person responsible for it.
+    pub owner: String,
+    /// Free-form labels, e.g. `"io"` or `"feature-flag"`.
+    pub tags: Vec<String>,
+}
+
+impl Metadata {
+    /// Metadata with a description and no owner or tags.
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the owner.
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = owner.into();
+        self
+    }
+
+    /// Add a tag.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Whether the metadata carries `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_builder() {
+        let meta = Metadata::new("cache")
+            .with_owner("platform")
+            .with_tag("memory")
+            .with_tag("hot-path");
+        assert_eq!(meta.description, "cache");
+        assert_eq!(meta.owner, "platform");
+        assert!(meta.has_tag("hot-path"));
+        assert!(!meta.has_tag("io"));
+    }
+
+    #[test]
+    fn test_metadata_follows_the_registration() {
+        define_registry!(reg);
+
+        assert_eq!(reg::metadata::<u8>(), None);
+
+        reg::register_with_meta(1u8, Metadata::new("retry limit").with_owner("sre"));
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(reg::metadata::<u8>().unwrap().owner, "sre");
+
+        // A plain replacement carries no metadata
+        reg::register(2u8);
+        assert_eq!(reg::metadata::<u8>(), None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Live mirroring of one registry into others.
+//!
+//! `mirror_into(target)` copies the current entries of a registry into
+//! `target` and from then on repeats every registration and removal there, so
+//! a read-only diagnostic registry or the standby side of a blue/green swap
+//! stays in sync without every registering call knowing about it. Mirrored
+//! entries share the source's `Arc`s and metadata.
+
+use std::any::TypeId;
+use std::sync::{Arc, Mutex};
+
+use crate::registry_trait::{remove_entry, store_entry};
+use crate::{RegistryApi, RegistryEntry, RegistryState};
+
+/// A registry that receives mirrored mutations.
+pub(crate) trait MirrorTarget: Send + Sync {
+    /// The target's state, to walk mirror chains when checking for cycles.
+    fn state(&self) -> &'static RegistryState;
+    /// Store a copy of a registered entry.
+    fn store(&self, type_id: TypeId, entry: RegistryEntry);
+    /// Remove the entry of a type.
+    fn remove(&self, type_id: TypeId, type_name: &'static str);
+}
+
+/// The [`MirrorTarget`] of a registry `R`.
+pub(crate) struct Target<R>(pub(crate) R);
+
+impl<R: RegistryApi + Send + Sync> MirrorTarget for Target<R> {
+    fn state(&self) -> &'static RegistryState {
+        R::state()
+    }
+
+    fn store(&self, type_id: TypeId, entry: RegistryEntry) {
+        let _ = store_entry(&self.0, type_id, entry, false);
+    }
+
+    fn remove(&self, type_id: TypeId, type_name: &'static str) {
+        // Final or already missing in the target; nothing to mirror
+        let _ = remove_entry(&self.0, type_id, type_name, None);
+    }
+}
+
+/// The registries one registry mirrors into, none by default.
+///
+/// Wiring configuration rather than registry data: a std lock, see `crate::sync`.
+#[derive(Default)]
+pub(crate) struct Mirrors {
+    targets: Mutex<Vec<Arc<dyn MirrorTarget>>>,
+}
+
+impl std::fmt::Debug for Mirrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mirrors")
+            .field("targets", &self.targets().len())
+            .finish()
+    }
+}
+
+impl Mirrors {
+    /// Mirror into `target` from now on.
+    ///
+    /// # Panics
+    ///
+    /// If `target` mirrors, directly or through other registries, into `own`,
+    /// since mutations would circle forever.
+    pub(crate) fn add(&self, own: &RegistryState, target: Arc<dyn MirrorTarget>) {
+        let mut pending = vec![target.state()];
+        while let Some(state) = pending.pop() {
+            assert!(
+                !std::ptr::eq(state, own),
+                "mirror_into: mirroring would form a cycle"
+            );
+            pending.extend(state.mirrors.targets().iter().map(|t| t.state()));
+        }
+        self.targets
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(target);
+    }
+
+    /// Stop mirroring into every target.
+    pub(crate) fn clear(&self) {
+        self.targets
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clear();
+    }
+
+    /// Whether there is any target.
+    pub(crate) fn is_active(&self) -> bool {
+        !self
+            .targets
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .is_empty()
+    }
+
+    /// The current targets, cloned so they are called with no lock held.
+    fn targets(&self) -> Vec<Arc<dyn MirrorTarget>> {
+        self.targets
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Repeat a registration in every target.
+    pub(crate) fn store(&self, type_id: TypeId, entry: &RegistryEntry) {
+        for target in self.targets() {
+            target.store(type_id, entry.clone());
+        }
+    }
+
+    /// Repeat a removal in every target.
+    pub(crate) fn remove(&self, type_id: TypeId, type_name: &'static str) {
+        for target in self.targets() {
+            target.remove(type_id, type_name);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, Metadata};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mirror_copies_and_follows() {
+        define_registry!(source);
+        define_registry!(mirror);
+
+        source::register_with_meta(1u8, Metadata::new("counter"));
+        source::register(String::from("kept"));
+        source::mirror_into(mirror::Api);
+
+        assert!(Arc::ptr_eq(
+            &mirror::get::<u8>().unwrap(),
+            &source::get::<u8>().unwrap()
+        ));
+        assert_eq!(mirror::metadata::<u8>().unwrap().description, "counter");
+
+        source::register(2u16);
+        source::remove::<String>().unwrap();
+        assert_eq!(*mirror::get::<u16>().unwrap(), 2);
+        assert!(!mirror::contains::<String>().unwrap());
+
+        // Changes to the mirror do not flow back
+        mirror::register(3u32);
+        assert!(!source::contains::<u32>().unwrap());
+
+        source::stop_mirroring();
+        source::register(4u16);
+        assert_eq!(*mirror::get::<u16>().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_mirror_chains() {
+        define_registry!(a);
+        define_registry!(b);
+        define_registry!(c);
+
+        a::mirror_into(b::Api);
+        b::mirror_into(c::Api);
+        a::register(1u8);
+        assert_eq!(*c::get::<u8>().unwrap(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "mirroring would form a cycle")]
+    fn test_mirror_cycle_panics() {
+        define_registry!(a);
+        define_registry!(b);
+
+        a::mirror_into(b::Api);
+        b::mirror_into(a::Api);
+    }
+}
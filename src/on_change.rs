@@ -0,0 +1,177 @@
+//! Typed change listeners.
+//!
+//! The trace callback only sees type names. `on_change::<T>()` listeners receive
+//! the newly registered `Arc<T>` itself, after every registration or replacement
+//! of `T`, and stay subscribed until their [`ChangeListenerGuard`] is dropped.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use crate::sync::Mutex;
+
+/// Type-erased listener; downcasts the value it is handed to `Arc<T>`.
+type Listener = Arc<dyn Fn(&Arc<dyn Any + Send + Sync>) + Send + Sync>;
+
+#[derive(Default)]
+struct ListenerTable {
+    next_id: u64,
+    by_type: HashMap<TypeId, Vec<(u64, Listener)>>,
+}
+
+/// Change listeners per registered type.
+#[derive(Default)]
+pub(crate) struct ChangeListeners {
+    /// Shared with the guards, so a guard may outlive a runtime `Registry`.
+    table: Arc<Mutex<ListenerTable>>,
+}
+
+impl std::fmt::Debug for ChangeListeners {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("ChangeListeners")
+            .field("types", &table.by_type.len())
+            .finish()
+    }
+}
+
+impl ChangeListeners {
+    /// Add a listener for `T`, returning a guard that removes it on drop.
+    pub(crate) fn add<T, F>(&self, callback: F) -> ChangeListenerGuard
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&Arc<T>) + Send + Sync + 'static,
+    {
+        let listener: Listener = Arc::new(move |value: &Arc<dyn Any + Send + Sync>| {
+            if let Ok(value) = value.clone().downcast::<T>() {
+                callback(&value);
+            }
+        });
+
+        let mut table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+        let id = table.next_id;
+        table.next_id += 1;
+        table
+            .by_type
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .push((id, listener));
+
+        ChangeListenerGuard {
+            table: Arc::downgrade(&self.table),
+            type_id: TypeId::of::<T>(),
+            id,
+        }
+    }
+
+    /// Invoke every listener for `type_id` with the newly registered value.
+    ///
+    /// Listeners are called without holding any lock, so they may use the registry
+    /// (and add or drop listeners) freely.
+    pub(crate) fn notify(&self, type_id: TypeId, value: &Arc<dyn Any + Send + Sync>) {
+        let listeners: Vec<Listener> = {
+            let table = self.table.lock().unwrap_or_else(|p| p.into_inner());
+            match table.by_type.get(&type_id) {
+                Some(list) => list.iter().map(|(_, l)| l.clone()).collect(),
+                None => return,
+            }
+        };
+
+        for listener in listeners {
+            listener(value);
+        }
+    }
+}
+
+impl ListenerTable {
+    fn remove(&mut self, type_id: TypeId, id: u64) {
+        if let Some(list) = self.by_type.get_mut(&type_id) {
+            list.retain(|(listener_id, _)| *listener_id != id);
+            if list.is_empty() {
+                self.by_type.remove(&type_id);
+            }
+        }
+    }
+}
+
+/// Subscription handle returned by `on_change()`.
+///
+/// The listener stays registered as long as the guard is alive; dropping it
+/// unsubscribes.
+#[must_use = "dropping the guard immediately unsubscribes the listener"]
+pub struct ChangeListenerGuard {
+    table: Weak<Mutex<ListenerTable>>,
+    type_id: TypeId,
+    id: u64,
+}
+
+impl std::fmt::Debug for ChangeListenerGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChangeListenerGuard")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl Drop for ChangeListenerGuard {
+    fn drop(&mut self) {
+        if let Some(table) = self.table.upgrade() {
+            table
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .remove(self.type_id, self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_listener_receives_registered_values() {
+        define_registry!(reg);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let guard = {
+            let seen = seen.clone();
+            reg::on_change::<u32>(move |v| seen.lock().unwrap().push(**v))
+        };
+
+        reg::register(1u32);
+        reg::register(2u32);
+        reg::register("other type");
+
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+        drop(guard);
+    }
+
+    #[test]
+    fn test_dropping_guard_unsubscribes() {
+        define_registry!(reg);
+
+        let seen = Arc::new(Mutex::new(0));
+        let guard = {
+            let seen = seen.clone();
+            reg::on_change::<u8>(move |_| *seen.lock().unwrap() += 1)
+        };
+
+        reg::register(1u8);
+        drop(guard);
+        reg::register(2u8);
+
+        assert_eq!(*seen.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_listener_can_use_registry() {
+        define_registry!(reg);
+
+        let _guard = reg::on_change::<String>(|v| reg::register(v.len()));
+
+        reg::register("hello".to_string());
+        assert_eq!(*reg::get::<usize>().unwrap(), 5);
+    }
+}
@@ -0,0 +1,190 @@
+//! Binary snapshots of serializable entries.
+//!
+//! `export_snapshot()` encodes every entry registered with
+//! `register_serializable` with bincode, and `import_snapshot(bytes)` registers
+//! the values again, so a process can persist its configuration and restore it
+//! on restart, or ship it to a replica. Entries are keyed by type name, since
+//! `TypeId`s differ between builds.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bincode::error::{DecodeError, EncodeError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::registry_trait::{lock_storage, store_entry};
+use crate::{RegistryApi, RegistryEntry, RegistryError};
+
+/// Bumped whenever the snapshot layout changes.
+const FORMAT_VERSION: u8 = 1;
+
+/// The encoded snapshot: the format version and each entry's type name and bytes.
+type Snapshot = (u8, Vec<(String, Vec<u8>)>);
+
+type EncodeFn = fn(&(dyn Any + Send + Sync)) -> Result<Vec<u8>, EncodeError>;
+type DecodeFn = fn(&[u8]) -> Result<(TypeId, RegistryEntry), DecodeError>;
+
+/// Snapshot functions for the concrete type of an entry.
+#[derive(Clone, Copy)]
+pub(crate) struct SnapshotShim {
+    encode: EncodeFn,
+    decode: DecodeFn,
+}
+
+impl SnapshotShim {
+    /// The functions for values of type `T`.
+    pub(crate) fn of<T>() -> Self
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self {
+            encode: encode::<T>,
+            decode: decode::<T>,
+        }
+    }
+}
+
+fn encode<T: Serialize + 'static>(value: &(dyn Any + Send + Sync)) -> Result<Vec<u8>, EncodeError> {
+    match value.downcast_ref::<T>() {
+        Some(value) => bincode::serde::encode_to_vec(value, bincode::config::standard()),
+        None => Err(EncodeError::Other("type mismatch")),
+    }
+}
+
+/// Decode a value of `T` into a serializable entry.
+fn decode<T>(bytes: &[u8]) -> Result<(TypeId, RegistryEntry), DecodeError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let (value, _) = bincode::serde::decode_from_slice::<T, _>(bytes, bincode::config::standard())?;
+    let entry = RegistryEntry::new(Arc::new(value)).with_serde::<T>();
+    Ok((TypeId::of::<T>(), entry))
+}
+
+/// Encode the serializable `entries`.
+pub(crate) fn export(entries: &[RegistryEntry]) -> Result<Vec<u8>, RegistryError> {
+    let mut encoded = Vec::new();
+    for entry in entries {
+        let Some(shim) = entry.serde_shim() else {
+            continue;
+        };
+        let bytes = (shim.snapshot().encode)(&**entry.value()).map_err(|err| {
+            RegistryError::from_source(format!("encoding {}", entry.type_name()), err)
+        })?;
+        encoded.push((entry.type_name().to_string(), bytes));
+    }
+    let snapshot: Snapshot = (FORMAT_VERSION, encoded);
+    bincode::serde::encode_to_vec(&snapshot, bincode::config::standard())
+        .map_err(|err| RegistryError::from_source("encoding snapshot", err))
+}
+
+/// Decode `bytes` and register every value in `api`, or none if any fails.
+pub(crate) fn import<R: RegistryApi + ?Sized>(api: &R, bytes: &[u8]) -> Result<(), RegistryError> {
+    let ((version, encoded), _): (Snapshot, usize) =
+        bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+            .map_err(|err| RegistryError::from_source("decoding snapshot", err))?;
+    if version != FORMAT_VERSION {
+        return Err(RegistryError::custom(format!(
+            "unsupported snapshot format version {version}"
+        )));
+    }
+
+    // Types are known by the serializable entries currently registered
+    let shims: HashMap<&'static str, SnapshotShim> = lock_storage::<R>()
+        .unwrap_or_else(|p| p.into_inner())
+        .values()
+        .filter_map(|entry| Some((entry.type_name(), *entry.serde_shim()?.snapshot())))
+        .collect();
+
+    let mut unknown = Vec::new();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    for (type_name, bytes) in &encoded {
+        let Some(shim) = shims.get(type_name.as_str()) else {
+            unknown.push(type_name.as_str());
+            continue;
+        };
+        let decoded_entry = (shim.decode)(bytes)
+            .map_err(|err| RegistryError::from_source(format!("decoding {type_name}"), err))?;
+        decoded.push(decoded_entry);
+    }
+    if !unknown.is_empty() {
+        return Err(RegistryError::custom(format!(
+            "snapshot contains types not registered as serializable: {}",
+            unknown.join(", ")
+        )));
+    }
+
+    for (type_id, entry) in decoded {
+        let _ = store_entry(api, type_id, entry, false);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Limits {
+        max_connections: u32,
+        hosts: Vec<String>,
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        define_registry!(source);
+        define_registry!(replica);
+
+        source::register_serializable(Limits {
+            max_connections: 64,
+            hosts: vec!["a".to_string()],
+        });
+        source::register_serializable(7u16);
+        source::register(1u8); // not serializable, not exported
+        let snapshot = source::export_snapshot().unwrap();
+
+        // The replica knows the types from its defaults
+        replica::register_serializable(Limits {
+            max_connections: 1,
+            hosts: Vec::new(),
+        });
+        replica::register_serializable(0u16);
+        replica::import_snapshot(&snapshot).unwrap();
+
+        assert_eq!(
+            *replica::get::<Limits>().unwrap(),
+            Limits {
+                max_connections: 64,
+                hosts: vec!["a".to_string()],
+            }
+        );
+        assert_eq!(*replica::get::<u16>().unwrap(), 7);
+        assert!(!replica::contains::<u8>().unwrap());
+        // Imported values stay serializable
+        assert_eq!(replica::export_snapshot().unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_import_is_all_or_nothing() {
+        define_registry!(source);
+        define_registry!(target);
+
+        source::register_serializable(7u16);
+        source::register_serializable(String::from("new"));
+        let snapshot = source::export_snapshot().unwrap();
+
+        target::register_serializable(String::from("old"));
+        let err = target::import_snapshot(&snapshot).unwrap_err();
+        assert_eq!(
+            err,
+            RegistryError::custom("snapshot contains types not registered as serializable: u16")
+        );
+        assert_eq!(*target::get::<String>().unwrap(), "old");
+
+        assert!(target::import_snapshot(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Bounded resource pools.
+//!
+//! A [`Pool<T>`] holds a fixed set of interchangeable resources (connections,
+//! buffers) and is registered like any singleton, so pooled resources live behind
+//! the same type-keyed lookup: `checkout::<T>()` finds the registered `Pool<T>`
+//! and lends out one item through a [`PoolGuard`] that returns it on drop.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::sync::{Condvar, Mutex};
+
+struct PoolInner<T> {
+    idle: Mutex<Vec<T>>,
+    returned: Condvar,
+    size: usize,
+}
+
+impl<T> PoolInner<T> {
+    fn put_back(&self, item: T) {
+        self.idle
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .push(item);
+        self.returned.notify_one();
+    }
+}
+
+/// A fixed-size pool of interchangeable resources.
+///
+/// Register it like any other value; `checkout` lends out an item, blocking
+/// while all of them are in use.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, Pool};
+///
+/// define_registry!(services);
+///
+/// struct Conn(u32);
+///
+/// services::register(Pool::new((0..4).map(Conn)));
+///
+/// let conn = services::checkout::<Conn>().unwrap();
+/// assert!(conn.0 < 4);
+/// drop(conn); // back in the pool
+/// ```
+pub struct Pool<T> {
+    inner: Arc<PoolInner<T>>,
+}
+
+impl<T> Pool<T> {
+    /// Create a pool lending out `items`.
+    pub fn new(items: impl IntoIterator<Item = T>) -> Self {
+        let idle: Vec<T> = items.into_iter().collect();
+        Self {
+            inner: Arc::new(PoolInner {
+                size: idle.len(),
+                idle: Mutex::new(idle),
+                returned: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Total number of items, checked out or not.
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Number of items currently available for checkout.
+    pub fn available(&self) -> usize {
+        self.inner
+            .idle
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .len()
+    }
+
+    /// Check out an item, blocking until one is returned if all are in use.
+    ///
+    /// Blocks forever on an empty pool.
+    pub fn checkout(&self) -> PoolGuard<T> {
+        let mut idle = self.inner.idle.lock().unwrap_or_else(|p| p.into_inner());
+        loop {
+            if let Some(item) = idle.pop() {
+                return self.guard(item);
+            }
+            idle = self
+                .inner
+                .returned
+                .wait(idle)
+                .unwrap_or_else(|p| p.into_inner());
+        }
+    }
+
+    /// Check out an item if one is available right now.
+    pub fn try_checkout(&self) -> Option<PoolGuard<T>> {
+        let item = self
+            .inner
+            .idle
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .pop()?;
+        Some(self.guard(item))
+    }
+
+    /// Check out an item, waiting at most `timeout` for one to be returned.
+    pub fn checkout_timeout(&self, timeout: Duration) -> Option<PoolGuard<T>> {
+        // A timeout too large for an `Instant` means waiting with no deadline
+        let Some(deadline) = Instant::now().checked_add(timeout) else {
+            return Some(self.checkout());
+        };
+        let mut idle = self.inner.idle.lock().unwrap_or_else(|p| p.into_inner());
+        loop {
+            if let Some(item) = idle.pop() {
+                return Some(self.guard(item));
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            idle = self
+                .inner
+                .returned
+                .wait_timeout(idle, remaining)
+                .unwrap_or_else(|p| p.into_inner())
+                .0;
+        }
+    }
+
+    fn guard(&self, item: T) -> PoolGuard<T> {
+        PoolGuard {
+            item: Some(item),
+            pool: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("size", &self.size())
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+/// An item checked out of a [`Pool`], returned to it on drop.
+///
+/// The guard keeps the pool alive, so it may outlive the `Arc<Pool<T>>` it came
+/// from, e.g. after the pool was replaced in the registry.
+#[must_use = "dropping the guard returns the item to the pool immediately"]
+pub struct PoolGuard<T> {
+    item: Option<T>,
+    pool: Arc<PoolInner<T>>,
+}
+
+impl<T> Deref for PoolGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().expect("item is present until drop")
+    }
+}
+
+impl<T> DerefMut for PoolGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_mut().expect("item is present until drop")
+    }
+}
+
+impl<T> Drop for PoolGuard<T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.pool.put_back(item);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PoolGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PoolGuard").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, RegistryError};
+    use std::thread;
+
+    #[test]
+    fn test_checkout_and_return() {
+        let pool = Pool::new(vec![1, 2]);
+        assert_eq!(pool.size(), 2);
+
+        let a = pool.checkout();
+        let b = pool.checkout();
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_checkout().is_none());
+
+        drop(a);
+        assert_eq!(pool.available(), 1);
+        drop(b);
+        assert_eq!(pool.available(), 2);
+    }
+
+    #[test]
+    fn test_mutations_persist() {
+        let pool = Pool::new(vec![String::new()]);
+        pool.checkout().push_str("used");
+        assert_eq!(*pool.checkout(), "used");
+    }
+
+    #[test]
+    fn test_checkout_blocks_until_returned() {
+        let pool = Arc::new(Pool::new(vec![0u8]));
+        let held = pool.checkout();
+
+        let waiter = {
+            let pool = pool.clone();
+            thread::spawn(move || *pool.checkout())
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(held);
+        assert_eq!(waiter.join().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_checkout_timeout() {
+        let pool = Pool::new(vec![0u8]);
+        let _held = pool.checkout();
+        assert!(pool.checkout_timeout(Duration::from_millis(20)).is_none());
+    }
+
+    #[test]
+    fn test_checkout_timeout_without_deadline() {
+        let pool = Arc::new(Pool::new(vec![0u8]));
+        assert!(pool.checkout_timeout(Duration::MAX).is_some());
+
+        let held = pool.checkout();
+        let waiter = {
+            let pool = pool.clone();
+            thread::spawn(move || pool.checkout_timeout(Duration::MAX).is_some())
+        };
+        thread::sleep(Duration::from_millis(20));
+        drop(held);
+        assert!(waiter.join().unwrap());
+    }
+
+    #[test]
+    fn test_registry_checkout() {
+        define_registry!(reg);
+
+        assert!(matches!(
+            reg::checkout::<u8>().unwrap_err(),
+            RegistryError::TypeNotFound { type_name, .. }
+                if type_name == std::any::type_name::<Pool<u8>>()
+        ));
+
+        reg::register(Pool::new(vec![7u8]));
+        let item = reg::checkout::<u8>().unwrap();
+        assert_eq!(*item, 7);
+
+        // The guard outlives a replacement of the pool
+        reg::register(Pool::new(Vec::<u8>::new()));
+        drop(item);
+    }
+}
@@ -0,0 +1,127 @@
+//! Single-service dependencies that can be mocked.
+//!
+//! Application code written against `&dyn Provides<Db>` instead of a registry
+//! module gets its database from whatever it is handed: a macro registry, a
+//! runtime [`Registry`](crate::Registry), a handle, or, in tests, a plain
+//! `Arc<Db>` or a hand-written mock, with no statics involved.
+
+use std::sync::Arc;
+
+use crate::{ReadOnlyRegistry, Registry, RegistryApi, RegistryError, RegistryHandle};
+
+/// Something that can provide a `T`.
+///
+/// Implemented by every registry, by [`RegistryHandle`] and
+/// [`ReadOnlyRegistry`], and by `Arc<T>` itself as the simplest stand-in. The
+/// trait is object safe.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, Provides};
+/// use std::sync::Arc;
+///
+/// struct Db {
+///     url: &'static str,
+/// }
+///
+/// fn connect(db: &dyn Provides<Db>) -> String {
+///     format!("connecting to {}", db.provide().unwrap().url)
+/// }
+///
+/// define_registry!(app);
+/// app::register(Db { url: "postgres://prod" });
+/// assert_eq!(connect(&app::Api), "connecting to postgres://prod");
+///
+/// // In a test, no registry at all
+/// let mock = Arc::new(Db { url: "sqlite::memory:" });
+/// assert_eq!(connect(&mock), "connecting to sqlite::memory:");
+/// ```
+pub trait Provides<T> {
+    /// The `T` to use.
+    ///
+    /// # Errors
+    ///
+    /// - No `T` is available, e.g. `RegistryError::TypeNotFound`
+    fn provide(&self) -> Result<Arc<T>, RegistryError>;
+}
+
+impl<R: RegistryApi, T: Send + Sync + 'static> Provides<T> for R {
+    #[track_caller]
+    fn provide(&self) -> Result<Arc<T>, RegistryError> {
+        self.get::<T>()
+    }
+}
+
+impl<T: Send + Sync + 'static> Provides<T> for Registry {
+    #[track_caller]
+    fn provide(&self) -> Result<Arc<T>, RegistryError> {
+        self.get::<T>()
+    }
+}
+
+impl<T: Send + Sync + 'static> Provides<T> for RegistryHandle {
+    #[track_caller]
+    fn provide(&self) -> Result<Arc<T>, RegistryError> {
+        self.get::<T>()
+    }
+}
+
+impl<T: Send + Sync + 'static> Provides<T> for ReadOnlyRegistry {
+    #[track_caller]
+    fn provide(&self) -> Result<Arc<T>, RegistryError> {
+        self.get::<T>()
+    }
+}
+
+impl<T> Provides<T> for Arc<T> {
+    fn provide(&self) -> Result<Arc<T>, RegistryError> {
+        Ok(self.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[derive(Debug, PartialEq)]
+    struct Port(u16);
+
+    fn port(provider: &dyn Provides<Port>) -> Result<u16, RegistryError> {
+        provider.provide().map(|port| port.0)
+    }
+
+    #[test]
+    fn test_registries_provide() {
+        define_registry!(reg);
+
+        assert!(matches!(
+            port(&reg::Api),
+            Err(RegistryError::TypeNotFound { .. })
+        ));
+        reg::register(Port(80));
+        assert_eq!(port(&reg::Api), Ok(80));
+        assert_eq!(port(&reg::handle()), Ok(80));
+        assert_eq!(port(&reg::read_only()), Ok(80));
+
+        let registry = Registry::new();
+        registry.register(Port(81));
+        assert_eq!(port(&registry), Ok(81));
+    }
+
+    #[test]
+    fn test_mock_provider() {
+        struct Failing;
+
+        impl Provides<Port> for Failing {
+            fn provide(&self) -> Result<Arc<Port>, RegistryError> {
+                Err(RegistryError::custom("unavailable"))
+            }
+        }
+
+        assert_eq!(port(&Arc::new(Port(8080))), Ok(8080));
+        assert_eq!(port(&Failing), Err(RegistryError::custom("unavailable")));
+    }
+}
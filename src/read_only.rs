@@ -0,0 +1,122 @@
+//! Lookup-only views of a registry.
+//!
+//! Registries are meant to be wired once at startup. Handing a subsystem a
+//! [`ReadOnlyRegistry`] instead of a [`RegistryHandle`] enforces that
+//! structurally: the view can look values up but has no way to register,
+//! remove or clear them.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{RegistryError, RegistryHandle};
+
+/// A cloneable view of a registry that can only look values up.
+///
+/// Obtained with `name::read_only()` for a macro registry or
+/// [`RegistryHandle::read_only`] for any handle.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, ReadOnlyRegistry};
+///
+/// define_registry!(app);
+///
+/// // The subsystem can read the wiring, not change it
+/// fn worker_count(registry: &ReadOnlyRegistry) -> usize {
+///     registry.get_cloned::<usize>().unwrap_or(1)
+/// }
+///
+/// app::register(8usize);
+/// assert_eq!(worker_count(&app::read_only()), 8);
+/// ```
+#[derive(Clone)]
+pub struct ReadOnlyRegistry(RegistryHandle);
+
+impl ReadOnlyRegistry {
+    /// Name of the registry, as reported in its events.
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Retrieve a value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    #[track_caller]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        self.0.get::<T>()
+    }
+
+    /// Retrieve a cloned value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch
+    #[track_caller]
+    pub fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.0.get_cloned::<T>()
+    }
+
+    /// Retrieve a value from the registry, returning `None` if not registered.
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.0.try_get::<T>()
+    }
+
+    /// Check if a type is registered.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned
+    pub fn contains<T: Send + Sync + 'static>(&self) -> Result<bool, RegistryError> {
+        self.0.contains::<T>()
+    }
+}
+
+impl From<RegistryHandle> for ReadOnlyRegistry {
+    fn from(handle: RegistryHandle) -> Self {
+        Self(handle)
+    }
+}
+
+impl fmt::Debug for ReadOnlyRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReadOnlyRegistry")
+            .field(&self.name())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, Registry};
+
+    #[test]
+    fn test_sees_later_registrations() {
+        define_registry!(reg);
+
+        let view = reg::read_only();
+        assert_eq!(view.contains::<u8>(), Ok(false));
+        reg::register(1u8);
+        assert_eq!(*view.get::<u8>().unwrap(), 1);
+        assert_eq!(view.get_cloned::<u8>().unwrap(), 1);
+        assert_eq!(format!("{view:?}"), "ReadOnlyRegistry(\"reg\")");
+    }
+
+    #[test]
+    fn test_from_a_runtime_registry_handle() {
+        let handle = RegistryHandle::from(Registry::named("tenant"));
+        handle.register(String::from("eu"));
+
+        let view = handle.read_only();
+        drop(handle);
+        assert_eq!(*view.try_get::<String>().unwrap(), "eu");
+        assert_eq!(view.name(), "tenant");
+    }
+}
@@ -0,0 +1,169 @@
+//! Per-registry configuration fixed at definition time.
+//!
+//! `define_registry!(app, { poison_policy: Recover, trace: disabled, registration: AppendOnly })`
+//! generates a `const` [`RegistryConfig`] for the registry, so behavior tweaks
+//! don't require abandoning the macro for a manual `RegistryApi` implementation.
+//! Manual implementations override `RegistryApi::config()` instead.
+
+/// What lookups do when the storage lock is poisoned.
+///
+/// A lock is poisoned when a thread panicked while holding it. Mutations and
+/// inspection methods always recover the data; this policy decides whether
+/// `get`, `get_cloned` and `contains` do too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoisonPolicy {
+    /// Fail the lookup with `RegistryError::RegistryLock`.
+    #[default]
+    Error,
+    /// Ignore the poison and look up the value anyway.
+    Recover,
+}
+
+/// What registering a type that already has a value does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegistrationMode {
+    /// Replace the registered value.
+    #[default]
+    Override,
+    /// Reject the registration with `RegistryError::AlreadyRegistered`; new
+    /// types can still be added.
+    AppendOnly,
+}
+
+/// Static configuration of a registry.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, PoisonPolicy};
+///
+/// define_registry!(app, { poison_policy: Recover, trace: disabled, capacity: 64 });
+///
+/// assert_eq!(app::config().poison_policy, PoisonPolicy::Recover);
+/// assert!(!app::config().trace);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RegistryConfig {
+    /// How lookups treat a poisoned storage lock.
+    pub poison_policy: PoisonPolicy,
+    /// Whether the registry emits events at all; `false` skips tracing,
+    /// subscribers and sinks entirely (the audit log still records mutations).
+    pub trace: bool,
+    /// Initial capacity of the storage map.
+    pub capacity: usize,
+    /// Whether registering an already registered type replaces its value.
+    pub registration: RegistrationMode,
+}
+
+impl RegistryConfig {
+    /// The configuration of a registry defined without options.
+    pub const fn new() -> Self {
+        Self {
+            poison_policy: PoisonPolicy::Error,
+            trace: true,
+            capacity: 0,
+            registration: RegistrationMode::Override,
+        }
+    }
+
+    /// Set how lookups treat a poisoned storage lock.
+    pub const fn with_poison_policy(mut self, poison_policy: PoisonPolicy) -> Self {
+        self.poison_policy = poison_policy;
+        self
+    }
+
+    /// Enable or disable event emission.
+    pub const fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Set the initial capacity of the storage map.
+    pub const fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Set whether registering an already registered type replaces its value.
+    pub const fn with_registration(mut self, registration: RegistrationMode) -> Self {
+        self.registration = registration;
+        self
+    }
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, RegistryApi, RegistryError};
+    use std::sync::{Arc, Mutex};
+
+    fn poison<R: RegistryApi>() {
+        let _ = std::thread::spawn(|| {
+            let _guard = R::storage().lock();
+            panic!("poisoning the storage lock");
+        })
+        .join();
+    }
+
+    #[test]
+    fn test_default_config() {
+        define_registry!(reg);
+
+        assert_eq!(*reg::config(), RegistryConfig::new());
+
+        reg::register(1u8);
+        poison::<reg::Api>();
+        assert_eq!(reg::get::<u8>().unwrap_err(), RegistryError::RegistryLock);
+        assert_eq!(reg::contains::<u8>(), Err(RegistryError::RegistryLock));
+    }
+
+    #[test]
+    fn test_options() {
+        define_registry!(reg, { poison_policy: Recover, trace: disabled, capacity: 64 });
+
+        let events = Arc::new(Mutex::new(0));
+        let counter = events.clone();
+        reg::set_trace_callback(move |_| *counter.lock().unwrap() += 1);
+
+        assert!(reg::capacity() >= 64);
+        reg::register(1u8);
+        poison::<reg::Api>();
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(reg::contains::<u8>(), Ok(true));
+        assert_eq!(*events.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_append_only() {
+        define_registry!(reg, { registration: AppendOnly });
+
+        reg::register(1u8);
+        reg::register(2u8);
+        reg::register(1u16);
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(*reg::get::<u16>().unwrap(), 1);
+        assert_eq!(
+            reg::try_register(3u8),
+            Err(RegistryError::AlreadyRegistered { type_name: "u8" })
+        );
+
+        // A removed type is new again
+        reg::remove::<u8>().unwrap();
+        reg::try_register(4u8).unwrap();
+        assert_eq!(*reg::get::<u8>().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_partial_options() {
+        define_registry!(reg, { trace: enabled });
+        assert_eq!(*reg::config(), RegistryConfig::new());
+    }
+}
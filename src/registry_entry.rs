@@ -0,0 +1,332 @@
+//! Entries held in registry storage.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{Metadata, RegistryError};
+
+/// Type name of entries registered with `register_boxed`.
+pub(crate) const BOXED_TYPE_NAME: &str = "<boxed>";
+
+/// A value stored in a registry together with its bookkeeping data.
+///
+/// The storage map of every registry is keyed by `TypeId` and holds one
+/// `RegistryEntry` per registered type. Entries are created by the registry
+/// itself; manual `RegistryApi` implementations only need to name the type
+/// when declaring their storage static.
+#[derive(Clone)]
+pub struct RegistryEntry {
+    value: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+    is_final: bool,
+    is_protected: bool,
+    registered_at: Option<&'static Location<'static>>,
+    metadata: Option<Arc<Metadata>>,
+    registered: SystemTime,
+    generation: u64,
+    debug: Option<DebugFn>,
+    value_eq: Option<crate::contents_eq::EqFn>,
+    #[cfg(feature = "serde")]
+    serde: Option<crate::serializable::SerdeShim>,
+    stored: Instant,
+    last_access: Option<Instant>,
+}
+
+/// Formats a type-erased value as its concrete type's `Debug` output.
+type DebugFn = fn(&(dyn Any + Send + Sync), &mut fmt::Formatter<'_>) -> fmt::Result;
+
+fn debug_as<T: fmt::Debug + 'static>(
+    value: &(dyn Any + Send + Sync),
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    match value.downcast_ref::<T>() {
+        Some(value) => value.fmt(f),
+        None => f.write_str("<type mismatch>"),
+    }
+}
+
+/// The `Debug` output of an entry registered with `register_debuggable`.
+pub struct DebugValue<'a> {
+    value: &'a (dyn Any + Send + Sync),
+    debug: DebugFn,
+}
+
+impl fmt::Debug for DebugValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.debug)(self.value, f)
+    }
+}
+
+impl RegistryEntry {
+    /// Create an entry for an `Arc`-wrapped value.
+    pub fn new<T: Send + Sync + 'static>(value: Arc<T>) -> Self {
+        Self::erased(value, std::any::type_name::<T>())
+    }
+
+    /// Create an entry for a value built at runtime, which must be of the type
+    /// identified by `type_id`.
+    ///
+    /// The concrete type's name is unknown, so the entry is named
+    /// [`BOXED_TYPE_NAME`].
+    pub(crate) fn boxed(
+        type_id: TypeId,
+        value: Box<dyn Any + Send + Sync>,
+    ) -> Result<Self, RegistryError> {
+        if (*value).type_id() != type_id {
+            return Err(RegistryError::TypeMismatch {
+                type_name: BOXED_TYPE_NAME,
+            });
+        }
+        Ok(Self::erased(Arc::from(value), BOXED_TYPE_NAME))
+    }
+
+    fn erased(value: Arc<dyn Any + Send + Sync>, type_name: &'static str) -> Self {
+        Self {
+            value,
+            type_name,
+            is_final: false,
+            is_protected: false,
+            registered_at: None,
+            metadata: None,
+            registered: SystemTime::now(),
+            generation: 1,
+            debug: None,
+            value_eq: None,
+            #[cfg(feature = "serde")]
+            serde: None,
+            stored: Instant::now(),
+            last_access: None,
+        }
+    }
+
+    /// Record the source location of the registering call.
+    pub(crate) fn at(mut self, location: &'static Location<'static>) -> Self {
+        self.registered_at = Some(location);
+        self
+    }
+
+    /// List the entry under the name of the key type `K` instead of its value type.
+    pub(crate) fn keyed<K: 'static>(mut self) -> Self {
+        self.type_name = std::any::type_name::<K>();
+        self
+    }
+
+    /// Stamp a copy of an earlier entry as registered now.
+    pub(crate) fn restamped(mut self) -> Self {
+        self.registered = SystemTime::now();
+        self.stored = Instant::now();
+        self.last_access = None;
+        self
+    }
+
+    /// Count the entry as the successor of `previous`, the entry it replaces.
+    pub(crate) fn succeeding(mut self, previous: Option<&RegistryEntry>) -> Self {
+        self.generation = previous.map_or(1, |p| p.generation + 1);
+        self
+    }
+
+    /// Record that the value was just retrieved.
+    pub(crate) fn touch(&mut self) {
+        self.last_access = Some(Instant::now());
+    }
+
+    /// When the value was last retrieved with access tracking on, if ever.
+    pub(crate) fn last_access(&self) -> Option<Instant> {
+        self.last_access
+    }
+
+    /// Time since the value was last retrieved, or stored if it never was.
+    pub(crate) fn idle(&self) -> Duration {
+        self.last_access.unwrap_or(self.stored).elapsed()
+    }
+
+    /// Keep a formatter for `T`, the type of the stored value.
+    pub(crate) fn with_debug<T: fmt::Debug + 'static>(mut self) -> Self {
+        self.debug = Some(debug_as::<T>);
+        self
+    }
+
+    /// Keep a `PartialEq` comparator for `T`, the type of the stored value.
+    pub(crate) fn with_value_eq<T: PartialEq + 'static>(mut self) -> Self {
+        self.value_eq = Some(crate::contents_eq::eq_as::<T>);
+        self
+    }
+
+    /// The comparator of an entry registered with `register_comparable`.
+    pub(crate) fn value_eq(&self) -> Option<crate::contents_eq::EqFn> {
+        self.value_eq
+    }
+
+    /// Keep serde functions for `T`, the type of the stored value.
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_serde<T>(mut self) -> Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        self.serde = Some(crate::serializable::SerdeShim::of::<T>());
+        self
+    }
+
+    /// The serde functions of an entry registered with `register_serializable`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn serde_shim(&self) -> Option<&crate::serializable::SerdeShim> {
+        self.serde.as_ref()
+    }
+
+    /// Attach descriptive metadata to the entry.
+    pub(crate) fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(Arc::new(metadata));
+        self
+    }
+
+    /// Mark the entry as final: the registry rejects replacing it.
+    pub(crate) fn into_final(mut self) -> Self {
+        self.is_final = true;
+        self
+    }
+
+    /// Mark the entry as protected: the registry rejects replacing or
+    /// removing it, and `clear()` keeps it.
+    pub(crate) fn protect(&mut self) {
+        self.is_protected = true;
+    }
+
+    /// The stored value, type-erased.
+    pub fn value(&self) -> &Arc<dyn Any + Send + Sync> {
+        &self.value
+    }
+
+    /// The stored value, consuming the entry.
+    pub(crate) fn into_value(self) -> Arc<dyn Any + Send + Sync> {
+        self.value
+    }
+
+    /// The type name of the stored value (e.g., "i32", "alloc::string::String").
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Whether the value was registered with `register_final` and cannot be replaced.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+
+    /// Whether the value was marked with `protect` and survives `clear()`.
+    pub fn is_protected(&self) -> bool {
+        self.is_protected
+    }
+
+    /// When the value was registered.
+    pub fn registration_time(&self) -> SystemTime {
+        self.registered
+    }
+
+    /// How many times the type has been registered since it was last absent,
+    /// counting this entry.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The value's `Debug` output, if it was registered with `register_debuggable`.
+    pub fn debug_value(&self) -> Option<DebugValue<'_>> {
+        self.debug.map(|debug| DebugValue {
+            value: &*self.value,
+            debug,
+        })
+    }
+
+    /// The metadata the value was registered with, if any.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_deref()
+    }
+
+    /// The shared metadata handle, for handing out without cloning.
+    pub(crate) fn metadata_arc(&self) -> Option<&Arc<Metadata>> {
+        self.metadata.as_ref()
+    }
+
+    /// Source location of the call that registered the value, if recorded.
+    pub fn registered_at(&self) -> Option<&'static Location<'static>> {
+        self.registered_at
+    }
+}
+
+impl fmt::Debug for RegistryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("RegistryEntry");
+        s.field("type_name", &self.type_name);
+        if let Some(value) = self.debug_value() {
+            s.field("value", &value);
+        }
+        s.field("strong_count", &Arc::strong_count(&self.value))
+            .field("is_final", &self.is_final)
+            .field("is_protected", &self.is_protected)
+            .field("generation", &self.generation)
+            .field("registered_at", &self.registered_at)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+/// Insert `value` into a storage map under construction.
+///
+/// Used by `define_registry!` for its initial values; not part of the API.
+#[doc(hidden)]
+pub fn __seed<T: Send + Sync + 'static>(map: &mut HashMap<TypeId, RegistryEntry>, value: T) {
+    map.insert(TypeId::of::<T>(), RegistryEntry::new(Arc::new(value)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_records_type_name() {
+        let entry = RegistryEntry::new(Arc::new(5u8));
+        assert_eq!(entry.type_name(), "u8");
+        assert_eq!(*entry.value().clone().downcast::<u8>().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_debug_format() {
+        let entry = RegistryEntry::new(Arc::new("x".to_string()));
+        let debug = format!("{:?}", entry);
+        assert!(debug.contains("alloc::string::String"));
+        assert!(debug.contains("strong_count: 1"));
+    }
+
+    #[test]
+    fn test_debug_value() {
+        let entry = RegistryEntry::new(Arc::new(vec![1, 2]));
+        assert!(entry.debug_value().is_none());
+
+        let entry = entry.with_debug::<Vec<i32>>();
+        assert_eq!(format!("{:?}", entry.debug_value().unwrap()), "[1, 2]");
+        assert!(format!("{:?}", entry).contains("value: [1, 2]"));
+    }
+
+    #[test]
+    fn test_into_final() {
+        let entry = RegistryEntry::new(Arc::new(1u8));
+        assert!(!entry.is_final());
+        assert!(entry.into_final().is_final());
+    }
+
+    #[test]
+    fn test_boxed() {
+        let entry = RegistryEntry::boxed(TypeId::of::<u8>(), Box::new(1u8)).unwrap();
+        assert_eq!(entry.type_name(), BOXED_TYPE_NAME);
+        assert_eq!(entry.value().downcast_ref::<u8>(), Some(&1));
+
+        assert_eq!(
+            RegistryEntry::boxed(TypeId::of::<u16>(), Box::new(1u8)).unwrap_err(),
+            RegistryError::TypeMismatch {
+                type_name: BOXED_TYPE_NAME
+            }
+        );
+    }
+}
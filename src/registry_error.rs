@@ -1,10 +1,14 @@
 use std::fmt;
+use std::panic::Location;
+use std::time::Duration;
 
 /// Error type for registry operations.
 ///
 /// All fallible registry operations return this error type to indicate
-/// what went wrong during the operation.
-#[derive(Debug, PartialEq)]
+/// what went wrong during the operation. New variants may be added in minor
+/// releases, so matches need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
 pub enum RegistryError {
     /// Failed to acquire the registry lock (lock poisoning).
     ///
@@ -22,11 +26,258 @@ pub enum RegistryError {
 
     /// The requested type was not found in the registry.
     ///
-    /// Includes the type name that was requested.
+    /// Includes the type name that was requested and, for `get` and `get_cloned`,
+    /// the source location of the call, so an `.unwrap()` deep in a library
+    /// points straight at the offending lookup. `get` also lists registered
+    /// types with a similar name, catching re-exported or duplicated structs.
     TypeNotFound {
         /// The type name that was requested
         type_name: &'static str,
+        /// Source location of the failed lookup, if known
+        location: Option<&'static Location<'static>>,
+        /// Registered type names resembling the requested one, best match first
+        suggestions: Vec<&'static str>,
+    },
+
+    /// The requested type was not registered before the wait timed out.
+    ///
+    /// Returned by `wait_for_blocking`.
+    Timeout {
+        /// The type name that was awaited
+        type_name: &'static str,
+        /// How long the caller waited
+        timeout: Duration,
+    },
+
+    /// The type was registered as final and cannot be replaced.
+    ///
+    /// Returned by `try_register` and `register_final`.
+    FinalType {
+        /// The type name whose registration was rejected
+        type_name: &'static str,
+    },
+
+    /// Resolving the type requires itself through its providers' dependencies.
+    ///
+    /// Returned by `resolve` and `resolve_async`.
+    DependencyCycle {
+        /// The types forming the cycle, starting and ending with the same type
+        cycle: Vec<&'static str>,
+    },
+
+    /// The type is only provided asynchronously and cannot be resolved synchronously.
+    ///
+    /// Returned by `resolve`; use `resolve_async` instead.
+    ProvidedAsync {
+        /// The type name that was requested
+        type_name: &'static str,
     },
+
+    /// Dependencies of a type could not be found.
+    ///
+    /// Returned by `resolve_all`, which tries every requested dependency (and
+    /// their providers' `resolve_all` dependencies) before failing, so one
+    /// error lists everything that is missing.
+    MissingDependencies {
+        /// Every missing dependency, in the order they were requested
+        missing: Vec<MissingDependency>,
+    },
+
+    /// There is no replaced value of the type to go back to.
+    ///
+    /// Returned by `revert` when the history of the type is off or exhausted.
+    NoPreviousValue {
+        /// The type name that was reverted
+        type_name: &'static str,
+    },
+
+    /// Types registered in both registries of a `merge_from` with
+    /// `ConflictPolicy::Error`; nothing was imported.
+    MergeConflict {
+        /// The conflicting type names, sorted
+        conflicts: Vec<&'static str>,
+    },
+
+    /// The type was marked as protected and cannot be replaced or removed.
+    ///
+    /// Returned by `try_register`, `remove` and `move_to` after `protect`.
+    ProtectedType {
+        /// The type name whose registration or removal was rejected
+        type_name: &'static str,
+    },
+
+    /// The type is already registered in an append-only registry.
+    ///
+    /// Returned by `try_register` in a registry defined with
+    /// `registration: AppendOnly`.
+    AlreadyRegistered {
+        /// The type name whose registration was rejected
+        type_name: &'static str,
+    },
+
+    /// The value is still referenced outside the registry and can't be taken
+    /// by value.
+    ///
+    /// Returned by `take_owned`; the value stays registered.
+    StillShared {
+        /// The type name that was taken
+        type_name: &'static str,
+        /// Number of `Arc` handles to the value, including the registry's own
+        strong_count: usize,
+    },
+
+    /// A failure outside the registry itself, such as a provider's own error.
+    ///
+    /// Built with [`custom`](RegistryError::custom),
+    /// [`from_source`](RegistryError::from_source) or
+    /// [`with_context`](RegistryError::with_context); `source` is exposed
+    /// through [`std::error::Error::source`].
+    Custom {
+        /// Description of the failure
+        message: String,
+        /// The underlying error, if any
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+}
+
+impl RegistryError {
+    /// A `Custom` error with only a message.
+    pub fn custom(message: impl Into<String>) -> Self {
+        RegistryError::Custom {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// A `Custom` error describing `source`, e.g. the I/O error a provider hit.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, RegistryError};
+    ///
+    /// define_registry!(services);
+    ///
+    /// #[derive(Debug)]
+    /// struct Config(String);
+    ///
+    /// services::register_provider(|_| {
+    ///     std::fs::read_to_string("/nonexistent/app.toml")
+    ///         .map(Config)
+    ///         .map_err(|err| RegistryError::from_source("reading app.toml", err))
+    /// });
+    ///
+    /// let err = services::resolve::<Config>().unwrap_err();
+    /// assert!(std::error::Error::source(&err).is_some());
+    /// ```
+    pub fn from_source(
+        message: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        RegistryError::Custom {
+            message: message.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Wrap this error in a `Custom` error saying what was being done.
+    ///
+    /// ```rust
+    /// use singleton_registry::RegistryError;
+    ///
+    /// let err = RegistryError::RegistryLock.with_context("loading plugins");
+    /// assert_eq!(err.to_string(), "loading plugins: Failed to acquire registry lock");
+    /// ```
+    pub fn with_context(self, message: impl Into<String>) -> Self {
+        RegistryError::from_source(message, self)
+    }
+}
+
+/// A dependency that `resolve_all` could neither find nor build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    /// The type name of the dependency
+    pub type_name: &'static str,
+    /// The type whose provider requested it, or `None` for a top-level
+    /// `resolve_all`
+    pub required_by: Option<&'static str>,
+}
+
+impl fmt::Display for MissingDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.required_by {
+            Some(required_by) => write!(f, "{} (required by {})", self.type_name, required_by),
+            None => write!(f, "{}", self.type_name),
+        }
+    }
+}
+
+/// `Custom` errors compare by message and by the text of their sources; the
+/// sources themselves are opaque.
+impl PartialEq for RegistryError {
+    fn eq(&self, other: &Self) -> bool {
+        use RegistryError::*;
+
+        match (self, other) {
+            (RegistryLock, RegistryLock) => true,
+            (TypeMismatch { type_name: a }, TypeMismatch { type_name: b }) => a == b,
+            (
+                TypeNotFound {
+                    type_name: a,
+                    location: a_location,
+                    suggestions: a_suggestions,
+                },
+                TypeNotFound {
+                    type_name: b,
+                    location: b_location,
+                    suggestions: b_suggestions,
+                },
+            ) => a == b && a_location == b_location && a_suggestions == b_suggestions,
+            (
+                Timeout {
+                    type_name: a,
+                    timeout: a_timeout,
+                },
+                Timeout {
+                    type_name: b,
+                    timeout: b_timeout,
+                },
+            ) => a == b && a_timeout == b_timeout,
+            (FinalType { type_name: a }, FinalType { type_name: b }) => a == b,
+            (DependencyCycle { cycle: a }, DependencyCycle { cycle: b }) => a == b,
+            (ProvidedAsync { type_name: a }, ProvidedAsync { type_name: b }) => a == b,
+            (MissingDependencies { missing: a }, MissingDependencies { missing: b }) => a == b,
+            (NoPreviousValue { type_name: a }, NoPreviousValue { type_name: b }) => a == b,
+            (ProtectedType { type_name: a }, ProtectedType { type_name: b }) => a == b,
+            (AlreadyRegistered { type_name: a }, AlreadyRegistered { type_name: b }) => a == b,
+            (
+                StillShared {
+                    type_name: a,
+                    strong_count: a_count,
+                },
+                StillShared {
+                    type_name: b,
+                    strong_count: b_count,
+                },
+            ) => a == b && a_count == b_count,
+            (MergeConflict { conflicts: a }, MergeConflict { conflicts: b }) => a == b,
+            (
+                Custom {
+                    message: a,
+                    source: a_source,
+                },
+                Custom {
+                    message: b,
+                    source: b_source,
+                },
+            ) => {
+                a == b
+                    && a_source.as_ref().map(ToString::to_string)
+                        == b_source.as_ref().map(ToString::to_string)
+            }
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for RegistryError {
@@ -36,14 +287,111 @@ impl fmt::Display for RegistryError {
             RegistryError::TypeMismatch { type_name } => {
                 write!(f, "Type mismatch in registry for type: {}", type_name)
             }
-            RegistryError::TypeNotFound { type_name } => {
-                write!(f, "Type not found in registry: {}", type_name)
+            RegistryError::TypeNotFound {
+                type_name,
+                location,
+                suggestions,
+            } => {
+                write!(f, "Type not found in registry: {}", type_name)?;
+                if let Some(location) = location {
+                    write!(f, " (at {})", location)?;
+                }
+                if !suggestions.is_empty() {
+                    write!(f, "; did you mean {}?", suggestions.join(" or "))?;
+                }
+                Ok(())
+            }
+            RegistryError::Timeout { type_name, timeout } => {
+                write!(
+                    f,
+                    "Timed out after {:?} waiting for type: {}",
+                    timeout, type_name
+                )
+            }
+            RegistryError::FinalType { type_name } => {
+                write!(
+                    f,
+                    "Type is registered as final and cannot be replaced: {}",
+                    type_name
+                )
+            }
+            RegistryError::DependencyCycle { cycle } => {
+                write!(f, "Dependency cycle detected: {}", cycle.join(" -> "))
+            }
+            RegistryError::ProvidedAsync { type_name } => {
+                write!(
+                    f,
+                    "Type is provided asynchronously and must be resolved with resolve_async: {}",
+                    type_name
+                )
+            }
+            RegistryError::MissingDependencies { missing } => {
+                write!(f, "Missing dependencies: ")?;
+                for (i, dependency) in missing.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", dependency)?;
+                }
+                Ok(())
+            }
+            RegistryError::NoPreviousValue { type_name } => {
+                write!(f, "No previous value to revert to for type: {}", type_name)
             }
+            RegistryError::ProtectedType { type_name } => {
+                write!(
+                    f,
+                    "Type is protected and cannot be replaced or removed: {}",
+                    type_name
+                )
+            }
+            RegistryError::StillShared {
+                type_name,
+                strong_count,
+            } => {
+                write!(
+                    f,
+                    "Type is still shared by {} handles and cannot be taken: {}",
+                    strong_count, type_name
+                )
+            }
+            RegistryError::AlreadyRegistered { type_name } => {
+                write!(
+                    f,
+                    "Type is already registered in an append-only registry: {}",
+                    type_name
+                )
+            }
+            RegistryError::MergeConflict { conflicts } => {
+                write!(
+                    f,
+                    "Types registered in both registries: {}",
+                    conflicts.join(", ")
+                )
+            }
+            RegistryError::Custom {
+                message,
+                source: None,
+            } => write!(f, "{}", message),
+            RegistryError::Custom {
+                message,
+                source: Some(source),
+            } => write!(f, "{}: {}", message, source),
         }
     }
 }
 
-impl std::error::Error for RegistryError {}
+impl std::error::Error for RegistryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegistryError::Custom {
+                source: Some(source),
+                ..
+            } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -65,14 +413,58 @@ mod tests {
     fn test_type_not_found_display() {
         let err = RegistryError::TypeNotFound {
             type_name: "String",
+            location: None,
+            suggestions: Vec::new(),
         };
         assert_eq!(err.to_string(), "Type not found in registry: String");
     }
 
+    #[test]
+    fn test_type_not_found_display_with_location() {
+        let location = Location::caller();
+        let err = RegistryError::TypeNotFound {
+            type_name: "String",
+            location: Some(location),
+            suggestions: Vec::new(),
+        };
+        assert_eq!(
+            err.to_string(),
+            format!("Type not found in registry: String (at {})", location)
+        );
+    }
+
+    #[test]
+    fn test_type_not_found_display_with_suggestions() {
+        let err = RegistryError::TypeNotFound {
+            type_name: "app::Config",
+            location: None,
+            suggestions: vec!["app::config::Config", "app::Confg"],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Type not found in registry: app::Config; \
+             did you mean app::config::Config or app::Confg?"
+        );
+    }
+
+    #[test]
+    fn test_timeout_display() {
+        let err = RegistryError::Timeout {
+            type_name: "i32",
+            timeout: Duration::from_millis(250),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Timed out after 250ms waiting for type: i32"
+        );
+    }
+
     #[test]
     fn test_debug_format() {
         let err = RegistryError::TypeNotFound {
             type_name: "String",
+            location: None,
+            suggestions: Vec::new(),
         };
         assert!(format!("{:?}", err).contains("TypeNotFound"));
     }
@@ -83,7 +475,9 @@ mod tests {
         assert_ne!(
             RegistryError::RegistryLock,
             RegistryError::TypeNotFound {
-                type_name: "String"
+                type_name: "String",
+                location: None,
+                suggestions: Vec::new(),
             }
         );
     }
@@ -92,7 +486,100 @@ mod tests {
     fn test_error_trait() {
         let err: &dyn std::error::Error = &RegistryError::TypeNotFound {
             type_name: "String",
+            location: None,
+            suggestions: Vec::new(),
         };
         assert_eq!(err.to_string(), "Type not found in registry: String");
     }
+
+    #[test]
+    fn test_final_type_display() {
+        let err = RegistryError::FinalType { type_name: "Key" };
+        assert_eq!(
+            err.to_string(),
+            "Type is registered as final and cannot be replaced: Key"
+        );
+    }
+
+    #[test]
+    fn test_still_shared_display() {
+        let err = RegistryError::StillShared {
+            type_name: "Pool",
+            strong_count: 3,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Type is still shared by 3 handles and cannot be taken: Pool"
+        );
+    }
+
+    #[test]
+    fn test_already_registered_display() {
+        let err = RegistryError::AlreadyRegistered { type_name: "Key" };
+        assert_eq!(
+            err.to_string(),
+            "Type is already registered in an append-only registry: Key"
+        );
+    }
+
+    #[test]
+    fn test_protected_type_display() {
+        let err = RegistryError::ProtectedType { type_name: "Key" };
+        assert_eq!(
+            err.to_string(),
+            "Type is protected and cannot be replaced or removed: Key"
+        );
+    }
+
+    #[test]
+    fn test_dependency_cycle_display() {
+        let err = RegistryError::DependencyCycle {
+            cycle: vec!["A", "B", "A"],
+        };
+        assert_eq!(err.to_string(), "Dependency cycle detected: A -> B -> A");
+    }
+
+    #[test]
+    fn test_missing_dependencies_display() {
+        let err = RegistryError::MissingDependencies {
+            missing: vec![
+                MissingDependency {
+                    type_name: "Config",
+                    required_by: Some("Db"),
+                },
+                MissingDependency {
+                    type_name: "Cache",
+                    required_by: None,
+                },
+            ],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Missing dependencies: Config (required by Db), Cache"
+        );
+    }
+
+    #[test]
+    fn test_custom_and_context() {
+        use std::error::Error;
+
+        let err = RegistryError::custom("no database configured");
+        assert_eq!(err.to_string(), "no database configured");
+        assert!(err.source().is_none());
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "app.toml missing");
+        let err = RegistryError::from_source("reading config", io).with_context("starting up");
+        assert_eq!(
+            err.to_string(),
+            "starting up: reading config: app.toml missing"
+        );
+
+        let inner = err.source().unwrap();
+        assert_eq!(inner.to_string(), "reading config: app.toml missing");
+        assert_eq!(inner.source().unwrap().to_string(), "app.toml missing");
+        assert_eq!(
+            err,
+            RegistryError::from_source("starting up", "reading config: app.toml missing")
+        );
+    }
 }
@@ -1,3 +1,10 @@
+use std::panic::Location;
+use std::sync::Arc;
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+use crate::EventLevel;
+
 /// Events emitted by the registry during operations.
 ///
 /// These events are passed to the tracing callback set via `set_trace_callback`.
@@ -7,13 +14,20 @@
 /// value is stored (so a panic during storage is visible in the log), and
 /// `RegisterCompleted` fires after the value is successfully stored. If only
 /// `Register` appears without a following `RegisterCompleted`, the store panicked.
+/// `RegisterCompleted` also tells a first registration from a replacement.
+///
+/// Every variant carries an [`EventMeta`] filled in by the registry when the
+/// event is emitted; events constructed by hand can use `EventMeta::default()`.
 ///
 /// # Examples
 ///
 /// ```rust
 /// use singleton_registry::RegistryEvent;
 ///
-/// let event = RegistryEvent::Register { type_name: "i32" };
+/// let event = RegistryEvent::Register {
+///     type_name: "i32",
+///     meta: Default::default(),
+/// };
 /// assert_eq!(event.to_string(), "register { type_name: i32 }");
 /// ```
 #[derive(Debug, Clone)]
@@ -23,12 +37,33 @@ pub enum RegistryEvent {
     Register {
         /// The type name of the value being registered (e.g., "i32", "alloc::string::String")
         type_name: &'static str,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
     },
 
     /// A value was successfully stored in the registry. Fires after the insert.
     RegisterCompleted {
         /// The type name of the value that was stored
         type_name: &'static str,
+        /// Whether the value replaced a previously registered one
+        ///
+        /// Replacements after startup are usually worth an alert in production.
+        replaced: bool,
+        /// Whether the value was restored by `revert`
+        rollback: bool,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
+    },
+
+    /// A registration was refused because the registered value is final or
+    /// protected, or the registry is append-only.
+    ///
+    /// Follows the `Register` event in place of `RegisterCompleted`.
+    RegisterRejected {
+        /// The type name of the rejected value
+        type_name: &'static str,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
     },
 
     /// A value was requested from the registry.
@@ -37,6 +72,8 @@ pub enum RegistryEvent {
         type_name: &'static str,
         /// Whether the value was found in the registry
         found: bool,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
     },
 
     /// A type existence check was performed.
@@ -45,33 +82,311 @@ pub enum RegistryEvent {
         type_name: &'static str,
         /// Whether the type exists in the registry
         found: bool,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
+    },
+
+    /// A value was removed from the registry. Fires after the removal.
+    Remove {
+        /// The type name of the removed value
+        type_name: &'static str,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
     },
 
     /// The registry was cleared.
-    Clear {},
+    Clear {
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
+    },
+
+    /// The registry's configuration is being reloaded.
+    ///
+    /// Emitted by the `reload_on_sighup` handler (feature `unix`) before it runs
+    /// the reload closure, whose registrations follow.
+    Reload {
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
+    },
+
+    /// A cleared value was still referenced outside the registry.
+    ///
+    /// Emitted by `clear_with_leak_report` once per entry that outlived the teardown.
+    Leak {
+        /// The type name of the value
+        type_name: &'static str,
+        /// Number of `Arc` handles still alive outside the registry
+        outstanding: usize,
+        /// Data attached by the emitting registry (correlation context, ...)
+        meta: EventMeta,
+    },
+}
+
+impl RegistryEvent {
+    /// Whether the event reports a lookup (`Get` or `Contains`) rather than a change.
+    ///
+    /// Only lookups are subject to event sampling; see `set_event_sampling`.
+    pub fn is_lookup(&self) -> bool {
+        matches!(
+            self,
+            RegistryEvent::Get { .. } | RegistryEvent::Contains { .. }
+        )
+    }
+
+    /// The type the event is about; `None` for `Clear` and `Reload`.
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self {
+            RegistryEvent::Register { type_name, .. }
+            | RegistryEvent::RegisterCompleted { type_name, .. }
+            | RegistryEvent::RegisterRejected { type_name, .. }
+            | RegistryEvent::Get { type_name, .. }
+            | RegistryEvent::Contains { type_name, .. }
+            | RegistryEvent::Remove { type_name, .. }
+            | RegistryEvent::Leak { type_name, .. } => Some(type_name),
+            RegistryEvent::Clear { .. } | RegistryEvent::Reload { .. } => None,
+        }
+    }
+
+    /// Short name of the operation, as used in the `Display` output (e.g.
+    /// `"register"` or `"get"`).
+    pub fn op(&self) -> &'static str {
+        match self {
+            RegistryEvent::Register { .. } => "register",
+            RegistryEvent::RegisterCompleted { .. } => "register_completed",
+            RegistryEvent::RegisterRejected { .. } => "register_rejected",
+            RegistryEvent::Get { .. } => "get",
+            RegistryEvent::Contains { .. } => "contains",
+            RegistryEvent::Remove { .. } => "remove",
+            RegistryEvent::Clear { .. } => "clear",
+            RegistryEvent::Reload { .. } => "reload",
+            RegistryEvent::Leak { .. } => "leak",
+        }
+    }
+
+    /// The severity of the event; see `set_trace_level`.
+    pub fn level(&self) -> EventLevel {
+        match self {
+            RegistryEvent::Get { found: true, .. } | RegistryEvent::Contains { .. } => {
+                EventLevel::Debug
+            }
+            RegistryEvent::Register { .. }
+            | RegistryEvent::RegisterCompleted { .. }
+            | RegistryEvent::Remove { .. } => EventLevel::Info,
+            RegistryEvent::Clear { .. } | RegistryEvent::Reload { .. } => EventLevel::Notice,
+            RegistryEvent::Get { found: false, .. }
+            | RegistryEvent::RegisterRejected { .. }
+            | RegistryEvent::Leak { .. } => EventLevel::Warn,
+        }
+    }
+
+    /// The data attached to the event by the emitting registry.
+    pub fn meta(&self) -> &EventMeta {
+        match self {
+            RegistryEvent::Register { meta, .. }
+            | RegistryEvent::RegisterCompleted { meta, .. }
+            | RegistryEvent::RegisterRejected { meta, .. }
+            | RegistryEvent::Get { meta, .. }
+            | RegistryEvent::Contains { meta, .. }
+            | RegistryEvent::Remove { meta, .. }
+            | RegistryEvent::Clear { meta }
+            | RegistryEvent::Reload { meta }
+            | RegistryEvent::Leak { meta, .. } => meta,
+        }
+    }
+
+    /// The correlation context the event was emitted in, if any.
+    ///
+    /// See [`with_trace_context`](crate::with_trace_context).
+    pub fn context(&self) -> Option<&str> {
+        self.meta().context.as_deref()
+    }
+
+    /// Name of the registry that emitted the event.
+    ///
+    /// Tells events apart when several registries share one trace sink.
+    pub fn registry(&self) -> &'static str {
+        self.meta().registry
+    }
+
+    /// A copy of the event carrying `meta`, keeping the duration, location and
+    /// scope recorded by the emitter.
+    pub(crate) fn with_meta(&self, meta: EventMeta) -> Self {
+        let meta = EventMeta {
+            duration: self.meta().duration,
+            location: self.meta().location,
+            scope: self.meta().scope,
+            ..meta
+        };
+        let mut event = self.clone();
+        match &mut event {
+            RegistryEvent::Register { meta: slot, .. }
+            | RegistryEvent::RegisterCompleted { meta: slot, .. }
+            | RegistryEvent::RegisterRejected { meta: slot, .. }
+            | RegistryEvent::Get { meta: slot, .. }
+            | RegistryEvent::Contains { meta: slot, .. }
+            | RegistryEvent::Remove { meta: slot, .. }
+            | RegistryEvent::Clear { meta: slot }
+            | RegistryEvent::Reload { meta: slot }
+            | RegistryEvent::Leak { meta: slot, .. } => *slot = meta,
+        }
+        event
+    }
+}
+
+/// Data the registry attaches to every event it emits.
+///
+/// Construct it with `EventMeta::default()` when building events by hand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EventMeta {
+    /// The correlation context active where the event was emitted.
+    ///
+    /// Set with [`with_trace_context`](crate::with_trace_context).
+    pub context: Option<Arc<str>>,
+    /// Name of the registry that emitted the event, e.g. `"services"` for
+    /// `define_registry!(services)`; empty for hand-built events.
+    pub registry: &'static str,
+    /// The thread that emitted the event, if the registry records thread identity.
+    ///
+    /// Enable it with `set_event_threads(true)`; off by default.
+    pub thread_id: Option<ThreadId>,
+    /// Name of the emitting thread, if it has one and thread identity is recorded.
+    pub thread_name: Option<Arc<str>>,
+    /// Time spent inside the operation, including lock waits.
+    ///
+    /// Measured for `Get` and `RegisterCompleted`, so lock contention shows up
+    /// in the trace stream; `None` for other events.
+    pub duration: Option<Duration>,
+    /// Source location of the registering call, for the register events.
+    ///
+    /// Tells which module replaced a value: `RegisterCompleted { replaced: true }`
+    /// also shows it in its `Display`.
+    pub location: Option<&'static Location<'static>>,
+    /// The scope the operation ran in, for operations on a
+    /// [`Scope`](crate::Scope) view; `None` for the registry itself.
+    pub scope: Option<&'static str>,
+}
+
+impl EventMeta {
+    /// Capture the metadata of the current thread or task, for an event
+    /// emitted by `registry`.
+    pub(crate) fn capture(registry: &'static str) -> Self {
+        Self {
+            context: crate::trace_context::trace_context(),
+            registry,
+            thread_id: None,
+            thread_name: None,
+            duration: None,
+            location: None,
+            scope: None,
+        }
+    }
+
+    /// Metadata of a call made at `location`.
+    pub(crate) fn at(location: &'static Location<'static>) -> Self {
+        Self {
+            location: Some(location),
+            ..Self::default()
+        }
+    }
+
+    /// Metadata of an operation that started at `started`.
+    pub(crate) fn timed(started: Instant) -> Self {
+        Self {
+            duration: Some(started.elapsed()),
+            ..Self::default()
+        }
+    }
+
+    /// The same metadata, also recording the call site `location`.
+    pub(crate) fn with_location(mut self, location: &'static Location<'static>) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// The same metadata, for an operation in `scope`.
+    pub(crate) fn in_scope(mut self, scope: &'static str) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    /// Record the identity of the current thread.
+    pub(crate) fn with_current_thread(mut self) -> Self {
+        let thread = std::thread::current();
+        self.thread_id = Some(thread.id());
+        self.thread_name = thread.name().map(Arc::from);
+        self
+    }
 }
 
 impl std::fmt::Display for RegistryEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RegistryEvent::Register { type_name } => {
-                write!(f, "register {{ type_name: {} }}", type_name)
+            RegistryEvent::Register { type_name, .. } => {
+                write!(f, "register {{ type_name: {} }}", type_name)?
             }
-            RegistryEvent::RegisterCompleted { type_name } => {
-                write!(f, "register_completed {{ type_name: {} }}", type_name)
-            }
-            RegistryEvent::Get { type_name, found } => {
-                write!(f, "get {{ type_name: {}, found: {} }}", type_name, found)
-            }
-            RegistryEvent::Contains { type_name, found } => {
+            RegistryEvent::RegisterCompleted {
+                type_name,
+                replaced: false,
+                ..
+            } => write!(f, "register_completed {{ type_name: {} }}", type_name)?,
+            RegistryEvent::RegisterCompleted {
+                type_name,
+                rollback,
+                meta,
+                ..
+            } => {
                 write!(
                     f,
-                    "contains {{ type_name: {}, found: {} }}",
-                    type_name, found
-                )
+                    "register_completed {{ type_name: {}, replaced: true{} }}",
+                    type_name,
+                    if *rollback { ", rollback: true" } else { "" }
+                )?;
+                if let Some(location) = meta.location {
+                    write!(f, " (at {})", location)?;
+                }
+            }
+            RegistryEvent::RegisterRejected { type_name, .. } => {
+                write!(f, "register_rejected {{ type_name: {} }}", type_name)?
+            }
+            RegistryEvent::Get {
+                type_name, found, ..
+            } => write!(f, "get {{ type_name: {}, found: {} }}", type_name, found)?,
+            RegistryEvent::Contains {
+                type_name, found, ..
+            } => write!(
+                f,
+                "contains {{ type_name: {}, found: {} }}",
+                type_name, found
+            )?,
+            RegistryEvent::Remove { type_name, .. } => {
+                write!(f, "remove {{ type_name: {} }}", type_name)?
             }
-            RegistryEvent::Clear {} => write!(f, "Clearing the Registry"),
+            RegistryEvent::Clear { .. } => write!(f, "Clearing the Registry")?,
+            RegistryEvent::Reload { .. } => write!(f, "reload")?,
+            RegistryEvent::Leak {
+                type_name,
+                outstanding,
+                ..
+            } => write!(
+                f,
+                "leak {{ type_name: {}, outstanding: {} }}",
+                type_name, outstanding
+            )?,
+        }
+
+        if let Some(context) = self.context() {
+            write!(f, " (context: {})", context)?;
+        }
+        if let Some(scope) = self.meta().scope {
+            write!(f, " (scope: {})", scope)?;
         }
+        match (&self.meta().thread_name, self.meta().thread_id) {
+            (Some(name), _) => write!(f, " (thread: {})", name)?,
+            (None, Some(id)) => write!(f, " (thread: {:?})", id)?,
+            (None, None) => {}
+        }
+        Ok(())
     }
 }
 
@@ -81,14 +396,53 @@ mod tests {
 
     #[test]
     fn test_display_register() {
-        let ev = RegistryEvent::Register { type_name: "i32" };
+        let ev = RegistryEvent::Register {
+            type_name: "i32",
+            meta: EventMeta::default(),
+        };
         assert_eq!(ev.to_string(), "register { type_name: i32 }");
     }
 
     #[test]
     fn test_display_register_completed() {
-        let ev = RegistryEvent::RegisterCompleted { type_name: "i32" };
+        let ev = RegistryEvent::RegisterCompleted {
+            type_name: "i32",
+            replaced: false,
+            rollback: false,
+            meta: EventMeta::default(),
+        };
         assert_eq!(ev.to_string(), "register_completed { type_name: i32 }");
+
+        let ev = RegistryEvent::RegisterCompleted {
+            type_name: "i32",
+            replaced: true,
+            rollback: false,
+            meta: EventMeta::default(),
+        };
+        assert_eq!(
+            ev.to_string(),
+            "register_completed { type_name: i32, replaced: true }"
+        );
+
+        let ev = RegistryEvent::RegisterCompleted {
+            type_name: "i32",
+            replaced: true,
+            rollback: true,
+            meta: EventMeta::default(),
+        };
+        assert_eq!(
+            ev.to_string(),
+            "register_completed { type_name: i32, replaced: true, rollback: true }"
+        );
+    }
+
+    #[test]
+    fn test_display_register_rejected() {
+        let ev = RegistryEvent::RegisterRejected {
+            type_name: "Key",
+            meta: EventMeta::default(),
+        };
+        assert_eq!(ev.to_string(), "register_rejected { type_name: Key }");
     }
 
     #[test]
@@ -96,6 +450,7 @@ mod tests {
         let ev = RegistryEvent::Get {
             type_name: "String",
             found: true,
+            meta: EventMeta::default(),
         };
         assert_eq!(ev.to_string(), "get { type_name: String, found: true }");
     }
@@ -105,13 +460,86 @@ mod tests {
         let ev = RegistryEvent::Contains {
             type_name: "u8",
             found: false,
+            meta: EventMeta::default(),
         };
         assert_eq!(ev.to_string(), "contains { type_name: u8, found: false }");
     }
 
+    #[test]
+    fn test_display_remove() {
+        let ev = RegistryEvent::Remove {
+            type_name: "u8",
+            meta: EventMeta::default(),
+        };
+        assert_eq!(ev.to_string(), "remove { type_name: u8 }");
+    }
+
     #[test]
     fn test_display_clear() {
-        let ev = RegistryEvent::Clear {};
+        let ev = RegistryEvent::Clear {
+            meta: EventMeta::default(),
+        };
         assert_eq!(ev.to_string(), "Clearing the Registry");
     }
+
+    #[test]
+    fn test_display_leak() {
+        let ev = RegistryEvent::Leak {
+            type_name: "i32",
+            outstanding: 2,
+            meta: EventMeta::default(),
+        };
+        assert_eq!(ev.to_string(), "leak { type_name: i32, outstanding: 2 }");
+    }
+
+    #[test]
+    fn test_is_lookup() {
+        assert!(RegistryEvent::Get {
+            type_name: "u8",
+            found: true,
+            meta: EventMeta::default()
+        }
+        .is_lookup());
+        assert!(RegistryEvent::Contains {
+            type_name: "u8",
+            found: false,
+            meta: EventMeta::default()
+        }
+        .is_lookup());
+        assert!(!RegistryEvent::Register {
+            type_name: "u8",
+            meta: EventMeta::default()
+        }
+        .is_lookup());
+        assert!(!RegistryEvent::Clear {
+            meta: EventMeta::default()
+        }
+        .is_lookup());
+    }
+
+    #[test]
+    fn test_level() {
+        let get = |found| RegistryEvent::Get {
+            type_name: "u8",
+            found,
+            meta: EventMeta::default(),
+        };
+        assert_eq!(get(true).level(), EventLevel::Debug);
+        assert_eq!(get(false).level(), EventLevel::Warn);
+        assert_eq!(
+            RegistryEvent::Remove {
+                type_name: "u8",
+                meta: EventMeta::default()
+            }
+            .level(),
+            EventLevel::Info
+        );
+        assert_eq!(
+            RegistryEvent::Clear {
+                meta: EventMeta::default()
+            }
+            .level(),
+            EventLevel::Notice
+        );
+    }
 }
@@ -0,0 +1,113 @@
+//! Cloneable handles to a specific registry.
+//!
+//! A library can't name the module a downstream `define_registry!` invocation
+//! generates. It accepts a [`RegistryHandle`] instead: `app::handle()` for a
+//! macro registry, `RegistryHandle::from(registry)` for a runtime [`Registry`].
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{DynRegistry, ReadOnlyRegistry, Registry};
+
+/// A cheap, cloneable handle to a registry.
+///
+/// Clones share the registry, and a runtime [`Registry`] lives as long as its
+/// last handle. The typed operations (`register`, `get`, `contains`, ...) come
+/// from `dyn DynRegistry`, which the handle dereferences to.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, Registry, RegistryHandle};
+///
+/// define_registry!(app);
+///
+/// // A library function, independent of the application's registries
+/// fn configure(registry: RegistryHandle) {
+///     registry.register(30u64);
+/// }
+///
+/// configure(app::handle());
+/// assert_eq!(*app::get::<u64>().unwrap(), 30);
+///
+/// let tenant = RegistryHandle::from(Registry::named("tenant"));
+/// configure(tenant.clone());
+/// assert_eq!(*tenant.get::<u64>().unwrap(), 30);
+/// ```
+#[derive(Clone)]
+pub struct RegistryHandle(Arc<dyn DynRegistry>);
+
+impl RegistryHandle {
+    /// Create a handle owning `registry`.
+    pub fn new(registry: impl DynRegistry + 'static) -> Self {
+        Self(Arc::new(registry))
+    }
+
+    /// Name of the registry, as reported in its events.
+    pub fn name(&self) -> &'static str {
+        self.0.registry_name()
+    }
+
+    /// A view of the same registry that can only look values up.
+    pub fn read_only(&self) -> ReadOnlyRegistry {
+        ReadOnlyRegistry::from(self.clone())
+    }
+}
+
+impl Deref for RegistryHandle {
+    type Target = dyn DynRegistry;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl From<Registry> for RegistryHandle {
+    fn from(registry: Registry) -> Self {
+        Self::new(registry)
+    }
+}
+
+impl fmt::Debug for RegistryHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RegistryHandle").field(&self.name()).finish()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_macro_registry_handle() {
+        define_registry!(reg);
+
+        let handle = reg::handle();
+        handle.clone().register(1u8);
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(handle.contains::<u8>(), Ok(true));
+        assert_eq!(format!("{handle:?}"), "RegistryHandle(\"reg\")");
+    }
+
+    #[test]
+    fn test_runtime_registry_lives_as_long_as_its_handles() {
+        let value = Arc::new(String::from("tenant"));
+        let handle = RegistryHandle::from(Registry::named("tenant"));
+        handle.register_arc(value.clone());
+
+        let clone = handle.clone();
+        drop(handle);
+        assert_eq!(*clone.get::<String>().unwrap(), "tenant");
+        assert_eq!(clone.name(), "tenant");
+
+        drop(clone);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Process-wide index of the registries defined with `define_registry!`.
+//!
+//! Each registry adds itself the first time it is used, so
+//! [`registries()`] lets diagnostics tooling discover and inspect every
+//! registry in the process, including those of dependency crates, without
+//! knowing their names. Registries that were never touched have nothing to
+//! show and are not listed.
+
+use std::any::TypeId;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::registry_trait::lock_storage;
+use crate::{EntryInfo, RegistryApi, RegistryConfig};
+
+// Wiring configuration rather than registry data: a std lock, see `crate::sync`.
+static INDEX: Mutex<Vec<RegistryInfo>> = Mutex::new(Vec::new());
+
+/// A registry found by [`registries()`], with its basic read-only operations.
+#[derive(Clone, Copy)]
+pub struct RegistryInfo {
+    type_id: TypeId,
+    name: &'static str,
+    config: fn() -> &'static RegistryConfig,
+    len: fn() -> usize,
+    describe: fn() -> Vec<EntryInfo>,
+    dump: fn() -> String,
+}
+
+impl RegistryInfo {
+    fn of<R: RegistryApi + Default + 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<R>(),
+            name: R::name(),
+            config: R::config,
+            len: || lock_storage::<R>().unwrap_or_else(|p| p.into_inner()).len(),
+            describe: || R::default().describe(),
+            dump: || R::default().dump(),
+        }
+    }
+
+    /// Name of the registry, as reported in its events.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Options the registry was defined with.
+    pub fn config(&self) -> &'static RegistryConfig {
+        (self.config)()
+    }
+
+    /// Number of registered values.
+    pub fn len(&self) -> usize {
+        (self.len)()
+    }
+
+    /// Whether no value is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Snapshot of every entry, like the registry's `describe()`.
+    pub fn describe(&self) -> Vec<EntryInfo> {
+        (self.describe)()
+    }
+
+    /// Every entry, one per line, like the registry's `dump()`.
+    pub fn dump(&self) -> String {
+        (self.dump)()
+    }
+}
+
+impl fmt::Debug for RegistryInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistryInfo")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Every registry defined with `define_registry!` that has been used so far,
+/// sorted by name.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, registries};
+///
+/// define_registry!(services);
+///
+/// services::register(8080u16);
+///
+/// let services = registries()
+///     .into_iter()
+///     .find(|r| r.name() == "services")
+///     .unwrap();
+/// assert_eq!(services.len(), 1);
+/// assert_eq!(services.describe()[0].type_name, "u16");
+/// ```
+pub fn registries() -> Vec<RegistryInfo> {
+    let mut registries = INDEX.lock().unwrap_or_else(|p| p.into_inner()).clone();
+    registries.sort_by_key(|r| r.name);
+    registries
+}
+
+/// Add `R` to the index, once. Called by `define_registry!` when the registry's
+/// state is first initialized.
+#[doc(hidden)]
+pub fn __index_registry<R: RegistryApi + Default + 'static>() {
+    let mut index = INDEX.lock().unwrap_or_else(|p| p.into_inner());
+    if !index.iter().any(|r| r.type_id == TypeId::of::<R>()) {
+        index.push(RegistryInfo::of::<R>());
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    fn find(name: &str) -> Option<RegistryInfo> {
+        registries().into_iter().find(|r| r.name() == name)
+    }
+
+    #[test]
+    fn test_registries_lists_used_registries() {
+        define_registry!(index_used);
+        define_registry!(index_unused, { capacity: 8 });
+
+        index_used::register(1u8);
+        index_used::register("x");
+        assert!(find("index_unused").is_none());
+
+        let used = find("index_used").unwrap();
+        assert_eq!(used.len(), 2);
+        assert_eq!(used.describe()[0].type_name, "&str");
+        assert!(used.dump().contains("u8 = <opaque>"));
+
+        let _ = index_unused::contains::<u8>();
+        let unused = find("index_unused").unwrap();
+        assert!(unused.is_empty());
+        assert_eq!(unused.config().capacity, 8);
+
+        // Listed once, however often it is used
+        let _ = index_unused::contains::<u8>();
+        let count = registries()
+            .iter()
+            .filter(|r| r.name() == "index_unused")
+            .count();
+        assert_eq!(count, 1);
+    }
+}
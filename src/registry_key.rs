@@ -0,0 +1,91 @@
+//! Named keys for registry entries.
+//!
+//! A registry holds one value per type, so two `String`s (a database URL and a
+//! cache URL) need wrapper types. A [`RegistryKey`] is a lighter alternative,
+//! in the style of `typemap`: a zero-sized key type names the entry and
+//! declares its value type, and `get_key::<DatabaseUrl>()` documents itself.
+
+/// A key type naming a registry entry of type [`Value`](RegistryKey::Value).
+///
+/// The entry is stored under the key's `TypeId`, so any number of keys can
+/// map to the same value type, and it is listed under the key's type name.
+/// Use it with `register_key`, `get_key`, `try_get_key`, `contains_key` and
+/// `remove_key`.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, RegistryKey};
+///
+/// struct DatabaseUrl;
+/// impl RegistryKey for DatabaseUrl {
+///     type Value = String;
+/// }
+///
+/// struct CacheUrl;
+/// impl RegistryKey for CacheUrl {
+///     type Value = String;
+/// }
+///
+/// define_registry!(config);
+///
+/// config::register_key::<DatabaseUrl>("postgres://localhost".to_string());
+/// config::register_key::<CacheUrl>("redis://localhost".to_string());
+///
+/// assert_eq!(*config::get_key::<DatabaseUrl>().unwrap(), "postgres://localhost");
+/// assert_eq!(*config::get_key::<CacheUrl>().unwrap(), "redis://localhost");
+/// assert!(!config::contains::<String>().unwrap());
+/// ```
+pub trait RegistryKey: 'static {
+    /// The type of the value registered under the key.
+    type Value: Send + Sync + 'static;
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, RegistryError};
+
+    struct Port;
+    impl RegistryKey for Port {
+        type Value = u16;
+    }
+
+    struct AdminPort;
+    impl RegistryKey for AdminPort {
+        type Value = u16;
+    }
+
+    #[test]
+    fn test_keys_sharing_a_value_type() {
+        define_registry!(reg);
+
+        reg::register_key::<Port>(8080);
+        reg::register_key::<AdminPort>(9090);
+        reg::register(1u16);
+
+        assert_eq!(*reg::get_key::<Port>().unwrap(), 8080);
+        assert_eq!(*reg::get_key::<AdminPort>().unwrap(), 9090);
+        assert_eq!(*reg::get::<u16>().unwrap(), 1);
+        assert_eq!(*reg::remove_key::<AdminPort>().unwrap(), 9090);
+        assert!(!reg::contains_key::<AdminPort>().unwrap());
+        assert!(reg::try_get_key::<AdminPort>().is_none());
+    }
+
+    #[test]
+    fn test_entries_are_named_after_the_key() {
+        define_registry!(reg);
+
+        reg::register_key::<Port>(8080);
+        let names: Vec<_> = reg::describe().iter().map(|e| e.type_name).collect();
+        assert_eq!(names, [std::any::type_name::<Port>()]);
+
+        let err = reg::get_key::<AdminPort>().unwrap_err();
+        assert!(matches!(
+            err,
+            RegistryError::TypeNotFound { type_name, .. }
+                if type_name == std::any::type_name::<AdminPort>()
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+//! Auxiliary per-registry state.
+//!
+//! Everything a registry needs besides its storage map and trace callback lives
+//! in [`RegistryState`], so new subsystems can extend it without changing the
+//! accessors that `RegistryApi` implementors have to provide.
+
+/// Auxiliary state owned by a single registry.
+///
+/// Implementors of [`RegistryApi`](crate::RegistryApi) expose one static instance
+/// through `RegistryApi::state()`. The contents are internal; construct it with
+/// [`RegistryState::new`] (or `Default`) and never share it between registries.
+///
+/// # Examples
+///
+/// ```rust
+/// use singleton_registry::RegistryState;
+/// use std::sync::LazyLock;
+///
+/// static STATE: LazyLock<RegistryState> = LazyLock::new(RegistryState::new);
+/// ```
+#[derive(Debug, Default)]
+pub struct RegistryState {
+    pub(crate) registration_waiters: crate::wait_for::RegistrationWaiters,
+
+    pub(crate) change_listeners: crate::on_change::ChangeListeners,
+
+    pub(crate) history: crate::history::History,
+
+    pub(crate) timeline: crate::timeline::Timeline,
+
+    pub(crate) lazy: crate::lazy::LazyProviders,
+
+    pub(crate) init_locks: crate::init_lock::InitLocks,
+
+    pub(crate) providers: crate::resolver::Providers,
+
+    pub(crate) event_sampler: crate::event_sampling::EventSampler,
+
+    pub(crate) event_threads: crate::sync::AtomicBool,
+
+    pub(crate) access_tracking: crate::sync::AtomicBool,
+
+    pub(crate) trace_level: crate::event_level::LevelFilter,
+
+    pub(crate) event_parent: crate::event_forwarding::EventParent,
+
+    pub(crate) mirrors: crate::mirror::Mirrors,
+
+    pub(crate) scoped: crate::scope::ScopedEntries,
+
+    pub(crate) callback_panics: crate::callback_panic::CallbackPanics,
+
+    pub(crate) limits: crate::limited::Limits,
+
+    #[cfg(any(test, feature = "test-util"))]
+    pub(crate) test_lock: crate::test_guard::TestLock,
+
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    pub(crate) lazy_async: crate::lazy_async::AsyncLazyProviders,
+
+    #[cfg(feature = "tokio")]
+    pub(crate) watchers: crate::watch::Watchers,
+
+    #[cfg(feature = "tokio")]
+    pub(crate) event_subscribers: crate::event_stream::EventSubscribers,
+
+    #[cfg(feature = "tokio")]
+    pub(crate) shutdown_hooks: crate::shutdown::ShutdownHooks,
+
+    #[cfg(feature = "tokio")]
+    pub(crate) health_probes: crate::health::HealthProbes,
+
+    #[cfg(feature = "stats")]
+    pub(crate) stats: crate::stats::LookupStats,
+
+    #[cfg(feature = "audit")]
+    pub(crate) audit: crate::audit::AuditLog,
+
+    #[cfg(feature = "file-backend")]
+    pub(crate) file_backend: crate::file_backend::FileBackend,
+
+    #[cfg(feature = "log")]
+    pub(crate) log_bridge: crate::log_bridge::LogBridge,
+
+    #[cfg(feature = "contention-stats")]
+    pub(crate) storage_lock: crate::contention_stats::LockCounters,
+
+    #[cfg(feature = "contention-stats")]
+    pub(crate) trace_lock: crate::contention_stats::LockCounters,
+}
+
+impl RegistryState {
+    /// Create an empty registry state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -8,9 +8,28 @@
 
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use std::sync::{Arc, LazyLock, Mutex};
-
-use crate::{RegistryError, RegistryEvent};
+use std::fmt::{self, Write as _};
+use std::panic::Location;
+use std::sync::{Arc, LockResult};
+use std::time::{Duration, Instant};
+
+use crate::limited::Semaphore;
+use crate::resolver::Provider;
+use crate::sync::{LazyLock, Mutex, MutexGuard};
+use crate::wait_for::{get_with_retries, wait_blocking};
+#[cfg(feature = "tokio")]
+use crate::{AsyncHealthCheck, AsyncShutdown, HealthReport, ShutdownReport};
+use crate::{
+    CallbackPanicPolicy, ChangeListenerGuard, ConflictPolicy, ContentEquality, Dependencies,
+    EntryInfo, EventLevel, EventMeta, EventSampling, Metadata, Permit, PoisonPolicy, Pool,
+    PoolGuard, ReadOnlyRegistry, RegistrationMode, RegistryConfig, RegistryEntry, RegistryError,
+    RegistryEvent, RegistryHandle, RegistryKey, RegistryState, ResolveCtx, Scope, StaleEntry,
+    TimelineOp, TimelineRecord, WaitFor,
+};
+#[cfg(any(test, feature = "test-util"))]
+use crate::{
+    ClearReport, EventRecorder, LeakReport, LeakedEntry, RegistrySnapshot, TestGuard, TestLockGuard,
+};
 
 /// Type alias for the trace callback storage.
 ///
@@ -18,10 +37,613 @@ use crate::{RegistryError, RegistryEvent};
 /// Keep both definitions in sync.
 type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>>>;
 
+/// The map held behind the storage lock.
+pub(crate) type StorageMap = HashMap<TypeId, RegistryEntry>;
+
+/// The value held behind the trace lock.
+pub(crate) type TraceSlot = Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>;
+
+/// The parts of a registry the shared core operates on.
+///
+/// Implemented for every [`RegistryApi`] through its statics and for a runtime
+/// [`Registry`](crate::Registry) through its fields, so [`store_entry`],
+/// [`get_entry`], [`contains_entry`] and [`remove_entry`] behave the same for both.
+pub(crate) trait RegistryCore {
+    /// Acquire the storage lock.
+    fn core_storage(&self) -> LockResult<MutexGuard<'_, StorageMap>>;
+
+    /// The registry's auxiliary state.
+    fn core_state(&self) -> &RegistryState;
+
+    /// The registry's configuration.
+    fn core_config(&self) -> &RegistryConfig;
+
+    /// Emit `event` through the registry's trace pipeline.
+    fn core_emit(&self, event: &RegistryEvent);
+
+    /// Persist the contents after a mutation, if the registry is file-backed.
+    fn core_persist(&self) {}
+}
+
+impl<R: RegistryApi + ?Sized> RegistryCore for R {
+    fn core_storage(&self) -> LockResult<MutexGuard<'_, StorageMap>> {
+        lock_storage::<R>()
+    }
+
+    fn core_state(&self) -> &RegistryState {
+        R::state()
+    }
+
+    fn core_config(&self) -> &RegistryConfig {
+        R::config()
+    }
+
+    fn core_emit(&self, event: &RegistryEvent) {
+        self.emit_event(event);
+    }
+
+    #[cfg(feature = "file-backend")]
+    fn core_persist(&self) {
+        crate::file_backend::save(self);
+    }
+}
+
+/// Acquire the storage lock of `R`.
+#[cfg(not(feature = "contention-stats"))]
+pub(crate) fn lock_storage<R: RegistryApi + ?Sized>() -> LockResult<MutexGuard<'static, StorageMap>>
+{
+    LazyLock::force(R::storage()).lock()
+}
+
+/// Acquire the storage lock of `R`, recording contention.
+#[cfg(feature = "contention-stats")]
+pub(crate) fn lock_storage<R: RegistryApi + ?Sized>() -> LockResult<MutexGuard<'static, StorageMap>>
+{
+    R::state()
+        .storage_lock
+        .acquire(LazyLock::force(R::storage()))
+}
+
+/// Acquire the storage lock of `api` for a lookup, applying its poison policy.
+fn lock_for_lookup<R: RegistryCore + ?Sized>(
+    api: &R,
+) -> Result<MutexGuard<'_, StorageMap>, RegistryError> {
+    match api.core_storage() {
+        Ok(guard) => Ok(guard),
+        Err(poisoned) if api.core_config().poison_policy == PoisonPolicy::Recover => {
+            Ok(poisoned.into_inner())
+        }
+        Err(_) => Err(RegistryError::RegistryLock),
+    }
+}
+
+/// Look up `T` in `R` without emitting trace events.
+pub(crate) fn lookup<R: RegistryApi + ?Sized, T: Send + Sync + 'static>() -> Option<Arc<T>> {
+    lock_storage::<R>()
+        .unwrap_or_else(|p| p.into_inner())
+        .get(&TypeId::of::<T>())
+        .and_then(|e| e.value().clone().downcast::<T>().ok())
+}
+
+/// Acquire the trace lock of `R`.
+#[cfg(not(feature = "contention-stats"))]
+fn lock_trace<R: RegistryApi + ?Sized>() -> LockResult<MutexGuard<'static, TraceSlot>> {
+    LazyLock::force(R::trace()).lock()
+}
+
+/// Acquire the trace lock of `R`, recording contention.
+#[cfg(feature = "contention-stats")]
+fn lock_trace<R: RegistryApi + ?Sized>() -> LockResult<MutexGuard<'static, TraceSlot>> {
+    R::state().trace_lock.acquire(LazyLock::force(R::trace()))
+}
+
+/// Hand an emitted event to the trace callback and event subscribers of `R`,
+/// then to its parent, if it forwards events.
+fn deliver<R: RegistryApi + ?Sized>(event: &RegistryEvent) {
+    deliver_via(lock_trace::<R>, R::state(), event);
+}
+
+/// [`deliver`] for the registry whose trace lock `lock_trace` acquires and
+/// whose auxiliary state is `state`.
+fn deliver_via<'a>(
+    lock_trace: impl Fn() -> LockResult<MutexGuard<'a, TraceSlot>>,
+    state: &RegistryState,
+    event: &RegistryEvent,
+) {
+    let callback = {
+        let guard = lock_trace().unwrap_or_else(|p| p.into_inner());
+        guard.as_ref().cloned()
+    }; // lock released here, before the callback is invoked
+    if let Some(cb) = callback {
+        if state.callback_panics.call(|| cb(event)) {
+            let mut guard = lock_trace().unwrap_or_else(|p| p.into_inner());
+            // Keep a callback installed while this one was running
+            if guard
+                .as_ref()
+                .is_some_and(|current| Arc::ptr_eq(current, &cb))
+            {
+                *guard = None;
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    state.event_subscribers.send(event);
+
+    if let Some(parent) = state.event_parent.get() {
+        (parent.deliver)(event);
+    }
+}
+
+/// The link forwarding events to the registry `P`.
+pub(crate) fn parent_link<P: RegistryApi>() -> crate::event_forwarding::ParentLink {
+    crate::event_forwarding::ParentLink {
+        state: P::state,
+        deliver: deliver::<P>,
+    }
+}
+
+/// Emit `event` for the registry described by the arguments: record it for
+/// auditing, filter it by the trace switch, level and sampling, stamp its
+/// metadata and hand it to every sink.
+///
+/// The body of [`RegistryApi::emit_event`], shared with runtime registries.
+pub(crate) fn emit_via<'a>(
+    name: &'static str,
+    config: &RegistryConfig,
+    state: &RegistryState,
+    lock_trace: impl Fn() -> LockResult<MutexGuard<'a, TraceSlot>>,
+    event: &RegistryEvent,
+) {
+    #[cfg(feature = "audit")]
+    state.audit.record(event, name);
+
+    if !config.trace {
+        return;
+    }
+    if !state.trace_level.admits(event.level()) {
+        return;
+    }
+    if event.is_lookup() && !state.event_sampler.admit() {
+        return;
+    }
+    let mut meta = EventMeta::capture(name);
+    if state
+        .event_threads
+        .load(std::sync::atomic::Ordering::Relaxed)
+    {
+        meta = meta.with_current_thread();
+    }
+    let event = &event.with_meta(meta);
+
+    #[cfg(feature = "tracing")]
+    crate::tracing_bridge::emit(event);
+    #[cfg(feature = "log")]
+    state.log_bridge.forward(event);
+
+    deliver_via(lock_trace, state, event);
+    crate::global_sink::emit(event);
+}
+
+/// Store `value` in `api`, unless the registered value of `T` is final.
+///
+/// Shared by every registering method. The caller's location is recorded in
+/// the entry and the events; `prepare` customizes the new entry (finality,
+/// metadata, ...).
+#[track_caller]
+pub(crate) fn store<R: RegistryCore + ?Sized, T: Send + Sync + 'static>(
+    api: &R,
+    value: Arc<T>,
+    prepare: impl FnOnce(RegistryEntry) -> RegistryEntry,
+) -> Result<(), RegistryError> {
+    let entry = prepare(RegistryEntry::new(value).at(Location::caller()));
+    store_entry(api, TypeId::of::<T>(), entry, false)
+}
+
+/// Store `entry` under `type_id` in `api`, unless the registered value is final
+/// or protected, or the registry is append-only.
+///
+/// The type-erased core of [`store`], also used for entries taken from the
+/// history or a timeline. Emits the register events and notifies listeners and
+/// waiters after the storage lock is released. A `rollback` is flagged in the
+/// events and does not add the value it replaces to the type's history.
+///
+/// A rejected entry emits `RegisterRejected` and returns the error; the
+/// `register*` methods returning `()` discard it, leaving the event as the only
+/// report.
+pub(crate) fn store_entry<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    entry: RegistryEntry,
+    rollback: bool,
+) -> Result<(), RegistryError> {
+    let type_name = entry.type_name();
+    let location = entry.registered_at();
+    let meta = || location.map_or_else(EventMeta::default, EventMeta::at);
+    api.core_emit(&RegistryEvent::Register {
+        type_name,
+        meta: meta(),
+    });
+
+    let started = Instant::now();
+
+    let registered = entry.value().clone();
+    let (previous, generation, copy) = {
+        let mut map = api.core_storage().unwrap_or_else(|p| p.into_inner());
+        if let Some(err) = rejection(api, map.get(&type_id), type_name) {
+            drop(map);
+            api.core_emit(&RegistryEvent::RegisterRejected {
+                type_name,
+                meta: meta(),
+            });
+            return Err(err);
+        }
+        let entry = entry.succeeding(map.get(&type_id));
+        let generation = entry.generation();
+        let copy = (api.core_state().timeline.is_recording()
+            || api.core_state().mirrors.is_active())
+        .then(|| entry.clone());
+        (map.insert(type_id, entry), generation, copy)
+    };
+    let replaced = previous.is_some();
+    let previous = match previous {
+        Some(entry) if !rollback => api.core_state().history.record(type_id, entry),
+        previous => previous,
+    };
+
+    api.core_state().timeline.record(|| {
+        let op = match (replaced, rollback) {
+            (_, true) => TimelineOp::Revert,
+            (true, false) => TimelineOp::Replace,
+            (false, false) => TimelineOp::Register,
+        };
+        TimelineRecord::new(op, type_id, type_name, generation, location, copy.clone())
+    });
+
+    api.core_state().lazy.discard(type_id);
+
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    api.core_state().lazy_async.discard(type_id);
+
+    #[cfg(feature = "tokio")]
+    api.core_state().watchers.publish(type_id, &registered);
+
+    api.core_state()
+        .change_listeners
+        .notify(type_id, &registered);
+
+    api.core_state().registration_waiters.notify(type_id);
+
+    let mut completed = EventMeta::timed(started);
+    if let Some(location) = location {
+        completed = completed.with_location(location);
+    }
+    api.core_emit(&RegistryEvent::RegisterCompleted {
+        type_name,
+        replaced,
+        rollback,
+        meta: completed,
+    });
+
+    if let Some(copy) = &copy {
+        api.core_state().mirrors.store(type_id, copy);
+    }
+
+    api.core_persist();
+    // The replaced value is dropped last, with no lock held
+    drop(previous);
+    Ok(())
+}
+
+/// Why `existing`, the entry registered under a type, can't be replaced in `api`.
+fn rejection<R: RegistryCore + ?Sized>(
+    api: &R,
+    existing: Option<&RegistryEntry>,
+    type_name: &'static str,
+) -> Option<RegistryError> {
+    match existing {
+        Some(entry) if entry.is_final() => Some(RegistryError::FinalType { type_name }),
+        Some(entry) if entry.is_protected() => Some(RegistryError::ProtectedType { type_name }),
+        Some(_) if api.core_config().registration == RegistrationMode::AppendOnly => {
+            Some(RegistryError::AlreadyRegistered { type_name })
+        }
+        _ => None,
+    }
+}
+
+/// Install `factory` as the pending provider of `T` in `api`, removing the
+/// registered value of `T`.
+///
+/// Rejected like [`store_entry`] if that value is final or protected, or the
+/// registry is append-only. The caller's location is reported as the built
+/// value's registration site.
+#[track_caller]
+pub(crate) fn store_lazy<R: RegistryCore + ?Sized, T: Send + Sync + 'static>(
+    api: &R,
+    factory: impl FnOnce() -> T + Send + 'static,
+) {
+    let type_id = TypeId::of::<T>();
+    let type_name = std::any::type_name::<T>();
+
+    let previous = {
+        let mut map = api.core_storage().unwrap_or_else(|p| p.into_inner());
+        if rejection(api, map.get(&type_id), type_name).is_some() {
+            drop(map);
+            api.core_emit(&RegistryEvent::RegisterRejected {
+                type_name,
+                meta: EventMeta::default(),
+            });
+            return;
+        }
+        // Installed under the storage lock, so no registration slips in between
+        api.core_state()
+            .lazy
+            .insert::<T>(factory, Location::caller());
+        map.remove(&type_id)
+    };
+
+    if let Some(previous) = previous {
+        removed(api, type_id, type_name, &previous, None);
+    }
+}
+
+/// Mark the entry under `type_id` in `api` as protected.
+pub(crate) fn protect_entry<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+) -> Result<(), RegistryError> {
+    let mut map = api.core_storage().unwrap_or_else(|p| p.into_inner());
+    match map.get_mut(&type_id) {
+        Some(entry) => {
+            entry.protect();
+            Ok(())
+        }
+        None => Err(RegistryError::TypeNotFound {
+            type_name,
+            location: None,
+            suggestions: Vec::new(),
+        }),
+    }
+}
+
+/// Remove the entry under `type_id` from `api`, unless it is final or protected.
+///
+/// `location` is the removing call, recorded in the timeline.
+pub(crate) fn remove_entry<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+    location: Option<&'static Location<'static>>,
+) -> Result<RegistryEntry, RegistryError> {
+    remove_entry_unless(api, type_id, type_name, location, |_| None)
+}
+
+/// [`remove_entry`], additionally refused if `refuse` returns an error for
+/// the entry; `refuse` runs under the storage lock.
+pub(crate) fn remove_entry_unless<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+    location: Option<&'static Location<'static>>,
+    refuse: impl FnOnce(&RegistryEntry) -> Option<RegistryError>,
+) -> Result<RegistryEntry, RegistryError> {
+    let entry = {
+        let mut map = api.core_storage().unwrap_or_else(|p| p.into_inner());
+        match map.get(&type_id) {
+            Some(entry) if entry.is_final() => {
+                return Err(RegistryError::FinalType { type_name });
+            }
+            Some(entry) if entry.is_protected() => {
+                return Err(RegistryError::ProtectedType { type_name });
+            }
+            Some(entry) => {
+                if let Some(err) = refuse(entry) {
+                    return Err(err);
+                }
+                map.remove(&type_id)
+            }
+            None => None,
+        }
+    };
+
+    api.core_state().lazy.discard(type_id);
+
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    api.core_state().lazy_async.discard(type_id);
+
+    let entry = entry.ok_or(RegistryError::TypeNotFound {
+        type_name,
+        location: None,
+        suggestions: Vec::new(),
+    })?;
+    removed(api, type_id, type_name, &entry, location);
+
+    Ok(entry)
+}
+
+/// Record the removal of `entry` from `api`: timeline, `Remove` event, mirrors
+/// and the file backend.
+fn removed<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+    entry: &RegistryEntry,
+    location: Option<&'static Location<'static>>,
+) {
+    api.core_state().timeline.record(|| {
+        TimelineRecord::new(
+            TimelineOp::Remove,
+            type_id,
+            type_name,
+            entry.generation(),
+            location,
+            None,
+        )
+    });
+    api.core_emit(&RegistryEvent::Remove {
+        type_name,
+        meta: EventMeta::default(),
+    });
+    api.core_state().mirrors.remove(type_id, type_name);
+
+    api.core_persist();
+}
+
+/// Look up the value stored under `type_id` in `api`, running its lazy provider
+/// if one is pending.
+///
+/// The type-erased core of [`RegistryApi::get`]. Emits the `Get` event and
+/// counts the lookup; a miss reports `location` and similar registered types.
+pub(crate) fn get_entry<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+    location: Option<&'static Location<'static>>,
+) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+    let started = Instant::now();
+    let track_access = api
+        .core_state()
+        .access_tracking
+        .load(std::sync::atomic::Ordering::Relaxed);
+    let mut map = lock_for_lookup(api)?;
+
+    let any_arc_opt = map.get_mut(&type_id).map(|e| {
+        if track_access {
+            e.touch();
+        }
+        e.value().clone()
+    });
+
+    drop(map);
+
+    let result = any_arc_opt
+        .or_else(|| crate::lazy::resolve_erased(api, type_id))
+        .ok_or_else(|| RegistryError::TypeNotFound {
+            type_name,
+            location,
+            suggestions: crate::suggest::suggestions(
+                type_name,
+                api.core_storage()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .values()
+                    .map(RegistryEntry::type_name),
+            ),
+        });
+
+    api.core_emit(&RegistryEvent::Get {
+        type_name,
+        found: result.is_ok(),
+        meta: EventMeta::timed(started),
+    });
+
+    #[cfg(feature = "stats")]
+    api.core_state()
+        .stats
+        .record(type_id, type_name, result.is_ok());
+
+    result
+}
+
+/// Whether a value or a pending lazy provider is registered under `type_id`.
+///
+/// The type-erased core of [`RegistryApi::contains`]; emits the `Contains` event.
+pub(crate) fn contains_entry<R: RegistryCore + ?Sized>(
+    api: &R,
+    type_id: TypeId,
+    type_name: &'static str,
+) -> Result<bool, RegistryError> {
+    let found =
+        lock_for_lookup(api)?.contains_key(&type_id) || api.core_state().lazy.contains(type_id);
+
+    api.core_emit(&RegistryEvent::Contains {
+        type_name,
+        found,
+        meta: EventMeta::default(),
+    });
+
+    Ok(found)
+}
+
+/// Remove every entry from `api` but the protected ones, or all of them if
+/// `force` is set, recovering a poisoned storage lock.
+///
+/// The single teardown behind [`clear_all`](RegistryApi::clear_all),
+/// [`clear_with_leak_report`](RegistryApi::clear_with_leak_report) and replayed
+/// `Clear` records: emits the `Clear` event, drops scoped values and pending
+/// lazy providers, records the timeline and saves the file backend. `inspect`
+/// sees the removed entries before they are dropped with no lock held; each is
+/// then announced with a `Remove` event. Returns their type names, sorted.
+pub(crate) fn clear_entries<R: RegistryApi + ?Sized>(
+    api: &R,
+    force: bool,
+    inspect: impl FnOnce(&[RegistryEntry]),
+) -> Vec<&'static str> {
+    api.emit_event(&RegistryEvent::Clear {
+        meta: EventMeta::default(),
+    });
+
+    let mut cleared: Vec<RegistryEntry> = {
+        let mut registry = lock_storage::<R>().unwrap_or_else(|p| p.into_inner());
+        let (kept, cleared): (Vec<_>, Vec<_>) = registry
+            .drain()
+            .partition(|(_, entry)| !force && entry.is_protected());
+        registry.extend(kept);
+        cleared.into_iter().map(|(_, entry)| entry).collect()
+    };
+    cleared.extend(R::state().scoped.drain());
+    R::state().lazy.clear();
+
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    R::state().lazy_async.clear();
+
+    R::state().timeline.record(TimelineRecord::clear);
+
+    #[cfg(feature = "file-backend")]
+    crate::file_backend::save(api);
+
+    inspect(&cleared);
+    let mut removed: Vec<&'static str> = cleared.iter().map(RegistryEntry::type_name).collect();
+    drop(cleared);
+    removed.sort_unstable();
+    for type_name in &removed {
+        api.emit_event(&RegistryEvent::Remove {
+            type_name,
+            meta: EventMeta::default(),
+        });
+    }
+    removed
+}
+
+/// The entries of `R`, copied out so they are hashed or compared with no lock held.
+fn copied_entries<R: RegistryApi + ?Sized>() -> Vec<(TypeId, RegistryEntry)> {
+    lock_storage::<R>()
+        .unwrap_or_else(|p| p.into_inner())
+        .iter()
+        .map(|(type_id, entry)| (*type_id, entry.clone()))
+        .collect()
+}
+
+/// Look up `T` for a permit that has just been acquired, releasing it if the lookup fails.
+fn permit_for<R: RegistryApi + ?Sized, T: Send + Sync + 'static>(
+    api: &R,
+    semaphore: Option<Arc<Semaphore>>,
+) -> Result<Permit<T>, RegistryError> {
+    match api.get::<T>() {
+        Ok(value) => Ok(Permit::new(value, semaphore)),
+        Err(err) => {
+            if let Some(semaphore) = semaphore {
+                semaphore.release();
+            }
+            Err(err)
+        }
+    }
+}
+
 /// Core trait defining registry behavior.
 ///
 /// Provides default implementations for all registry operations, requiring only
-/// two accessor methods (`storage` and `trace`) to be implemented by the implementor.
+/// three accessor methods (`storage`, `trace` and `state`) to be implemented by the implementor.
 ///
 /// The registry stores singleton instances indexed by their type (`TypeId`).
 /// Each type can have at most one instance stored at any given time.
@@ -35,6 +657,24 @@ pub trait RegistryApi {
     /// This method must be implemented to provide access to the registry's trace callback.
     fn trace() -> &'static TraceCallback;
 
+    /// Name of the registry, reported in the `registry` field of every event.
+    ///
+    /// `define_registry!` returns the module name; defaults to the implementing
+    /// type's name.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Static configuration of the registry.
+    ///
+    /// `define_registry!(name, { ... })` returns the configured options; defaults
+    /// to [`RegistryConfig::new`]. Implementors overriding it should also size
+    /// their storage map with `capacity`.
+    fn config() -> &'static RegistryConfig {
+        const DEFAULT: RegistryConfig = RegistryConfig::new();
+        &DEFAULT
+    }
+
     /// Set a tracing callback for registry operations.
     ///
     /// The callback will be invoked for every registry operation (register, get, contains).
@@ -51,7 +691,8 @@ pub trait RegistryApi {
     /// as this will cause a deadlock. The callback is invoked while holding
     /// the trace lock.
     fn set_trace_callback(&self, callback: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
-        let mut guard = Self::trace().lock().unwrap_or_else(|p| p.into_inner());
+        let mut guard = lock_trace::<Self>().unwrap_or_else(|p| p.into_inner());
+        Self::state().callback_panics.reset();
         *guard = Some(Arc::new(callback));
     }
 
@@ -64,10 +705,70 @@ pub trait RegistryApi {
     ///
     /// If the trace lock is poisoned, this method automatically recovers.
     fn clear_trace_callback(&self) {
-        let mut guard = Self::trace().lock().unwrap_or_else(|p| p.into_inner());
+        let mut guard = lock_trace::<Self>().unwrap_or_else(|p| p.into_inner());
         *guard = None;
     }
 
+    /// Sample lookup events (`Get`, `Contains`) to keep tracing affordable.
+    ///
+    /// Applies to the trace callback and event subscribers alike. Mutation events
+    /// are always emitted. Replaces the previous configuration and restarts its
+    /// counters; [`EventSampling::all`] turns sampling off again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, EventSampling};
+    ///
+    /// define_registry!(services);
+    ///
+    /// services::set_trace_callback(|event| println!("{event}"));
+    /// services::set_event_sampling(EventSampling::one_in(100));
+    ///
+    /// services::register(42u32); // always traced
+    /// for _ in 0..1000 {
+    ///     let _ = services::get::<u32>(); // traced 10 times
+    /// }
+    /// ```
+    fn set_event_sampling(&self, sampling: EventSampling) {
+        Self::state().event_sampler.configure(sampling);
+    }
+
+    /// The current lookup event sampling configuration.
+    fn event_sampling(&self) -> EventSampling {
+        Self::state().event_sampler.config()
+    }
+
+    /// Emit only events at `level` or above, see [`RegistryEvent::level`].
+    ///
+    /// The default, [`EventLevel::Debug`], emits everything. Applies to the trace
+    /// callback and event subscribers alike.
+    fn set_trace_level(&self, level: EventLevel) {
+        Self::state().trace_level.set(level);
+    }
+
+    /// The minimum level of emitted events.
+    fn trace_level(&self) -> EventLevel {
+        Self::state().trace_level.get()
+    }
+
+    /// Record the emitting thread's id and name in every event's [`EventMeta`].
+    ///
+    /// Off by default, since looking up the thread costs a little on every event.
+    /// Useful to find out which thread performed an unexpected late registration.
+    fn set_event_threads(&self, enabled: bool) {
+        Self::state()
+            .event_threads
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Whether events record the emitting thread.
+    fn event_threads(&self) -> bool {
+        Self::state()
+            .event_threads
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
     /// Convenience wrapper to emit a registry event using the current callback.
     ///
     /// If a trace callback is set, this method will invoke it with the provided event.
@@ -78,180 +779,2385 @@ pub trait RegistryApi {
     ///
     /// # Panics
     ///
-    /// If the callback itself panics, the panic will propagate to the caller.
-    /// The registry lock is not held during callback execution, so this won't
-    /// poison the registry storage.
+    /// If the callback itself panics, the panic will propagate to the caller,
+    /// unless [`set_callback_panic_policy`](RegistryApi::set_callback_panic_policy)
+    /// says otherwise. The registry lock is not held during callback execution,
+    /// so this won't poison the registry storage.
     fn emit_event(&self, event: &RegistryEvent) {
-        let callback = {
-            let guard = Self::trace().lock().unwrap_or_else(|p| p.into_inner());
-            guard.as_ref().cloned()
-        }; // lock released here, before the callback is invoked
-        if let Some(cb) = callback {
-            cb(event);
+        emit_via(
+            Self::name(),
+            Self::config(),
+            Self::state(),
+            lock_trace::<Self>,
+            event,
+        );
+    }
+
+    /// Decide what happens when the trace callback panics.
+    ///
+    /// The default, [`CallbackPanicPolicy::Propagate`], lets the panic unwind
+    /// into the registry call that emitted the event. `Swallow` catches it and
+    /// drops the event; `Disable(n)` also removes the callback after its `n`-th
+    /// panic. The panic hook still reports each panic.
+    fn set_callback_panic_policy(&self, policy: CallbackPanicPolicy) {
+        Self::state().callback_panics.set(policy);
+    }
+
+    /// The current trace callback panic policy.
+    fn callback_panic_policy(&self) -> CallbackPanicPolicy {
+        Self::state().callback_panics.get()
+    }
+
+    /// Forward every event of this registry to the registry `P`.
+    ///
+    /// Events that pass this registry's trace level and sampling reach `P`'s
+    /// trace callback and event subscribers, keeping this registry's name in
+    /// `event.registry()`, and travel on if `P` forwards too. A subscriber set
+    /// on the root of a registry tree thus observes the whole tree. Replaces any
+    /// previous parent.
+    ///
+    /// # Panics
+    ///
+    /// If `P` already forwards, directly or through other registries, to this
+    /// registry.
+    fn forward_events_to<P: RegistryApi>(&self) {
+        Self::state()
+            .event_parent
+            .set(Self::state(), parent_link::<P>());
+    }
+
+    /// Stop forwarding events to the parent registry.
+    fn stop_forwarding_events(&self) {
+        Self::state().event_parent.clear();
+    }
+
+    /// A namespaced view of this registry.
+    ///
+    /// Values registered through the view are keyed by `(name, TypeId)`, so
+    /// each scope is isolated from the registry's own values and from other
+    /// scopes, while events still go through this registry's trace pipeline.
+    /// Views with the same name share their values; `clear` empties every scope.
+    fn scope(&self, name: &'static str) -> Scope<Self>
+    where
+        Self: Clone,
+    {
+        Scope::new(self.clone(), name)
+    }
+
+    /// A cloneable [`RegistryHandle`] to this registry.
+    ///
+    /// Libraries accept the handle instead of naming the module generated by
+    /// an application's `define_registry!`.
+    fn handle(&self) -> RegistryHandle
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        RegistryHandle::new(self.clone())
+    }
+
+    /// A cloneable [`ReadOnlyRegistry`] view of this registry, which can look
+    /// values up but not register, remove or clear them.
+    fn read_only(&self) -> ReadOnlyRegistry
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        self.handle().read_only()
+    }
+
+    /// Copy every entry of this registry into `target` and keep repeating
+    /// registrations and removals there.
+    ///
+    /// The target shares the values (the same `Arc`s) and their metadata.
+    /// Mutations made directly on the target do not flow back, and a final
+    /// value in the target is kept. A registry may mirror into several
+    /// targets, and targets may mirror on.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(app);
+    /// define_registry!(diagnostics);
+    ///
+    /// app::register(8080u16);
+    /// app::mirror_into(diagnostics::Api);
+    /// app::register("ready");
+    ///
+    /// assert_eq!(*diagnostics::get::<u16>().unwrap(), 8080);
+    /// assert_eq!(*diagnostics::get::<&str>().unwrap(), "ready");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `target` already mirrors, directly or through other registries, into
+    /// this registry.
+    fn mirror_into<P: RegistryApi + Send + Sync + 'static>(&self, target: P) {
+        let target = Arc::new(crate::mirror::Target(target));
+        Self::state().mirrors.add(Self::state(), target.clone());
+
+        let entries: Vec<(TypeId, RegistryEntry)> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .iter()
+            .map(|(type_id, entry)| (*type_id, entry.clone()))
+            .collect();
+        for (type_id, entry) in entries {
+            crate::mirror::MirrorTarget::store(&*target, type_id, entry);
         }
     }
 
+    /// Stop mirroring into every target of [`mirror_into`](RegistryApi::mirror_into).
+    fn stop_mirroring(&self) {
+        Self::state().mirrors.clear();
+    }
+
+    /// Import every entry of `other` into this registry.
+    ///
+    /// Entries keep their `Arc`s and metadata, so both registries share the
+    /// values. `policy` decides about types registered in both: keep this
+    /// registry's value, replace it (final values are kept regardless), or
+    /// import nothing. Returns the number of entries imported.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, ConflictPolicy};
+    ///
+    /// define_registry!(app);
+    /// define_registry!(plugin);
+    ///
+    /// app::register(8080u16);
+    /// plugin::register(9090u16);
+    /// plugin::register("plugin ready");
+    ///
+    /// assert_eq!(app::merge_from(&plugin::Api, ConflictPolicy::Skip), Ok(1));
+    /// assert_eq!(*app::get::<u16>().unwrap(), 8080);
+    /// assert_eq!(*app::get::<&str>().unwrap(), "plugin ready");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::MergeConflict` listing the types registered in both, with
+    /// `ConflictPolicy::Error`.
+    fn merge_from<O: RegistryApi>(
+        &self,
+        other: &O,
+        policy: ConflictPolicy,
+    ) -> Result<usize, RegistryError> {
+        crate::transfer::merge(self, other, policy)
+    }
+
+    /// Forward events at `level` or above to the `log` facade.
+    ///
+    /// Records use target `singleton_registry` and the message `[registry] event`;
+    /// `Debug` events log at `debug`, `Info` and `Notice` at `info`, `Warn` at
+    /// `warn`. Events below the trace level are never forwarded.
+    #[cfg(feature = "log")]
+    fn enable_log_bridge(&self, level: EventLevel) {
+        Self::state().log_bridge.enable(level);
+    }
+
+    /// Stop forwarding events to the `log` facade.
+    #[cfg(feature = "log")]
+    fn disable_log_bridge(&self) {
+        Self::state().log_bridge.disable();
+    }
+
+    /// Append every mutation of this registry to the audit log at `path`.
+    ///
+    /// Registrations, replacements, removals and clears are written as JSON
+    /// lines, regardless of the trace level; the file is created if missing and
+    /// only ever appended to. Records are not fsync'ed, see
+    /// [`enable_audit_log_with`](RegistryApi::enable_audit_log_with).
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file cannot be opened.
+    #[cfg(feature = "audit")]
+    fn enable_audit_log(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        self.enable_audit_log_with(path, crate::FsyncPolicy::default())
+    }
+
+    /// Like [`enable_audit_log`](RegistryApi::enable_audit_log), with an explicit fsync policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error if the file cannot be opened.
+    #[cfg(feature = "audit")]
+    fn enable_audit_log_with(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        policy: crate::FsyncPolicy,
+    ) -> std::io::Result<()> {
+        Self::state().audit.enable(path.as_ref(), policy)
+    }
+
+    /// Stop writing the audit log, syncing records the policy left unsynced.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error of the final sync.
+    #[cfg(feature = "audit")]
+    fn disable_audit_log(&self) -> std::io::Result<()> {
+        Self::state().audit.disable()
+    }
+
+    /// Receive every registry event through a bounded channel.
+    ///
+    /// The receiver gets the same events as the trace callback, so observability
+    /// pipelines can consume them in a task. Emitting never blocks: events that
+    /// don't fit into the `capacity`-sized buffer are dropped for this subscriber.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[cfg(feature = "tokio")]
+    fn subscribe_events(&self, capacity: usize) -> tokio::sync::mpsc::Receiver<RegistryEvent> {
+        Self::state().event_subscribers.subscribe(capacity)
+    }
+
     // -------------------------------------------------------------------------------------------------
     // Registry
     // -------------------------------------------------------------------------------------------------
 
     /// Access the storage static.
     ///
-    /// This method must be implemented to provide access to the registry's storage.
-    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>;
-
-    /// Register a value in the registry.
+    /// This method must be implemented to provide access to the registry's storage.
+    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>>;
+
+    /// Access the auxiliary state static.
+    ///
+    /// This method must be implemented to provide access to the registry's
+    /// [`RegistryState`]. Each registry needs its own instance.
+    fn state() -> &'static RegistryState;
+
+    /// Hit and miss counts of `get` lookups of `T`, zero if it was never looked up.
+    ///
+    /// Tells whether anything actually uses a registered service.
+    #[cfg(feature = "stats")]
+    fn stats<T: 'static>(&self) -> crate::TypeStats {
+        Self::state().stats.get::<T>()
+    }
+
+    /// Lookup counters of every type looked up so far, sorted by type name.
+    #[cfg(feature = "stats")]
+    fn stats_all(&self) -> Vec<crate::TypeStats> {
+        Self::state().stats.all()
+    }
+
+    /// Reset all lookup counters.
+    #[cfg(feature = "stats")]
+    fn reset_stats(&self) {
+        Self::state().stats.reset();
+    }
+
+    /// Snapshot the lock contention counters of this registry.
+    ///
+    /// Reports how many times the storage and trace locks were acquired, how many
+    /// of those acquisitions had to wait, and the cumulative wait time. Counters
+    /// are cumulative for the lifetime of the process.
+    #[cfg(feature = "contention-stats")]
+    fn contention_stats(&self) -> crate::ContentionStats {
+        crate::ContentionStats {
+            storage: Self::state().storage_lock.snapshot(),
+            trace: Self::state().trace_lock.snapshot(),
+        }
+    }
+
+    /// Register a value in the registry.
+    ///
+    /// Takes ownership of the value and wraps it in an `Arc` automatically.
+    /// If a value of the same type is already registered, it will be replaced.
+    ///
+    /// # Design Note
+    ///
+    /// This method does not return a `Result` because registration is designed
+    /// for the "write-once" pattern during application startup (and rarely at runtime for rewrite). Lock poisoning
+    /// is automatically recovered. If registration must succeed, ensure your
+    /// application initialization doesn't panic while holding registry locks.
+    #[track_caller]
+    fn register<T: Send + Sync + 'static>(&self, value: T) {
+        self.register_arc(Arc::new(value));
+    }
+
+    /// Register an Arc-wrapped value in the registry.
+    ///
+    /// More efficient than `register` when you already have an `Arc`,
+    /// as it avoids creating an additional reference count.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    /// This is safe because the insert operation is idempotent.
+    #[track_caller]
+    fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        let _ = store(self, value, |e| e);
+    }
+
+    /// Register a value unless the registered value of its type can't be replaced.
+    ///
+    /// Behaves like [`register`](RegistryApi::register) but reports the rejection
+    /// instead of only emitting a `RegisterRejected` event.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::FinalType` if `T` was registered with
+    ///   [`register_final`](RegistryApi::register_final)
+    /// - `RegistryError::ProtectedType` if the value of `T` is
+    ///   [protected](RegistryApi::protect)
+    /// - `RegistryError::AlreadyRegistered` if `T` is registered and the registry
+    ///   is append-only
+    #[track_caller]
+    fn try_register<T: Send + Sync + 'static>(&self, value: T) -> Result<(), RegistryError> {
+        store(self, Arc::new(value), |e| e)
+    }
+
+    /// Register a value together with descriptive [`Metadata`].
+    ///
+    /// Behaves like [`register`](RegistryApi::register); the metadata is readable
+    /// through [`metadata`](RegistryApi::metadata) until the value is replaced.
+    #[track_caller]
+    fn register_with_meta<T: Send + Sync + 'static>(&self, value: T, metadata: Metadata) {
+        let _ = store(self, Arc::new(value), |e| e.with_metadata(metadata));
+    }
+
+    /// Register a value whose `Debug` output is included in [`dump`](RegistryApi::dump).
+    ///
+    /// Behaves like [`register`](RegistryApi::register); the entry additionally
+    /// keeps a formatter for `T`, so diagnostics can show which configuration was
+    /// actually loaded.
+    #[track_caller]
+    fn register_debuggable<T: fmt::Debug + Send + Sync + 'static>(&self, value: T) {
+        let _ = store(self, Arc::new(value), RegistryEntry::with_debug::<T>);
+    }
+
+    /// Register a value built at runtime, for code-generation and scripting
+    /// layers that only know its `TypeId`.
+    ///
+    /// `value` must be of the type identified by `type_id`; typed lookups of
+    /// that type then find it through the usual downcast-checked path. The
+    /// entry is named `<boxed>` in events and diagnostics, since the concrete
+    /// type's name is not known.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::TypeMismatch` if `value` is not of the type `type_id`
+    /// - `RegistryError::FinalType` / `ProtectedType` / `AlreadyRegistered` if
+    ///   the type can't be registered again
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    /// use std::any::{Any, TypeId};
+    ///
+    /// define_registry!(app);
+    ///
+    /// let (type_id, value): (TypeId, Box<dyn Any + Send + Sync>) =
+    ///     (TypeId::of::<u16>(), Box::new(8080u16));
+    /// app::register_boxed(type_id, value).unwrap();
+    ///
+    /// assert_eq!(*app::get::<u16>().unwrap(), 8080);
+    /// assert!(app::register_boxed(TypeId::of::<u32>(), Box::new(1u8)).is_err());
+    /// ```
+    #[track_caller]
+    fn register_boxed(
+        &self,
+        type_id: TypeId,
+        value: Box<dyn Any + Send + Sync>,
+    ) -> Result<(), RegistryError> {
+        let entry = RegistryEntry::boxed(type_id, value)?.at(Location::caller());
+        store_entry(self, type_id, entry, false)
+    }
+
+    /// Register a value whose `PartialEq` is used by
+    /// [`contents_equal`](RegistryApi::contents_equal).
+    ///
+    /// Behaves like [`register`](RegistryApi::register); the entry additionally
+    /// keeps a comparator for `T`. Registering `T` again with plain `register`
+    /// drops it.
+    #[track_caller]
+    fn register_comparable<T: PartialEq + Send + Sync + 'static>(&self, value: T) {
+        let _ = store(self, Arc::new(value), RegistryEntry::with_value_eq::<T>);
+    }
+
+    /// Register a value that is included in [`export_json`](RegistryApi::export_json).
+    ///
+    /// Behaves like [`register`](RegistryApi::register); the entry additionally
+    /// keeps serde functions for `T`. Registering `T` again with plain `register`
+    /// drops them.
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    fn register_serializable<T>(&self, value: T)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let _ = store(self, Arc::new(value), RegistryEntry::with_serde::<T>);
+    }
+
+    /// Register a value that can never be replaced.
+    ///
+    /// Gives `OnceLock`-like guarantees for a single type (e.g. cryptographic keys):
+    /// every later registration of `T`, final or not, is rejected, while other types
+    /// keep override semantics. Only `clear()` removes a final value.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::FinalType` if `T` is already registered as final
+    /// - `RegistryError::ProtectedType` if the value of `T` is
+    ///   [protected](RegistryApi::protect)
+    /// - `RegistryError::AlreadyRegistered` if `T` is registered and the registry
+    ///   is append-only
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, RegistryError};
+    ///
+    /// define_registry!(keys);
+    ///
+    /// struct SigningKey([u8; 4]);
+    ///
+    /// keys::register_final(SigningKey([1, 2, 3, 4])).unwrap();
+    ///
+    /// keys::register(SigningKey([0; 4])); // ignored
+    /// assert_eq!(keys::get::<SigningKey>().unwrap().0, [1, 2, 3, 4]);
+    /// assert!(matches!(
+    ///     keys::try_register(SigningKey([0; 4])),
+    ///     Err(RegistryError::FinalType { .. })
+    /// ));
+    /// ```
+    #[track_caller]
+    fn register_final<T: Send + Sync + 'static>(&self, value: T) -> Result<(), RegistryError> {
+        store(self, Arc::new(value), RegistryEntry::into_final)
+    }
+
+    /// Protect the registered value of `T` from being replaced, removed or cleared.
+    ///
+    /// Unlike [`register_final`](RegistryApi::register_final) this applies to a
+    /// value that is already registered, and the value also survives `clear()`;
+    /// only `clear_all(true)` drops it. Critical singletons such as the panic
+    /// handler configuration can't be swapped out silently afterwards.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::TypeNotFound` if `T` is not registered
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, RegistryError};
+    ///
+    /// define_registry!(app);
+    ///
+    /// struct PanicConfig { abort: bool }
+    ///
+    /// app::register(PanicConfig { abort: true });
+    /// app::protect::<PanicConfig>().unwrap();
+    ///
+    /// assert!(matches!(
+    ///     app::try_register(PanicConfig { abort: false }),
+    ///     Err(RegistryError::ProtectedType { .. })
+    /// ));
+    /// assert!(app::get::<PanicConfig>().unwrap().abort);
+    /// ```
+    fn protect<T: Send + Sync + 'static>(&self) -> Result<(), RegistryError> {
+        protect_entry(self, TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// Build `T` from the environment variables starting with `prefix` and
+    /// register it.
+    ///
+    /// Fields map to variables envy-style: `port` is read from `APP_PORT` for
+    /// the prefix `APP_`. See the `env` feature in the crate docs for the
+    /// supported field types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(app);
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    ///     log_level: Option<String>,
+    /// }
+    ///
+    /// std::env::set_var("MYAPP_PORT", "8080");
+    /// app::register_from_env::<Config>("MYAPP_").unwrap();
+    ///
+    /// assert_eq!(app::get::<Config>().unwrap().port, 8080);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::Custom` if a variable cannot be parsed into its field or a
+    ///   required one is missing; nothing is registered then
+    /// - any error of [`try_register`](RegistryApi::try_register) if the value
+    ///   read is rejected
+    #[cfg(feature = "env")]
+    #[track_caller]
+    fn register_from_env<T>(&self, prefix: &str) -> Result<(), RegistryError>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let value = crate::env_config::from_env::<T>(prefix).map_err(|err| {
+            RegistryError::from_source(
+                format!(
+                    "reading {} from {}* environment variables",
+                    std::any::type_name::<T>(),
+                    prefix
+                ),
+                err,
+            )
+        })?;
+        self.try_register(value)
+    }
+
+    /// Deserialize the configuration file at `path` into `T` and register it.
+    ///
+    /// The format follows the extension: `.toml` (feature `toml`) or `.json`
+    /// (feature `json`). Use [`ConfigManifest`](crate::ConfigManifest) to load
+    /// several sections of one file into separate types.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::Custom` if the file cannot be read, has an unsupported
+    ///   extension, or does not deserialize into `T`; nothing is registered then
+    /// - any error of [`try_register`](RegistryApi::try_register) if the value
+    ///   read is rejected
+    #[cfg(any(feature = "toml", feature = "json"))]
+    #[track_caller]
+    fn load_config_file<T>(&self, path: impl AsRef<std::path::Path>) -> Result<(), RegistryError>
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let value = crate::config_file::load::<T>(path.as_ref())?;
+        self.try_register(value)
+    }
+
+    /// Register a service whose async `shutdown()` runs on `shutdown_with_timeout`.
+    ///
+    /// Registers `value` like [`register`](RegistryApi::register) and enrolls `T`
+    /// in graceful shutdown. Replacing the value later keeps the enrollment; the
+    /// value registered at shutdown time is the one torn down.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    fn register_shutdown<T: AsyncShutdown>(&self, value: T) {
+        self.register(value);
+        Self::state().shutdown_hooks.add::<Self, T>();
+    }
+
+    /// Call every enrolled service's `shutdown()` concurrently.
+    ///
+    /// Each hook is bounded by `timeout`; the report lists which services
+    /// completed, timed out or panicked. Services that are no longer registered
+    /// are skipped. Must be called within a tokio runtime with time enabled.
+    #[cfg(feature = "tokio")]
+    fn shutdown_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> impl std::future::Future<Output = ShutdownReport> + Send {
+        Self::state().shutdown_hooks.run(timeout)
+    }
+
+    /// Register a service whose async `check()` is probed by `health_report_async`.
+    ///
+    /// Registers `value` like [`register`](RegistryApi::register) and enrolls `T`
+    /// in health reporting; the value registered at probe time is the one checked.
+    #[cfg(feature = "tokio")]
+    #[track_caller]
+    fn register_health_checked<T: AsyncHealthCheck>(&self, value: T) {
+        self.register(value);
+        Self::state().health_probes.add::<Self, T>();
+    }
+
+    /// Probe every enrolled service concurrently.
+    ///
+    /// Each probe is bounded by `timeout`. Services that are no longer registered
+    /// are skipped. Must be called within a tokio runtime with time enabled.
+    #[cfg(feature = "tokio")]
+    fn health_report_async(
+        &self,
+        timeout: Duration,
+    ) -> impl std::future::Future<Output = HealthReport> + Send {
+        Self::state().health_probes.run(timeout)
+    }
+
+    /// Retrieve the value of `T`, registering `factory()` first if it is absent.
+    ///
+    /// Racing callers for the same type are serialized by a per-type
+    /// initialization lock: exactly one factory runs, and every other caller
+    /// blocks until it finishes and then receives the winner's value. Important
+    /// when the factory has side effects. If the factory panics, the next caller
+    /// runs its own.
+    ///
+    /// The factory may use the registry, but must not call `get_or_register::<T>`
+    /// itself (that would deadlock).
+    ///
+    /// # Errors
+    ///
+    /// Any error of [`try_register`](RegistryApi::try_register) if the factory's
+    /// value is rejected and no value of `T` is registered to return instead,
+    /// so the caller never holds a value the registry doesn't.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// let first = services::get_or_register(|| "created".to_string()).unwrap();
+    /// let second = services::get_or_register(|| "never built".to_string()).unwrap();
+    /// assert_eq!(*second, "created");
+    /// ```
+    #[track_caller]
+    fn get_or_register<T: Send + Sync + 'static>(
+        &self,
+        factory: impl FnOnce() -> T,
+    ) -> Result<Arc<T>, RegistryError> {
+        if let Some(value) = self.try_get::<T>() {
+            return Ok(value);
+        }
+
+        let init_lock = Self::state().init_locks.for_type(TypeId::of::<T>());
+        let _init = init_lock.lock().unwrap_or_else(|p| p.into_inner());
+
+        // Another caller may have registered the value while this one waited
+        if let Some(value) = lookup::<Self, T>() {
+            return Ok(value);
+        }
+
+        let value = Arc::new(factory());
+        match store(self, value.clone(), |e| e) {
+            Ok(()) => Ok(value),
+            // Lost to a value registered meanwhile, e.g. by the factory; hand out
+            // the one that stays
+            Err(err) => lookup::<Self, T>().ok_or(err),
+        }
+    }
+
+    /// Register a factory for `T` that runs on first access.
+    ///
+    /// Lets expensive singletons be wired eagerly at startup but constructed only
+    /// if actually used. The first `get::<T>()` (or `get_cloned`, `try_get`) runs
+    /// the factory outside the storage lock and registers the result as usual,
+    /// emitting the register events; concurrent callers wait for it and share the
+    /// value. `contains::<T>()` reports `true` while the provider is pending.
+    ///
+    /// Registering a value of `T` explicitly discards a provider that has not run
+    /// yet. The factory may use the registry, but must not request `T` itself.
+    /// A value of `T` registered earlier is removed and replaced by the provider's;
+    /// if that value is final or protected, or the registry is append-only, the
+    /// provider is rejected instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// services::register_lazy(|| vec![0u8; 1024 * 1024]); // nothing allocated yet
+    ///
+    /// let buffer = services::get::<Vec<u8>>().unwrap(); // allocated here, once
+    /// assert_eq!(buffer.len(), 1024 * 1024);
+    /// ```
+    #[track_caller]
+    fn register_lazy<T: Send + Sync + 'static>(
+        &self,
+        factory: impl FnOnce() -> T + Send + 'static,
+    ) {
+        store_lazy(self, factory);
+    }
+
+    /// Register a provider that builds `T` from other registry entries.
+    ///
+    /// Nothing runs until [`resolve`](RegistryApi::resolve) asks for `T`. The
+    /// provider receives a [`ResolveCtx`] and requests its dependencies through
+    /// it, so providers can depend on each other and each is built once, in
+    /// dependency order. Registering a provider again replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// struct Pool(usize);
+    /// struct Repo(usize);
+    ///
+    /// services::register_provider(|_| Ok(Pool(4)));
+    /// services::register_provider(|ctx| Ok(Repo(ctx.resolve::<Pool>()?.0)));
+    ///
+    /// assert_eq!(services::resolve::<Repo>().unwrap().0, 4);
+    /// ```
+    fn register_provider<T: Send + Sync + 'static>(
+        &self,
+        provider: impl Fn(&ResolveCtx<Self>) -> Result<T, RegistryError> + Send + Sync + 'static,
+    ) where
+        Self: Sized + 'static,
+    {
+        Self::state()
+            .providers
+            .insert::<Self, T>(Provider::Sync(Box::new(provider)));
+    }
+
+    /// Retrieve `T`, building it and its dependencies from providers if needed.
+    ///
+    /// A registered value is returned as is. Otherwise the provider for `T` runs,
+    /// and its result is registered like any other value. Types without a
+    /// provider behave as with [`get`](RegistryApi::get). A provider that fails
+    /// leaves nothing registered, so a later call retries it.
+    ///
+    /// Concurrent resolutions of the same type may both run the provider; the
+    /// first value registered wins and is returned to both.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::DependencyCycle` if the providers depend on each other in a cycle
+    /// - `RegistryError::ProvidedAsync` if `T` or a dependency only has an async provider
+    /// - Any error returned by a provider
+    /// - Type `T` is neither registered nor provided
+    fn resolve<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError>
+    where
+        Self: Default + 'static,
+    {
+        crate::resolver::resolve_in::<Self, T>(&[])
+    }
+
+    /// Resolve a tuple of types like [`resolve`](RegistryApi::resolve), reporting
+    /// every missing one at once.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::MissingDependencies` listing every type, or dependency
+    ///   of a provider using [`ResolveCtx::resolve_all`], that is neither
+    ///   registered nor provided
+    /// - Any other error of the first type failing otherwise
+    fn resolve_all<D: Dependencies<Self>>(&self) -> Result<D::Output, RegistryError>
+    where
+        Self: Default + 'static,
+    {
+        D::resolve_all_in(&[])
+    }
+
+    /// Retrieve a value from the registry.
+    ///
+    /// Returns `Ok(Arc<T>)` if the type is found, running its lazy provider first
+    /// if one is pending (see [`register_lazy`](RegistryApi::register_lazy)).
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned, unless the registry's [`PoisonPolicy`] is `Recover`
+    #[track_caller]
+    fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        get_entry(self, TypeId::of::<T>(), type_name, Some(Location::caller()))?
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Retrieve a cloned value from the registry.
+    ///
+    /// Returns an owned value by cloning the value stored in the registry.
+    /// The type `T` must implement `Clone`.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch
+    #[track_caller]
+    fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        let arc = self.get::<T>()?;
+        Ok((*arc).clone())
+    }
+
+    /// Retrieve a cloned value from the registry, or `T::default()` if there is
+    /// none.
+    ///
+    /// For optional knobs where the caller just wants a value; a failed lookup,
+    /// including a poisoned lock, also yields the default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(app);
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct RetryPolicy {
+    ///     attempts: u32,
+    /// }
+    ///
+    /// assert_eq!(app::get_cloned_or_default::<RetryPolicy>().attempts, 0);
+    /// app::register(RetryPolicy { attempts: 3 });
+    /// assert_eq!(app::get_cloned_or_default::<RetryPolicy>().attempts, 3);
+    /// ```
+    fn get_cloned_or_default<T: Send + Sync + Clone + Default + 'static>(&self) -> T {
+        self.try_get::<T>()
+            .map_or_else(T::default, |value| (*value).clone())
+    }
+
+    /// Retrieve a value from the registry, panicking if it is unavailable.
+    ///
+    /// A replacement for `get().unwrap()`: the panic message names the requested
+    /// type, the registry, the calling location and every registered type.
+    ///
+    /// # Panics
+    ///
+    /// If [`get`](RegistryApi::get) fails, for any reason.
+    #[track_caller]
+    fn get_expect<T: Send + Sync + 'static>(&self) -> Arc<T> {
+        let err = match self.get::<T>() {
+            Ok(value) => return value,
+            // The location is reported once, up front
+            Err(RegistryError::TypeNotFound {
+                type_name,
+                suggestions,
+                ..
+            }) => RegistryError::TypeNotFound {
+                type_name,
+                location: None,
+                suggestions,
+            },
+            Err(err) => err,
+        };
+        let registered: Vec<&str> = self.describe().iter().map(|e| e.type_name).collect();
+        panic!(
+            "{}::get_expect::<{}>() at {}: {}\nregistered types: [{}]",
+            Self::name(),
+            std::any::type_name::<T>(),
+            Location::caller(),
+            err,
+            registered.join(", ")
+        )
+    }
+
+    /// Retrieve a value from the registry, returning `None` if not registered.
+    ///
+    /// The primary graceful-degradation path — equivalent to `get::<T>().ok()` but
+    /// matches the language-neutral API surface defined in the JigsawFlow spec.
+    /// Prefer this over `get` when absence is expected and not a programming error.
+    fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Register a value under the key type `K`, replacing any previous value of `K`.
+    ///
+    /// The entry is stored under `K`'s `TypeId` and named after `K`, so several
+    /// keys can hold values of the same type; see [`RegistryKey`].
+    #[track_caller]
+    fn register_key<K: RegistryKey>(&self, value: K::Value) {
+        let entry = RegistryEntry::new(Arc::new(value))
+            .at(Location::caller())
+            .keyed::<K>();
+        let _ = store_entry(self, TypeId::of::<K>(), entry, false);
+    }
+
+    /// Retrieve the value registered under the key type `K`.
+    ///
+    /// # Errors
+    ///
+    /// - No value is registered under `K`
+    /// - Registry lock is poisoned, unless the registry's [`PoisonPolicy`] is `Recover`
+    #[track_caller]
+    fn get_key<K: RegistryKey>(&self) -> Result<Arc<K::Value>, RegistryError> {
+        let type_name = std::any::type_name::<K>();
+        get_entry(self, TypeId::of::<K>(), type_name, Some(Location::caller()))?
+            .downcast::<K::Value>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Retrieve the value registered under the key type `K`, returning `None`
+    /// if there is none.
+    fn try_get_key<K: RegistryKey>(&self) -> Option<Arc<K::Value>> {
+        self.get_key::<K>().ok()
+    }
+
+    /// Check if a value is registered under the key type `K`.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned, unless the registry's [`PoisonPolicy`] is `Recover`
+    fn contains_key<K: RegistryKey>(&self) -> Result<bool, RegistryError> {
+        contains_entry(self, TypeId::of::<K>(), std::any::type_name::<K>())
+    }
+
+    /// Remove the value registered under the key type `K` and return it.
+    ///
+    /// # Errors
+    ///
+    /// - No value is registered under `K`
+    /// - The value was registered as final or is protected
+    #[track_caller]
+    fn remove_key<K: RegistryKey>(&self) -> Result<Arc<K::Value>, RegistryError> {
+        let type_name = std::any::type_name::<K>();
+        remove_entry(self, TypeId::of::<K>(), type_name, Some(Location::caller()))?
+            .value()
+            .clone()
+            .downcast::<K::Value>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Subscribe to the value of `T` and every later replacement.
+    ///
+    /// The returned receiver starts with the current value; each subsequent
+    /// registration of `T` is published to it. Lets hot-reload consumers react to
+    /// replacements (`rx.changed().await`) instead of re-calling `get()`.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered yet (there is no current value to start from)
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    #[cfg(feature = "tokio")]
+    fn subscribe<T: Send + Sync + 'static>(
+        &self,
+    ) -> Result<tokio::sync::watch::Receiver<Arc<T>>, RegistryError> {
+        let current = self.get::<T>()?;
+        Ok(Self::state().watchers.subscribe(current))
+    }
+
+    /// Register an async provider for `T` without running it.
+    ///
+    /// The factory's future runs on the first [`get_async`](RegistryApi::get_async)
+    /// for `T`; concurrent callers await the same in-flight initialization. The
+    /// built value is then registered as usual (emitting the register events), so
+    /// synchronous `get()` sees it as well. Until then `contains::<T>()` is `false`.
+    ///
+    /// Registering a value of `T` explicitly discards a provider that has not run
+    /// yet. If the initializing future is dropped before completing, the next
+    /// `get_async` calls the factory again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// struct Db(String);
+    ///
+    /// services::register_lazy_async(|| async { Db("connected".into()) });
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let db = services::get_async::<Db>().await.unwrap();
+    /// assert_eq!(db.0, "connected");
+    /// # });
+    /// ```
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    fn register_lazy_async<T, F, Fut>(&self, factory: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = T> + Send + 'static,
+    {
+        Self::state().lazy_async.insert::<T, F, Fut>(factory);
+    }
+
+    /// Retrieve a value, running its async provider first if one is pending.
+    ///
+    /// Falls back to a regular [`get`](RegistryApi::get) when no provider is
+    /// registered for `T`.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is neither registered nor provided lazily
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    fn get_async<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Arc<T>, RegistryError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let started = Instant::now();
+            let Some(lazy) = Self::state().lazy_async.get::<T>() else {
+                return self.get::<T>();
+            };
+
+            let (value, built_here) = lazy.resolve().await;
+            if built_here && Self::state().lazy_async.is_current(&lazy) {
+                self.register_arc(value.clone());
+            }
+
+            self.emit_event(&RegistryEvent::Get {
+                type_name: std::any::type_name::<T>(),
+                found: true,
+                meta: EventMeta::timed(started),
+            });
+
+            #[cfg(feature = "stats")]
+            Self::state()
+                .stats
+                .record(TypeId::of::<T>(), std::any::type_name::<T>(), true);
+
+            Ok(value)
+        }
+    }
+
+    /// Register an async provider that builds `T` from other registry entries.
+    ///
+    /// The async counterpart of [`register_provider`](RegistryApi::register_provider):
+    /// the provider's future runs on the first [`resolve_async`](RegistryApi::resolve_async)
+    /// of `T` and awaits its dependencies through the owned [`ResolveCtx`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// struct Config(String);
+    /// struct Db(String);
+    ///
+    /// services::register(Config("postgres://localhost".into()));
+    /// services::register_provider_async(|ctx| async move {
+    ///     let config = ctx.resolve_async::<Config>().await?;
+    ///     Ok(Db(config.0.clone())) // e.g. open the connection here
+    /// });
+    ///
+    /// # futures_lite::future::block_on(async {
+    /// let db = services::resolve_async::<Db>().await.unwrap();
+    /// assert_eq!(db.0, "postgres://localhost");
+    /// # });
+    /// ```
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    fn register_provider_async<T, F, Fut>(&self, provider: F)
+    where
+        Self: Sized + 'static,
+        T: Send + Sync + 'static,
+        F: Fn(ResolveCtx<Self>) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<T, RegistryError>> + Send + 'static,
+    {
+        Self::state()
+            .providers
+            .insert::<Self, T>(Provider::Async(Box::new(move |ctx| {
+                Box::pin(provider(ctx))
+            })));
+    }
+
+    /// Retrieve `T`, awaiting its providers and those of its dependencies if needed.
+    ///
+    /// Runs sync and async providers alike; otherwise behaves as
+    /// [`resolve`](RegistryApi::resolve). Types without a provider behave as
+    /// with [`get_async`](RegistryApi::get_async). Dependency cycles are detected
+    /// across sync and async providers.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::DependencyCycle` if the providers depend on each other in a cycle
+    /// - Any error returned by a provider
+    /// - Type `T` is neither registered nor provided
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    fn resolve_async<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Arc<T>, RegistryError>> + Send
+    where
+        Self: Default + Send + Sync + 'static,
+    {
+        crate::resolver::resolve_in_async::<Self, T>(Vec::new())
+    }
+
+    /// Check out an item from the registered [`Pool<T>`](crate::Pool).
+    ///
+    /// Blocks until an item is available; the returned guard puts it back on drop.
+    ///
+    /// # Errors
+    ///
+    /// - No `Pool<T>` is registered
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    fn checkout<T: Send + 'static>(&self) -> Result<PoolGuard<T>, RegistryError> {
+        Ok(self.get::<Pool<T>>()?.checkout())
+    }
+
+    /// Listen for registrations and replacements of `T`.
+    ///
+    /// `callback` receives the newly registered value after it has been stored,
+    /// without any registry lock held. It stays subscribed until the returned
+    /// guard is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    /// use std::sync::Arc;
+    ///
+    /// define_registry!(config);
+    ///
+    /// let _guard = config::on_change::<String>(|value: &Arc<String>| {
+    ///     println!("config reloaded: {value}");
+    /// });
+    ///
+    /// config::register("v2".to_string()); // prints "config reloaded: v2"
+    /// ```
+    fn on_change<T: Send + Sync + 'static>(
+        &self,
+        callback: impl Fn(&Arc<T>) + Send + Sync + 'static,
+    ) -> ChangeListenerGuard {
+        Self::state().change_listeners.add::<T, _>(callback)
+    }
+
+    /// Wait until a value of type `T` is registered.
+    ///
+    /// Returns a future resolving to the value; it resolves immediately if `T` is
+    /// already registered. Lets components started before their dependencies
+    /// `await` the registration instead of polling `contains` in a loop. The
+    /// future works with any executor.
+    fn wait_for<T: Send + Sync + 'static>(&self) -> WaitFor<Self, T> {
+        WaitFor::new()
+    }
+
+    /// Block the current thread until a value of type `T` is registered.
+    ///
+    /// The non-async counterpart of [`wait_for`](RegistryApi::wait_for). Returns
+    /// immediately if `T` is already registered.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::Timeout` if `T` is not registered within `timeout`
+    fn wait_for_blocking<T: Send + Sync + 'static>(
+        &self,
+        timeout: Duration,
+    ) -> Result<Arc<T>, RegistryError> {
+        wait_blocking::<Self, T>(timeout)
+    }
+
+    /// Retrieve a value, retrying with backoff while it is not registered yet.
+    ///
+    /// For staggered startup where registration order across threads isn't fully
+    /// controlled and neither [`wait_for`](RegistryApi::wait_for) nor a known
+    /// timeout fits. Sleeps `backoff` before the first retry and doubles the delay
+    /// before each further one, so at most `retries + 1` lookups are made.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is still not registered after the last retry
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    /// use std::time::Duration;
+    ///
+    /// define_registry!(services);
+    ///
+    /// std::thread::spawn(|| services::register("late".to_string()));
+    ///
+    /// // Up to 5 retries, sleeping 10ms, 20ms, 40ms, ...
+    /// let value = services::get_eventually::<String>(5, Duration::from_millis(10)).unwrap();
+    /// assert_eq!(*value, "late");
+    /// ```
+    fn get_eventually<T: Send + Sync + 'static>(
+        &self,
+        retries: u32,
+        backoff: Duration,
+    ) -> Result<Arc<T>, RegistryError> {
+        get_with_retries(self, retries, backoff)
+    }
+
+    /// Register a value whose concurrent users are bounded by `max_concurrent` permits.
+    ///
+    /// Users acquire a permit with [`get_permit`](RegistryApi::get_permit) or
+    /// [`get_permit_blocking`](RegistryApi::get_permit_blocking) and release it by
+    /// dropping the returned [`Permit`]. Plain `get` bypasses the limit. The limit
+    /// belongs to the type, so it stays in place when `T` is replaced later;
+    /// registering with a new limit changes it once the value is stored, and
+    /// permits already handed out count against the new limit. A rejected value
+    /// leaves the limit unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_concurrent` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// struct ApiClient;
+    ///
+    /// services::register_limited(ApiClient, 4); // at most 4 requests in flight
+    ///
+    /// let client = services::get_permit_blocking::<ApiClient>().unwrap();
+    /// // ... use `client`; the permit is released when it goes out of scope
+    /// ```
+    #[track_caller]
+    fn register_limited<T: Send + Sync + 'static>(&self, value: T, max_concurrent: usize) {
+        assert!(max_concurrent > 0, "max_concurrent must be non-zero");
+
+        if store(self, Arc::new(value), |e| e).is_ok() {
+            Self::state().limits.set(TypeId::of::<T>(), max_concurrent);
+        }
+    }
+
+    /// Wait for a permit to use `T` and return the value inside it.
+    ///
+    /// Types registered without a limit are handed out immediately. The future
+    /// works with any executor.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    fn get_permit<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Permit<T>, RegistryError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            let semaphore = Self::state().limits.get(TypeId::of::<T>());
+            if let Some(semaphore) = &semaphore {
+                std::future::poll_fn(|cx| semaphore.poll_acquire(cx)).await;
+            }
+            permit_for(self, semaphore)
+        }
+    }
+
+    /// Block the current thread until a permit to use `T` is free.
+    ///
+    /// The non-async counterpart of [`get_permit`](RegistryApi::get_permit).
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned
+    fn get_permit_blocking<T: Send + Sync + 'static>(&self) -> Result<Permit<T>, RegistryError> {
+        let semaphore = Self::state().limits.get(TypeId::of::<T>());
+        if let Some(semaphore) = &semaphore {
+            semaphore.acquire_blocking();
+        }
+        permit_for(self, semaphore)
+    }
+
+    /// Check if a type is registered in the registry.
+    ///
+    /// Returns `Ok(true)` if the type is registered, `Ok(false)` if not found.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned, unless the registry's [`PoisonPolicy`] is `Recover`
+    fn contains<T: Send + Sync + 'static>(&self) -> Result<bool, RegistryError> {
+        contains_entry(self, TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// The type names in `types` that are not registered, checked under a
+    /// single storage lock.
+    ///
+    /// Backs the [`contains_all!`](crate::contains_all) and
+    /// [`contains_any!`](crate::contains_any) macros, which build the
+    /// `(TypeId, type name)` pairs; a value with a pending lazy provider counts
+    /// as registered, as in [`contains`](RegistryApi::contains). Emits one
+    /// `RegistryEvent::Contains` trace event per type.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned, unless the registry's [`PoisonPolicy`] is `Recover`
+    fn missing_types(
+        &self,
+        types: &[(TypeId, &'static str)],
+    ) -> Result<Vec<&'static str>, RegistryError> {
+        let found: Vec<bool> = {
+            let map = lock_for_lookup(self)?;
+            types
+                .iter()
+                .map(|(type_id, _)| {
+                    map.contains_key(type_id) || Self::state().lazy.contains(*type_id)
+                })
+                .collect()
+        };
+
+        let mut missing = Vec::new();
+        for (&(_, type_name), found) in types.iter().zip(found) {
+            self.emit_event(&RegistryEvent::Contains {
+                type_name,
+                found,
+                meta: EventMeta::default(),
+            });
+            if !found {
+                missing.push(type_name);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Number of strong `Arc` references to the value registered for `T`.
+    ///
+    /// Returns `None` if `T` is not registered. The count includes the registry's
+    /// own reference, so `Some(1)` means nobody outside the registry holds the value.
+    /// Useful for finding out who still holds an old value after a replacement.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn strong_count<T: Send + Sync + 'static>(&self) -> Option<usize> {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&TypeId::of::<T>())
+            .map(|e| Arc::strong_count(e.value()))
+    }
+
+    /// Source location of the call that registered the current value of `T`.
+    ///
+    /// Answers "which module overwrote my logger?". A value built by a lazy factory
+    /// reports the [`register_lazy`](RegistryApi::register_lazy) call; one built by
+    /// a provider reports the location inside the registry that stored it.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn registered_at<T: Send + Sync + 'static>(&self) -> Option<&'static Location<'static>> {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&TypeId::of::<T>())
+            .and_then(RegistryEntry::registered_at)
+    }
+
+    /// The [`Metadata`] the current value of `T` was registered with, if any.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn metadata<T: Send + Sync + 'static>(&self) -> Option<Arc<Metadata>> {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&TypeId::of::<T>())
+            .and_then(|e| e.metadata_arc().cloned())
+    }
+
+    /// Keep the last `depth` values that registrations of `T` replaced, for
+    /// [`get_previous`](RegistryApi::get_previous).
+    ///
+    /// Off by default; `0` turns it off again and releases the retained values.
+    /// Lowering the depth drops the oldest values.
+    fn enable_history<T: Send + Sync + 'static>(&self, depth: usize) {
+        let dropped = Self::state().history.enable(TypeId::of::<T>(), depth);
+        // Dropped with no lock held
+        drop(dropped);
+    }
+
+    /// A value of `T` replaced since history was enabled with
+    /// [`enable_history`](RegistryApi::enable_history): `0` is the value the
+    /// current one replaced, `1` the one before it, and so on.
+    ///
+    /// Returns `None` if history is off for `T` or does not reach back `k + 1`
+    /// replacements.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(config);
+    ///
+    /// config::enable_history::<String>(5);
+    /// config::register("v1".to_string());
+    /// config::register("v2".to_string());
+    /// config::register("v3".to_string());
+    ///
+    /// assert_eq!(*config::get_previous::<String>(0).unwrap(), "v2");
+    /// assert_eq!(*config::get_previous::<String>(1).unwrap(), "v1");
+    /// assert!(config::get_previous::<String>(2).is_none());
+    /// ```
+    fn get_previous<T: Send + Sync + 'static>(&self, k: usize) -> Option<Arc<T>> {
+        Self::state()
+            .history
+            .get(TypeId::of::<T>(), k)
+            .and_then(|value| value.downcast::<T>().ok())
+    }
+
+    /// Register the value of `T` that the current one replaced again, undoing
+    /// the last registration; returns the restored value.
+    ///
+    /// Takes the value from the history kept by
+    /// [`enable_history`](RegistryApi::enable_history), so repeated calls step
+    /// further back. The value is restored with the metadata it was registered
+    /// with; the `RegisterCompleted` event is flagged with `rollback: true`,
+    /// and the reverted value is not added to the history.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(config);
+    ///
+    /// config::enable_history::<String>(5);
+    /// config::register("good".to_string());
+    /// config::register("bad".to_string());
+    ///
+    /// assert_eq!(*config::revert::<String>().unwrap(), "good");
+    /// assert_eq!(*config::get::<String>().unwrap(), "good");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::NoPreviousValue` if the history of `T` is empty or off
+    /// - `RegistryError::FinalType` if the current value of `T` is final; the
+    ///   history is left unchanged
+    #[track_caller]
+    fn revert<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        let history = &Self::state().history;
+        let entry = history
+            .pop(TypeId::of::<T>())
+            .ok_or(RegistryError::NoPreviousValue { type_name })?;
+        let value = match entry.value().clone().downcast::<T>() {
+            Ok(value) => value,
+            Err(_) => return Err(RegistryError::TypeMismatch { type_name }),
+        };
+
+        let restored = entry.clone().restamped().at(Location::caller());
+        if let Err(err) = store_entry(self, TypeId::of::<T>(), restored, true) {
+            history.restore(TypeId::of::<T>(), entry);
+            return Err(err);
+        }
+        Ok(value)
+    }
+
+    /// Record every mutation of this registry from now on, discarding earlier
+    /// records; see [`timeline`](RegistryApi::timeline).
+    ///
+    /// Records of registrations keep the registered value alive until the
+    /// timeline is stopped or restarted.
+    fn start_timeline(&self) {
+        let discarded = Self::state().timeline.start();
+        // Dropped with no lock held
+        drop(discarded);
+    }
+
+    /// Stop recording mutations and take the records.
+    fn stop_timeline(&self) -> Vec<TimelineRecord> {
+        Self::state().timeline.stop()
+    }
+
+    /// The mutations recorded since [`start_timeline`](RegistryApi::start_timeline),
+    /// oldest first.
+    ///
+    /// Each [`TimelineRecord`] tells the operation, type, generation, time and
+    /// calling location; records of registrations also hold the value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, TimelineOp};
+    ///
+    /// define_registry!(app);
+    /// define_registry!(replica);
+    ///
+    /// app::start_timeline();
+    /// app::register(1u32);
+    /// app::register(2u32);
+    ///
+    /// let timeline = app::timeline();
+    /// assert_eq!(timeline[1].op, TimelineOp::Replace);
+    /// assert_eq!(timeline[1].generation, 2);
+    ///
+    /// // Rebuild the state after the first registration
+    /// replica::replay_timeline(&timeline[..1]).unwrap();
+    /// assert_eq!(*replica::get::<u32>().unwrap(), 1);
+    /// ```
+    fn timeline(&self) -> Vec<TimelineRecord> {
+        Self::state().timeline.records()
+    }
+
+    /// Apply the mutations of `records`, taken from another registry's
+    /// [`timeline`](RegistryApi::timeline), to this registry in order.
+    ///
+    /// Replay into a fresh registry to reconstruct the state at any point of
+    /// the recording. Registrations keep their metadata and original
+    /// location; removals of types that are not registered are skipped.
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::FinalType` if a record would replace or remove a final
+    /// value; the records before it stay applied.
+    fn replay_timeline(&self, records: &[TimelineRecord]) -> Result<(), RegistryError> {
+        for record in records {
+            match (record.op, record.type_id, &record.entry) {
+                (TimelineOp::Clear, ..) => {
+                    clear_entries(self, false, |_| {});
+                }
+                (TimelineOp::Remove, Some(type_id), _) => {
+                    let type_name = record.type_name.unwrap_or_default();
+                    match remove_entry(self, type_id, type_name, record.location) {
+                        Ok(_) | Err(RegistryError::TypeNotFound { .. }) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+                (op, Some(type_id), Some(entry)) => {
+                    let entry = entry.clone().restamped();
+                    store_entry(self, type_id, entry, op == TimelineOp::Revert)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Stamp entries with the time of their last retrieval by `get`.
+    ///
+    /// Off by default. Needed for meaningful [`stale_entries`](RegistryApi::stale_entries)
+    /// reports: untracked retrievals leave no trace.
+    fn set_access_tracking(&self, enabled: bool) {
+        Self::state()
+            .access_tracking
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Whether `get` records access times.
+    fn access_tracking(&self) -> bool {
+        Self::state()
+            .access_tracking
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Entries not retrieved for at least `older_than`, sorted by type name.
+    ///
+    /// Entries that were never retrieved count from their registration. Only
+    /// retrievals made with [`set_access_tracking`](RegistryApi::set_access_tracking)
+    /// enabled are considered.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn stale_entries(&self, older_than: Duration) -> Vec<StaleEntry> {
+        let mut stale: Vec<StaleEntry> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .filter(|e| e.idle() >= older_than)
+            .map(|e| StaleEntry {
+                type_name: e.type_name(),
+                idle: e.idle(),
+                accessed: e.last_access().is_some(),
+            })
+            .collect();
+        stale.sort_by_key(|e| e.type_name);
+        stale
+    }
+
+    /// Snapshot every entry: type name, registration time, generation, metadata,
+    /// strong count and call site.
+    ///
+    /// Entries are sorted by type name. Meant for admin and debug endpoints that
+    /// show what is wired right now.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn describe(&self) -> Vec<EntryInfo> {
+        let mut entries: Vec<EntryInfo> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .map(EntryInfo::from)
+            .collect();
+        entries.sort_by_key(|e| e.type_name);
+        entries
+    }
+
+    /// Call `visit` with the [`EntryInfo`] and the type-erased value of every
+    /// entry, sorted by type name.
+    ///
+    /// Lets tooling walk the contents for inspections the registry doesn't
+    /// anticipate, e.g. downcasting to a few known types. The entries are
+    /// captured first and visited with no lock held, so `visit` may use the
+    /// registry; values registered meanwhile are not visited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(app);
+    ///
+    /// app::register(8080u16);
+    /// app::register(String::from("localhost"));
+    ///
+    /// let mut numbers = Vec::new();
+    /// app::for_each_entry(|info, value| {
+    ///     if let Some(port) = value.downcast_ref::<u16>() {
+    ///         numbers.push((info.type_name, *port));
+    ///     }
+    /// });
+    /// assert_eq!(numbers, [("u16", 8080)]);
+    /// ```
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn for_each_entry(&self, mut visit: impl FnMut(&EntryInfo, &Arc<dyn Any + Send + Sync>)) {
+        let mut entries: Vec<(EntryInfo, Arc<dyn Any + Send + Sync>)> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            // The info is taken before the copy, so its strong count is the registry's
+            .map(|e| (EntryInfo::from(e), e.value().clone()))
+            .collect();
+        entries.sort_by_key(|(info, _)| info.type_name);
+
+        for (info, value) in &entries {
+            visit(info, value);
+        }
+    }
+
+    /// List every entry, one per line and sorted by type name, with the `Debug`
+    /// output of values registered through
+    /// [`register_debuggable`](RegistryApi::register_debuggable).
+    ///
+    /// Other values are shown as `<opaque>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// #[derive(Debug)]
+    /// struct Config {
+    ///     port: u16,
+    /// }
+    ///
+    /// services::register_debuggable(Config { port: 8080 });
+    /// services::register(42u32);
+    ///
+    /// let dump = services::dump();
+    /// assert!(dump.contains("Config = Config { port: 8080 }"));
+    /// assert!(dump.contains("u32 = <opaque>"));
+    /// ```
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn dump(&self) -> String {
+        let mut entries: Vec<RegistryEntry> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by_key(RegistryEntry::type_name);
+
+        // Formatted without the lock held: a `Debug` impl may use the registry
+        let mut out = String::new();
+        for entry in &entries {
+            match entry.debug_value() {
+                Some(value) => writeln!(out, "{} = {:?}", entry.type_name(), value),
+                None => writeln!(out, "{} = <opaque>", entry.type_name()),
+            }
+            .expect("writing to a String cannot fail");
+        }
+        out
+    }
+
+    /// Serialize every entry registered with
+    /// [`register_serializable`](RegistryApi::register_serializable) into a
+    /// pretty-printed JSON object keyed by type name.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Limits {
+    ///     max_connections: u32,
+    /// }
+    ///
+    /// services::register_serializable(Limits { max_connections: 64 });
+    /// services::register(42u32); // not included
+    ///
+    /// assert!(services::export_json().contains("\"max_connections\": 64"));
+    /// ```
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    #[cfg(feature = "serde")]
+    fn export_json(&self) -> String {
+        let mut entries: Vec<RegistryEntry> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by_key(RegistryEntry::type_name);
+
+        // Serialized without the lock held, like `dump`
+        crate::serializable::export_json(&entries)
+    }
+
+    /// Move the values of the types listed in `L` from an `anymap` into the
+    /// registry, returning how many were present (feature `anymap`).
+    ///
+    /// Values of other types stay in `map`, so a migration can proceed one
+    /// type list at a time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, AnyMap};
+    ///
+    /// define_registry!(services);
+    ///
+    /// let mut map = AnyMap::new();
+    /// map.insert(String::from("postgres://localhost"));
+    /// map.insert(8080u16);
+    ///
+    /// assert_eq!(services::import_anymap::<(String, u16)>(&mut map), 2);
+    /// assert_eq!(*services::get::<u16>().unwrap(), 8080);
+    /// assert!(map.is_empty());
+    /// ```
+    #[cfg(feature = "anymap")]
+    #[track_caller]
+    fn import_anymap<L: crate::AnyMapImport>(&self, map: &mut crate::AnyMap) -> usize {
+        L::import(self, map)
+    }
+
+    /// An `anymap` holding clones of the registered values of the types listed
+    /// in `L` (feature `anymap`); unregistered types are left out.
+    #[cfg(feature = "anymap")]
+    fn export_anymap<L: crate::AnyMapExport>(&self) -> crate::AnyMap {
+        let mut map = crate::AnyMap::new();
+        L::export(self, &mut map);
+        map
+    }
+
+    /// A checksum of the registered types and their generations.
+    ///
+    /// Two registries have the same fingerprint if the same types are
+    /// registered, each replaced as often, so replicas can cheaply compare
+    /// whether their wiring diverged. Values are not hashed; see
+    /// [`fingerprint_with_values`](RegistryApi::fingerprint_with_values).
+    /// Only comparable between processes running the same binary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(primary);
+    /// define_registry!(replica);
+    ///
+    /// primary::register(8080u16);
+    /// replica::register(8080u16);
+    /// assert_eq!(primary::fingerprint(), replica::fingerprint());
+    ///
+    /// replica::register(9090u16);
+    /// assert_ne!(primary::fingerprint(), replica::fingerprint());
+    /// ```
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn fingerprint(&self) -> u64 {
+        crate::fingerprint::fingerprint(copied_entries::<Self>(), |_| None)
+    }
+
+    /// Whether `other` holds the same types, with equal values where both sides
+    /// registered them with [`register_comparable`](RegistryApi::register_comparable).
+    ///
+    /// Lets tests verify that two bootstrap paths produce equivalent wiring.
+    /// Shorthand for [`contents_equal_by`](RegistryApi::contents_equal_by) with
+    /// [`ContentEquality::Values`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(production);
+    /// define_registry!(testing);
+    ///
+    /// #[derive(PartialEq)]
+    /// struct Port(u16);
+    ///
+    /// production::register_comparable(Port(8080));
+    /// testing::register_comparable(Port(8080));
+    /// assert!(production::contents_equal(&testing::Api));
+    ///
+    /// testing::register_comparable(Port(0));
+    /// assert!(!production::contents_equal(&testing::Api));
+    /// ```
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If a storage lock is poisoned, this method automatically recovers.
+    fn contents_equal<O: RegistryApi>(&self, other: &O) -> bool {
+        self.contents_equal_by(other, ContentEquality::Values)
+    }
+
+    /// Whether `other` holds the same types, with values compared as `equality`
+    /// says.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If a storage lock is poisoned, this method automatically recovers.
+    fn contents_equal_by<O: RegistryApi>(&self, _other: &O, equality: ContentEquality) -> bool {
+        crate::contents_eq::contents_equal(
+            copied_entries::<Self>(),
+            copied_entries::<O>(),
+            equality,
+        )
+    }
+
+    /// Like [`fingerprint`](RegistryApi::fingerprint), additionally hashing the
+    /// JSON form of entries registered with
+    /// [`register_serializable`](RegistryApi::register_serializable).
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    #[cfg(feature = "serde")]
+    fn fingerprint_with_values(&self) -> u64 {
+        crate::fingerprint::fingerprint(copied_entries::<Self>(), crate::fingerprint::json_value)
+    }
+
+    /// Encode every entry registered with
+    /// [`register_serializable`](RegistryApi::register_serializable) into a
+    /// binary snapshot for [`import_snapshot`](RegistryApi::import_snapshot).
+    ///
+    /// Values are encoded with bincode, which does not support self-describing
+    /// serde types such as `serde_json::Value` or untagged enums.
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::Custom` if a value cannot be encoded.
+    #[cfg(feature = "bincode")]
+    fn export_snapshot(&self) -> Result<Vec<u8>, RegistryError> {
+        let mut entries: Vec<RegistryEntry> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by_key(RegistryEntry::type_name);
+        crate::persist::export(&entries)
+    }
+
+    /// Run `reload` whenever the process receives `SIGHUP` (feature `unix`).
+    ///
+    /// `reload` runs on a dedicated thread and typically re-reads the
+    /// configuration and registers it again; each run is preceded by a
+    /// `RegistryEvent::Reload` event. The handler stays installed until the
+    /// returned [`SighupGuard`](crate::SighupGuard) is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(config);
+    ///
+    /// fn load_settings() -> String {
+    ///     std::fs::read_to_string("/etc/app/settings").unwrap_or_default()
+    /// }
+    ///
+    /// config::register(load_settings());
+    /// let _reload = config::reload_on_sighup(|| config::register(load_settings())).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The signal handler or its thread cannot be set up.
+    #[cfg(all(unix, feature = "unix"))]
+    fn reload_on_sighup(
+        &self,
+        reload: impl Fn() + Send + 'static,
+    ) -> std::io::Result<crate::SighupGuard>
+    where
+        Self: Clone + Send + 'static,
+    {
+        crate::sighup::install(self.clone(), reload)
+    }
+
+    /// Persist the serializable entries of this registry in the file at `path`
+    /// (feature `file-backend`).
+    ///
+    /// Values saved in `path` by an earlier run are restored first, like
+    /// [`import_snapshot`](RegistryApi::import_snapshot) does, so register the
+    /// defaults with [`register_serializable`](RegistryApi::register_serializable)
+    /// before enabling the backend. From then on the file is atomically
+    /// rewritten after every registration, replacement, removal and clear;
+    /// write errors after enabling are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(settings);
+    ///
+    /// let path = std::env::temp_dir().join("settings-doctest.bin");
+    /// # let _ = std::fs::remove_file(&path);
+    /// settings::register_serializable(String::from("info")); // default log level
+    /// settings::enable_file_backend(&path).unwrap();
+    /// settings::register_serializable(String::from("debug")); // saved to `path`
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - The file exists but cannot be read or imported
+    /// - The current entries cannot be written to the file
+    #[cfg(feature = "file-backend")]
+    fn enable_file_backend(&self, path: impl AsRef<std::path::Path>) -> Result<(), RegistryError> {
+        crate::file_backend::enable(self, path.as_ref())
+    }
+
+    /// Stop writing the backing file; its contents are left as they are.
+    #[cfg(feature = "file-backend")]
+    fn disable_file_backend(&self) {
+        Self::state().file_backend.disable()
+    }
+
+    /// Register the values of a snapshot made by
+    /// [`export_snapshot`](RegistryApi::export_snapshot), possibly in another
+    /// process.
+    ///
+    /// The registry recognizes the types of the snapshot by their serializable
+    /// entries, so register defaults with `register_serializable` first. The
+    /// imported values replace them and stay serializable; final values are
+    /// kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(primary);
+    /// define_registry!(replica);
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Limits {
+    ///     max_connections: u32,
+    /// }
+    ///
+    /// primary::register_serializable(Limits { max_connections: 64 });
+    /// let snapshot = primary::export_snapshot().unwrap();
+    ///
+    /// replica::register_serializable(Limits { max_connections: 8 }); // default
+    /// replica::import_snapshot(&snapshot).unwrap();
+    /// assert_eq!(replica::get::<Limits>().unwrap().max_connections, 64);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::Custom` if the snapshot is malformed or contains a type
+    /// without a serializable entry; nothing is registered then.
+    #[cfg(feature = "bincode")]
+    fn import_snapshot(&self, bytes: &[u8]) -> Result<(), RegistryError> {
+        crate::persist::import(self, bytes)
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Capacity
+    // -------------------------------------------------------------------------------------------------
+
+    /// Number of entries the storage map can hold without reallocating.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn capacity(&self) -> usize {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .capacity()
+    }
+
+    /// Approximate number of heap bytes owned by the registry.
+    ///
+    /// Counts the storage map's table (sized by its capacity, not its length) plus,
+    /// for every entry, the `Arc` allocation header and the shallow size of the value.
+    /// Heap memory owned by the values themselves (e.g. a `String`'s buffer) is not
+    /// included, so treat the result as a lower bound useful for spotting growth.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn approx_heap_bytes(&self) -> usize {
+        let map = lock_storage::<Self>().unwrap_or_else(|p| p.into_inner());
+
+        // hashbrown stores one control byte per bucket next to each (key, value) slot
+        let slot = std::mem::size_of::<(TypeId, RegistryEntry)>() + 1;
+        let table = map.capacity() * slot;
+
+        // ArcInner holds the strong and weak counters ahead of the value
+        let arc_header = 2 * std::mem::size_of::<usize>();
+        let values: usize = map
+            .values()
+            .map(|e| arc_header + std::mem::size_of_val(&**e.value()))
+            .sum();
+
+        table + values
+    }
+
+    /// Reserve room for at least `additional` more types in the storage map.
+    ///
+    /// Call it before a registration burst (a plugin host wiring hundreds of
+    /// types at startup) to avoid repeated rehashing. To size the map from the
+    /// start, define the registry with `define_registry!(name, { capacity: n })`.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn reserve(&self, additional: usize) {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .reserve(additional);
+    }
+
+    /// Shrink the storage map's capacity as much as possible.
+    ///
+    /// Useful for long-lived processes that churn registrations (tests, plugin hosts)
+    /// and want to return the table memory once the registry has settled.
+    ///
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    fn shrink_to_fit(&self) {
+        lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .shrink_to_fit();
+    }
+
+    // EDUCATIONAL: Memory leak demonstration (commented out)
+    //
+    // This method demonstrates a common pitfall when working with Arc::into_raw().
+    // It leaks memory because the Arc reference count is never decremented.
+    // Every call to this method leaks one Arc reference permanently.
+    //
+    // #[doc(hidden)]
+    // fn get_ref<T: Send + Sync + Clone + 'static>(&self) -> Result<&'static T, RegistryError> {
+    //     let arc = self.get::<T>()?;
+    //     let ptr = Arc::into_raw(arc);  // ⚠️ MEMORY LEAK: Arc is never freed
+    //     Ok(unsafe { &*ptr })
+    // }
+
+    /// Remove the value of `T` from the registry and return it.
+    ///
+    /// Also discards a pending lazy provider of `T`. Emits a
+    /// `RegistryEvent::Remove` trace event once the entry is gone; handles
+    /// already retrieved stay valid.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::TypeNotFound` if `T` is not registered
+    /// - `RegistryError::FinalType` if `T` is registered as final
+    /// - `RegistryError::ProtectedType` if the value of `T` is
+    ///   [protected](RegistryApi::protect)
+    /// - Type mismatch (extremely rare)
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(services);
+    ///
+    /// services::register("temporary".to_string());
+    /// let value = services::remove::<String>().unwrap();
+    /// assert_eq!(*value, "temporary");
+    /// assert!(!services::contains::<String>().unwrap());
+    /// ```
+    #[track_caller]
+    fn remove<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        let entry = remove_entry(self, TypeId::of::<T>(), type_name, Some(Location::caller()))?;
+
+        entry
+            .value()
+            .clone()
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Remove the value of `T` from the registry and return it by value.
+    ///
+    /// For teardown code that consumes a resource, e.g. to call a
+    /// `fn close(self)`. Behaves like [`remove`](RegistryApi::remove), but the
+    /// entry is only removed if the registry holds the last handle to the value;
+    /// otherwise it stays registered.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::StillShared` if other `Arc` handles to the value exist
+    /// - `RegistryError::TypeNotFound` if `T` is not registered
+    /// - `RegistryError::FinalType` / `ProtectedType` if the entry can't be removed
+    ///
+    /// # Example
     ///
-    /// Takes ownership of the value and wraps it in an `Arc` automatically.
-    /// If a value of the same type is already registered, it will be replaced.
+    /// ```rust
+    /// use singleton_registry::{define_registry, RegistryError};
     ///
-    /// # Design Note
+    /// define_registry!(services);
     ///
-    /// This method does not return a `Result` because registration is designed
-    /// for the "write-once" pattern during application startup (and rarely at runtime for rewrite). Lock poisoning
-    /// is automatically recovered. If registration must succeed, ensure your
-    /// application initialization doesn't panic while holding registry locks.
-    fn register<T: Send + Sync + 'static>(&self, value: T) {
-        self.register_arc(Arc::new(value));
+    /// struct Connection(String);
+    ///
+    /// services::register(Connection("db".to_string()));
+    /// let held = services::get::<Connection>().unwrap();
+    /// assert!(matches!(
+    ///     services::take_owned::<Connection>(),
+    ///     Err(RegistryError::StillShared { strong_count: 2, .. })
+    /// ));
+    ///
+    /// drop(held);
+    /// let connection: Connection = services::take_owned().unwrap();
+    /// assert_eq!(connection.0, "db");
+    /// ```
+    #[track_caller]
+    fn take_owned<T: Send + Sync + 'static>(&self) -> Result<T, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        // Handles are only handed out under the storage lock, so a value the
+        // registry solely owns while it is held can be unwrapped once removed
+        let entry = remove_entry_unless(
+            self,
+            TypeId::of::<T>(),
+            type_name,
+            Some(Location::caller()),
+            |entry| {
+                let strong_count = Arc::strong_count(entry.value());
+                (strong_count > 1).then_some(RegistryError::StillShared {
+                    type_name,
+                    strong_count,
+                })
+            },
+        )?;
+
+        let value = entry
+            .into_value()
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })?;
+        Arc::try_unwrap(value).map_err(|value| RegistryError::StillShared {
+            type_name,
+            strong_count: Arc::strong_count(&value),
+        })
     }
 
-    /// Register an Arc-wrapped value in the registry.
+    /// Remove every entry whose type name `keep` rejects, returning the removed
+    /// type names, sorted.
     ///
-    /// More efficient than `register` when you already have an `Arc`,
-    /// as it avoids creating an additional reference count.
+    /// For bulk cleanup by module path, e.g. dropping everything under
+    /// `my_tests::`, where the concrete types aren't nameable at the cleanup
+    /// site. Each entry goes through [`remove`](RegistryApi::remove), so it
+    /// emits a `Remove` event and discards a pending lazy provider; final and
+    /// protected entries are kept regardless of `keep`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use singleton_registry::define_registry;
+    ///
+    /// define_registry!(app);
+    ///
+    /// mod fixtures {
+    ///     pub struct Db;
+    ///     pub struct Cache;
+    /// }
+    ///
+    /// app::register(8080u16);
+    /// app::register(fixtures::Db);
+    /// app::register(fixtures::Cache);
+    ///
+    /// let removed = app::retain(|type_name| !type_name.contains("::fixtures::"));
+    /// assert_eq!(removed.len(), 2);
+    /// assert!(app::contains::<u16>().unwrap());
+    /// ```
     ///
     /// # Lock Poisoning Recovery
     ///
     /// If the storage lock is poisoned, this method automatically recovers.
-    /// This is safe because the insert operation is idempotent.
-    fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
-        self.emit_event(&RegistryEvent::Register {
-            type_name: std::any::type_name::<T>(),
-        });
-
-        // Register the value
-        Self::storage()
-            .lock()
+    #[track_caller]
+    fn retain(&self, mut keep: impl FnMut(&str) -> bool) -> Vec<&'static str> {
+        let location = Location::caller();
+        let candidates: Vec<(TypeId, &'static str)> = lock_storage::<Self>()
             .unwrap_or_else(|p| p.into_inner())
-            .insert(TypeId::of::<T>(), value);
-
-        self.emit_event(&RegistryEvent::RegisterCompleted {
-            type_name: std::any::type_name::<T>(),
-        });
+            .iter()
+            .filter(|(_, entry)| !entry.is_final() && !entry.is_protected())
+            .map(|(type_id, entry)| (*type_id, entry.type_name()))
+            .filter(|(_, type_name)| !keep(type_name))
+            .collect();
+
+        let mut removed: Vec<&'static str> = candidates
+            .into_iter()
+            // An entry removed concurrently in the meantime is simply skipped
+            .filter(|(type_id, type_name)| {
+                remove_entry(self, *type_id, type_name, Some(location)).is_ok()
+            })
+            .map(|(_, type_name)| type_name)
+            .collect();
+        removed.sort_unstable();
+        removed
     }
 
-    /// Retrieve a value from the registry.
+    /// Clear all registered values from the registry.
     ///
-    /// Returns `Ok(Arc<T>)` if the type is found.
+    /// This method is intended for testing and only exists with the `test-util`
+    /// feature, so production builds cannot wipe their wiring by accident. It
+    /// removes all registered values and pending lazy providers but does NOT affect:
+    /// - Already-retrieved `Arc<T>` references (they remain valid)
+    /// - The tracing callback (use `clear_trace_callback()` to clear that)
+    /// - Values marked with [`protect`](RegistryApi::protect)
     ///
-    /// # Errors
+    /// Returns a [`ClearReport`] of the removed types, each of which is also
+    /// announced with a `RegistryEvent::Remove` trace event after the `Clear` event.
     ///
-    /// - Type `T` is not found in the registry
-    /// - Type mismatch (extremely rare)
-    /// - Registry lock is poisoned
-    fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
-        let map = Self::storage()
-            .lock()
-            .map_err(|_| RegistryError::RegistryLock)?;
-
-        let any_arc_opt = map.get(&TypeId::of::<T>()).cloned();
-
-        drop(map);
-
-        let result: Result<Arc<T>, RegistryError> = match any_arc_opt {
-            Some(any_arc) => any_arc
-                .downcast::<T>()
-                .map_err(|_| RegistryError::TypeMismatch {
-                    type_name: std::any::type_name::<T>(),
-                }),
-            None => Err(RegistryError::TypeNotFound {
-                type_name: std::any::type_name::<T>(),
-            }),
-        };
-
-        self.emit_event(&RegistryEvent::Get {
-            type_name: std::any::type_name::<T>(),
-            found: result.is_ok(),
-        });
+    /// # Lock Poisoning Recovery
+    ///
+    /// If the storage lock is poisoned, this method automatically recovers.
+    #[cfg(any(test, feature = "test-util"))]
+    fn clear(&self) -> ClearReport {
+        self.clear_all(false)
+    }
 
-        result
+    /// Clear the registry, including protected values if `force` is set.
+    ///
+    /// `clear_all(false)` is [`clear`](RegistryApi::clear). Requires the
+    /// `test-util` feature.
+    #[cfg(any(test, feature = "test-util"))]
+    fn clear_all(&self, force: bool) -> ClearReport {
+        ClearReport {
+            removed: clear_entries(self, force, |_| {}),
+        }
     }
 
-    /// Retrieve a cloned value from the registry.
+    /// Clear the registry and report values that are still referenced elsewhere.
     ///
-    /// Returns an owned value by cloning the value stored in the registry.
-    /// The type `T` must implement `Clone`.
+    /// Behaves like [`clear`](RegistryApi::clear), keeping protected values and
+    /// emitting the same `Clear` and `Remove` events, but first collects every
+    /// removed entry whose `Arc` is held outside the registry. Each such entry is
+    /// returned in the [`LeakReport`] instead of a `ClearReport` and announced with
+    /// a `RegistryEvent::Leak` trace event after the `Remove` events, so tests can
+    /// assert that no stale handles outlive teardown. Requires the `test-util` feature.
     ///
-    /// # Errors
+    /// # Lock Poisoning Recovery
     ///
-    /// - Type `T` is not found in the registry
-    /// - Type mismatch
-    fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
-        let arc = self.get::<T>()?;
-        Ok((*arc).clone())
+    /// If the storage lock is poisoned, this method automatically recovers.
+    #[cfg(any(test, feature = "test-util"))]
+    fn clear_with_leak_report(&self) -> LeakReport {
+        let mut entries = Vec::new();
+        clear_entries(self, false, |cleared| {
+            entries = cleared
+                .iter()
+                .filter(|e| Arc::strong_count(e.value()) > 1)
+                .map(|e| LeakedEntry {
+                    type_name: e.type_name(),
+                    // Discount the reference still held by the cleared entry
+                    outstanding: Arc::strong_count(e.value()) - 1,
+                })
+                .collect();
+        });
+
+        for entry in &entries {
+            self.emit_event(&RegistryEvent::Leak {
+                type_name: entry.type_name,
+                outstanding: entry.outstanding,
+                meta: EventMeta::default(),
+            });
+        }
+
+        LeakReport { entries }
     }
 
-    /// Retrieve a value from the registry, returning `None` if not registered.
-    ///
-    /// The primary graceful-degradation path — equivalent to `get::<T>().ok()` but
-    /// matches the language-neutral API surface defined in the JigsawFlow spec.
-    /// Prefer this over `get` when absence is expected and not a programming error.
-    fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
-        self.get::<T>().ok()
+    /// Install a new [`EventRecorder`] as the trace callback and return it,
+    /// replacing any previous callback. Requires the `test-util` feature.
+    #[cfg(any(test, feature = "test-util"))]
+    fn record_events(&self) -> EventRecorder {
+        let recorder = EventRecorder::new();
+        let sink = recorder.clone();
+        self.set_trace_callback(move |event| sink.record(event));
+        recorder
     }
 
-    /// Check if a type is registered in the registry.
+    /// Render the registry as deterministic text for golden-file or `insta`
+    /// snapshot tests, so "the bootstrap wired exactly these services" is one
+    /// assertion. Requires the `test-util` feature.
     ///
-    /// Returns `Ok(true)` if the type is registered, `Ok(false)` if not found.
+    /// One line per entry, sorted by type name: the `Debug` output of values
+    /// registered with [`register_debuggable`](RegistryApi::register_debuggable)
+    /// (`<opaque>` otherwise) and `[final]` / `[protected]` markers, followed by
+    /// indented metadata lines. Timestamps, locations and reference counts are
+    /// left out so the output is stable across runs.
     ///
-    /// # Errors
+    /// # Example
     ///
-    /// - Registry lock is poisoned
-    fn contains<T: Send + Sync + 'static>(&self) -> Result<bool, RegistryError> {
-        let found = Self::storage()
-            .lock()
-            .map(|m| m.contains_key(&TypeId::of::<T>()))
-            .map_err(|_| RegistryError::RegistryLock)?;
-
-        self.emit_event(&RegistryEvent::Contains {
-            type_name: std::any::type_name::<T>(),
-            found,
-        });
+    /// ```rust
+    /// use singleton_registry::{define_registry, Metadata};
+    ///
+    /// define_registry!(app);
+    ///
+    /// app::register_debuggable(8080u16);
+    /// app::register_with_meta(String::from("db"), Metadata::new("database url"));
+    ///
+    /// assert_eq!(
+    ///     app::golden_snapshot(),
+    ///     "alloc::string::String = <opaque>\n    description: database url\nu16 = 8080\n"
+    /// );
+    /// ```
+    #[cfg(any(test, feature = "test-util"))]
+    fn golden_snapshot(&self) -> String {
+        let entries: Vec<RegistryEntry> = lock_storage::<Self>()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .cloned()
+            .collect();
+        // Rendered without the lock held: a `Debug` impl may use the registry
+        crate::snapshot::render_golden(entries)
+    }
 
-        Ok(found)
+    /// A [`TestGuard`] that restores the registry to its current state when
+    /// dropped, including when the test panics. Requires the `test-util` feature.
+    #[cfg(any(test, feature = "test-util"))]
+    fn test_guard(&self) -> TestGuard<Self>
+    where
+        Self: Sized,
+    {
+        TestGuard::new()
     }
 
-    // EDUCATIONAL: Memory leak demonstration (commented out)
-    //
-    // This method demonstrates a common pitfall when working with Arc::into_raw().
-    // It leaks memory because the Arc reference count is never decremented.
-    // Every call to this method leaks one Arc reference permanently.
-    //
-    // #[doc(hidden)]
-    // fn get_ref<T: Send + Sync + Clone + 'static>(&self) -> Result<&'static T, RegistryError> {
-    //     let arc = self.get::<T>()?;
-    //     let ptr = Arc::into_raw(arc);  // ⚠️ MEMORY LEAK: Arc is never freed
-    //     Ok(unsafe { &*ptr })
-    // }
+    /// Take the registry's test lock, so tests sharing this registry run one at
+    /// a time without an external `serial_test` dependency. Blocks until the
+    /// [`TestLockGuard`] of any other test is dropped. Requires the `test-util`
+    /// feature.
+    #[cfg(any(test, feature = "test-util"))]
+    fn lock_for_test(&self) -> TestLockGuard {
+        Self::state().test_lock.acquire(Self::name())
+    }
 
-    /// Clear all registered values from the registry.
-    ///
-    /// This method is primarily intended for testing. It removes all registered
-    /// values but does NOT affect:
-    /// - Already-retrieved `Arc<T>` references (they remain valid)
-    /// - The tracing callback (use `clear_trace_callback()` to clear that)
+    /// Capture which value each type maps to, for comparing with a later
+    /// snapshot via [`RegistrySnapshot::diff`]. Requires the `test-util` feature.
     ///
     /// # Lock Poisoning Recovery
     ///
-    /// If the storage lock is poisoned, this method silently fails.
-    /// This is acceptable for a test-only method.
-    #[doc(hidden)]
-    fn clear(&self) {
-        self.emit_event(&RegistryEvent::Clear {});
-
-        if let Ok(mut registry) = Self::storage().lock() {
-            registry.clear();
-        }
+    /// If the storage lock is poisoned, this method automatically recovers.
+    #[cfg(any(test, feature = "test-util"))]
+    fn snapshot(&self) -> RegistrySnapshot {
+        let storage = lock_storage::<Self>().unwrap_or_else(|p| p.into_inner());
+        RegistrySnapshot::new(
+            storage
+                .iter()
+                .map(|(type_id, entry)| (*type_id, entry.type_name(), entry.value().clone())),
+        )
     }
 }
 
@@ -261,30 +3167,37 @@ pub trait RegistryApi {
 
 #[cfg(test)]
 mod tests {
-    use crate::RegistryError;
+    use crate::{RegistryEntry, RegistryError, RegistryState};
 
     use super::{RegistryApi, TraceCallback};
 
+    use crate::sync::LazyLock;
     use serial_test::serial;
-    use std::any::{Any, TypeId};
+    use std::any::TypeId;
     use std::collections::HashMap;
-    use std::sync::{Arc, LazyLock, Mutex};
+    use std::sync::{Arc, Mutex};
 
-    static STORAGE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
+    static STORAGE: LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> =
         LazyLock::new(|| Mutex::new(HashMap::new()));
 
     static TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
 
+    static STATE: LazyLock<RegistryState> = LazyLock::new(RegistryState::new);
+
     struct Api;
 
     impl RegistryApi for Api {
-        fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> {
+        fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> {
             &STORAGE
         }
 
         fn trace() -> &'static TraceCallback {
             &TRACE
         }
+
+        fn state() -> &'static RegistryState {
+            &STATE
+        }
     }
 
     const API: Api = Api;
@@ -333,13 +3246,20 @@ mod tests {
         API.clear();
 
         let result: Result<Arc<String>, RegistryError> = API.get();
+        let get_line = line!() - 1;
         assert!(result.is_err());
-        assert_eq!(
-            result.unwrap_err(),
+        match result.unwrap_err() {
             RegistryError::TypeNotFound {
-                type_name: "alloc::string::String"
+                type_name,
+                location: Some(location),
+                ..
+            } => {
+                assert_eq!(type_name, "alloc::string::String");
+                // The error points at the caller, not into the registry
+                assert_eq!((location.file(), location.line()), (file!(), get_line));
             }
-        );
+            other => panic!("unexpected error: {other:?}"),
+        }
     }
 
     #[test]
@@ -476,6 +3396,59 @@ mod tests {
         assert_eq!(value, "hello");
     }
 
+    #[test]
+    #[serial]
+    fn test_get_cloned_or_default() {
+        API.clear();
+        assert_eq!(API.get_cloned_or_default::<String>(), "");
+        API.register("hello".to_string());
+        assert_eq!(API.get_cloned_or_default::<String>(), "hello");
+    }
+
+    #[test]
+    #[serial]
+    fn test_take_owned() {
+        API.clear();
+        assert!(matches!(
+            API.take_owned::<String>(),
+            Err(RegistryError::TypeNotFound { .. })
+        ));
+
+        API.register("owned".to_string());
+        let held = API.get::<String>().unwrap();
+        assert_eq!(
+            API.take_owned::<String>(),
+            Err(RegistryError::StillShared {
+                type_name: "alloc::string::String",
+                strong_count: 2
+            })
+        );
+        assert!(API.contains::<String>().unwrap());
+
+        drop(held);
+        assert_eq!(API.take_owned::<String>().unwrap(), "owned");
+        assert!(!API.contains::<String>().unwrap());
+    }
+
+    #[test]
+    #[serial]
+    fn test_retain() {
+        API.clear();
+        API.register(1u8);
+        API.register(2u16);
+        API.register(3u32);
+        API.protect::<u16>().unwrap();
+
+        let removed = API.retain(|type_name| type_name == "u8");
+        assert_eq!(removed, ["u32"]);
+        assert!(API.contains::<u8>().unwrap());
+        assert!(API.contains::<u16>().unwrap());
+        assert!(!API.contains::<u32>().unwrap());
+
+        assert!(API.retain(|_| true).is_empty());
+        API.clear_all(true);
+    }
+
     // EDUCATIONAL: Memory leak test (commented out)
     //
     // This test demonstrates the memory leak in the get_ref() method above.
@@ -685,4 +3658,251 @@ mod tests {
         assert_eq!(*retrieved, 42);
         assert_eq!(Arc::strong_count(&clone), 3); // clone + registry + retrieved
     }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "contention-stats")]
+    fn test_contention_stats_counts_acquisitions() {
+        API.clear();
+        let before = API.contention_stats();
+
+        API.register(1u8);
+        let _ = API.get::<u8>();
+
+        let after = API.contention_stats();
+        assert_eq!(after.storage.acquisitions - before.storage.acquisitions, 2);
+        assert!(after.trace.acquisitions > before.trace.acquisitions);
+    }
+
+    #[test]
+    #[serial]
+    fn test_capacity_and_shrink_to_fit() {
+        API.clear();
+        API.shrink_to_fit();
+        assert_eq!(API.capacity(), 0);
+
+        API.register(1u8);
+        API.register(2u16);
+        API.register(3u32);
+        assert!(API.capacity() >= 3);
+
+        API.clear();
+        API.shrink_to_fit();
+        assert_eq!(API.capacity(), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_reserve() {
+        API.clear();
+        API.shrink_to_fit();
+
+        API.reserve(64);
+        assert!(API.capacity() >= 64);
+
+        API.register(1u8);
+        API.reserve(64);
+        assert!(API.capacity() >= 65);
+
+        API.clear();
+        API.shrink_to_fit();
+        assert_eq!(API.capacity(), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_approx_heap_bytes_grows_with_entries() {
+        API.clear();
+        API.shrink_to_fit();
+        assert_eq!(API.approx_heap_bytes(), 0);
+
+        API.register([0u8; 1024]);
+        assert!(API.approx_heap_bytes() > 1024);
+
+        API.clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_strong_count() {
+        API.clear();
+        assert_eq!(API.strong_count::<i64>(), None);
+
+        API.register(7i64);
+        assert_eq!(API.strong_count::<i64>(), Some(1));
+
+        let held: Arc<i64> = API.get().unwrap();
+        assert_eq!(API.strong_count::<i64>(), Some(2));
+
+        API.register(8i64);
+        assert_eq!(API.strong_count::<i64>(), Some(1));
+        assert_eq!(Arc::strong_count(&held), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_register_final_rejects_replacement() {
+        API.clear();
+        use std::sync::{Arc as StdArc, Mutex as StdMutex};
+        let events = StdArc::new(StdMutex::new(Vec::new()));
+        let events_clone = events.clone();
+
+        API.set_trace_callback(move |e| {
+            events_clone.lock().unwrap().push(format!("{}", e));
+        });
+
+        API.register(1u16);
+        API.register_final(2u16).unwrap();
+        API.register(3u16);
+
+        assert_eq!(*API.get::<u16>().unwrap(), 2);
+        assert_eq!(
+            API.try_register(4u16),
+            Err(RegistryError::FinalType { type_name: "u16" })
+        );
+        assert_eq!(
+            API.register_final(5u16),
+            Err(RegistryError::FinalType { type_name: "u16" })
+        );
+        assert!(events
+            .lock()
+            .unwrap()
+            .contains(&"register_rejected { type_name: u16 }".to_string()));
+
+        // Other types keep override semantics
+        API.try_register(1u32).unwrap();
+        API.try_register(2u32).unwrap();
+        assert_eq!(*API.get::<u32>().unwrap(), 2);
+
+        API.clear_trace_callback();
+
+        // clear() removes final values too
+        API.clear();
+        API.register(6u16);
+        assert_eq!(*API.get::<u16>().unwrap(), 6);
+    }
+
+    #[test]
+    #[serial]
+    fn test_clear_reports_removed_entries() {
+        API.clear();
+        API.register(1u8);
+        API.register(String::from("x"));
+        API.register(2i16);
+        API.protect::<i16>().unwrap();
+
+        let events = API.record_events();
+        let report = API.clear();
+        assert_eq!(report.removed, ["alloc::string::String", "u8"]);
+        assert_eq!(events.ops(), ["clear", "remove", "remove"]);
+        assert_eq!(events.events_for_type::<u8>().len(), 1);
+
+        API.clear_trace_callback();
+        assert_eq!(API.clear_all(true).removed, ["i16"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_protect_survives_clear() {
+        API.clear();
+        assert!(matches!(
+            API.protect::<i16>(),
+            Err(RegistryError::TypeNotFound { .. })
+        ));
+
+        API.register(1i16);
+        API.register(1i32);
+        API.protect::<i16>().unwrap();
+
+        API.register(2i16);
+        assert_eq!(*API.get::<i16>().unwrap(), 1);
+        assert_eq!(
+            API.try_register(3i16),
+            Err(RegistryError::ProtectedType { type_name: "i16" })
+        );
+        assert_eq!(
+            API.remove::<i16>().unwrap_err(),
+            RegistryError::ProtectedType { type_name: "i16" }
+        );
+
+        API.clear();
+        assert_eq!(*API.get::<i16>().unwrap(), 1);
+        assert!(!API.contains::<i32>().unwrap());
+
+        API.clear_all(true);
+        assert!(!API.contains::<i16>().unwrap());
+        API.register(4i16);
+        assert_eq!(*API.get::<i16>().unwrap(), 4);
+        API.clear();
+    }
+
+    #[test]
+    #[serial]
+    fn test_clear_with_leak_report() {
+        API.clear();
+        API.register(1u8);
+        API.register(2u16);
+        let held: Arc<u16> = API.get().unwrap();
+
+        let events = API.record_events();
+        let report = API.clear_with_leak_report();
+        API.clear_trace_callback();
+
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].type_name, "u16");
+        assert_eq!(report.entries[0].outstanding, 1);
+        assert!(!API.contains::<u8>().unwrap());
+        assert_eq!(*held, 2);
+
+        API.register(3i16);
+        API.protect::<i16>().unwrap();
+        API.clear_with_leak_report();
+        assert_eq!(*API.get::<i16>().unwrap(), 3);
+        API.clear_all(true);
+
+        assert_eq!(events.ops(), ["clear", "remove", "remove", "leak"]);
+        assert_eq!(
+            events.events()[3].to_string(),
+            "leak { type_name: u16, outstanding: 1 }"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_remove() {
+        API.clear();
+        use std::sync::{Arc as StdArc, Mutex as StdMutex};
+        let events = StdArc::new(StdMutex::new(Vec::new()));
+        let events_clone = events.clone();
+
+        API.register(5u8);
+        API.register_final(6u16).unwrap();
+        API.set_trace_callback(move |e| {
+            events_clone.lock().unwrap().push(format!("{}", e));
+        });
+
+        let removed: Arc<u8> = API.remove().unwrap();
+        let missing = API.remove::<u8>();
+        let final_type = API.remove::<u16>();
+        API.clear_trace_callback();
+
+        assert_eq!(*removed, 5);
+        assert_eq!(
+            missing,
+            Err(RegistryError::TypeNotFound {
+                type_name: "u8",
+                location: None,
+                suggestions: Vec::new(),
+            })
+        );
+        assert_eq!(
+            final_type,
+            Err(RegistryError::FinalType { type_name: "u16" })
+        );
+        assert!(!API.contains::<u8>().unwrap());
+        assert!(API.contains::<u16>().unwrap());
+
+        let captured = events.lock().unwrap().clone();
+        assert_eq!(captured, vec!["remove { type_name: u8 }".to_string()]);
+    }
 }
@@ -0,0 +1,532 @@
+//! Provider-based dependency resolution.
+//!
+//! `register_provider::<T>(provider)` describes how to build `T` from other
+//! registry entries without building it. `resolve::<T>()` returns the registered
+//! value or runs the provider, which pulls its own dependencies through the
+//! [`ResolveCtx`] it receives. The result is registered like any other value,
+//! so the provider runs once and plain `get()` sees it afterwards.
+//!
+//! The context carries the chain of types under construction. Requesting a type
+//! that is already in the chain fails with `RegistryError::DependencyCycle`
+//! instead of recursing forever. Async providers (`register_provider_async`,
+//! features `async`/`tokio`) share the same context, so cycles spanning sync
+//! and async providers are caught too.
+//!
+//! `resolve_all::<(A, B, C)>()` resolves several dependencies at once and keeps
+//! going past missing ones, failing with a single
+//! `RegistryError::MissingDependencies` that lists all of them.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+#[cfg(any(feature = "async", feature = "tokio"))]
+use std::future::Future;
+#[cfg(any(feature = "async", feature = "tokio"))]
+use std::pin::Pin;
+
+use crate::registry_trait::{lookup, store};
+use crate::sync::Mutex;
+use crate::{EventMeta, MissingDependency, RegistryApi, RegistryError, RegistryEvent};
+
+type SyncProvider<R, T> = Box<dyn Fn(&ResolveCtx<R>) -> Result<T, RegistryError> + Send + Sync>;
+
+#[cfg(any(feature = "async", feature = "tokio"))]
+type AsyncProvider<R, T> = Box<
+    dyn Fn(ResolveCtx<R>) -> Pin<Box<dyn Future<Output = Result<T, RegistryError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// How to build a `T` for registry `R`.
+pub(crate) enum Provider<R, T> {
+    Sync(SyncProvider<R, T>),
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    Async(AsyncProvider<R, T>),
+}
+
+/// Registered providers per type, stored type-erased as `Arc<Provider<R, T>>`.
+#[derive(Default)]
+pub(crate) struct Providers {
+    providers: Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl std::fmt::Debug for Providers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let providers = self.providers.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("Providers")
+            .field("registered", &providers.len())
+            .finish()
+    }
+}
+
+impl Providers {
+    /// Install the provider for `T`, replacing any previous one.
+    pub(crate) fn insert<R: 'static, T: Send + Sync + 'static>(&self, provider: Provider<R, T>) {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(TypeId::of::<T>(), Arc::new(provider));
+    }
+
+    /// The provider for `T`, if any.
+    fn get<R: 'static, T: Send + Sync + 'static>(&self) -> Option<Arc<Provider<R, T>>> {
+        self.providers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|p| p.downcast::<Provider<R, T>>().ok())
+    }
+}
+
+/// Resolution context handed to providers.
+///
+/// Providers request their dependencies through it, so the registry can track
+/// which types are under construction and report dependency cycles.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(services);
+///
+/// struct Config(String);
+/// struct Db(String);
+///
+/// services::register(Config("postgres://localhost".into()));
+/// services::register_provider(|ctx| {
+///     let config = ctx.resolve::<Config>()?;
+///     Ok(Db(config.0.clone()))
+/// });
+///
+/// let db = services::resolve::<Db>().unwrap();
+/// assert_eq!(db.0, "postgres://localhost");
+/// ```
+pub struct ResolveCtx<R> {
+    chain: Vec<(TypeId, &'static str)>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<R> ResolveCtx<R> {
+    /// A context for building `T` as a dependency of the types in `chain`.
+    fn enter<T: 'static>(chain: &[(TypeId, &'static str)]) -> Result<Self, RegistryError> {
+        let type_id = TypeId::of::<T>();
+        let type_name = std::any::type_name::<T>();
+
+        if let Some(start) = chain.iter().position(|(id, _)| *id == type_id) {
+            let mut cycle: Vec<&'static str> = chain[start..].iter().map(|(_, n)| *n).collect();
+            cycle.push(type_name);
+            return Err(RegistryError::DependencyCycle { cycle });
+        }
+
+        let mut chain = chain.to_vec();
+        chain.push((type_id, type_name));
+        Ok(Self {
+            chain,
+            _registry: PhantomData,
+        })
+    }
+
+    /// Names of the types under construction, outermost first.
+    pub fn chain(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.chain.iter().map(|(_, name)| *name)
+    }
+}
+
+impl<R: RegistryApi + Default + 'static> ResolveCtx<R> {
+    /// Resolve a dependency, running its provider if it is not registered yet.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::DependencyCycle` if `T` is already under construction
+    /// - `RegistryError::ProvidedAsync` if `T` only has an async provider
+    /// - Any error returned by the dependency's own provider
+    /// - Type `T` is neither registered nor provided
+    pub fn resolve<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        resolve_in::<R, T>(&self.chain)
+    }
+
+    /// Resolve a tuple of dependencies, reporting every missing one at once.
+    ///
+    /// ```rust
+    /// use singleton_registry::{define_registry, RegistryError};
+    ///
+    /// define_registry!(services);
+    ///
+    /// struct Config;
+    /// struct Cache;
+    /// #[derive(Debug)]
+    /// struct Db;
+    ///
+    /// services::register_provider(|ctx| {
+    ///     let (_config, _cache) = ctx.resolve_all::<(Config, Cache)>()?;
+    ///     Ok(Db)
+    /// });
+    ///
+    /// let err = services::resolve::<Db>().unwrap_err();
+    /// let RegistryError::MissingDependencies { missing } = err else { panic!() };
+    /// assert_eq!(missing.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::MissingDependencies` listing every dependency that is
+    ///   neither registered nor provided, including those missing further down
+    /// - Any other error of the first dependency failing otherwise
+    pub fn resolve_all<D: Dependencies<R>>(&self) -> Result<D::Output, RegistryError> {
+        D::resolve_all_in(&self.chain)
+    }
+
+    /// Resolve a dependency, awaiting its provider if it is not registered yet.
+    ///
+    /// Runs both sync and async providers.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`resolve`](ResolveCtx::resolve), except that async providers are run.
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    pub fn resolve_async<T: Send + Sync + 'static>(
+        &self,
+    ) -> impl Future<Output = Result<Arc<T>, RegistryError>> + Send
+    where
+        R: Send + Sync,
+    {
+        resolve_in_async::<R, T>(self.chain.clone())
+    }
+}
+
+impl<R> std::fmt::Debug for ResolveCtx<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.chain()).finish()
+    }
+}
+
+/// Register a freshly built value, unless a racing resolver registered one first.
+fn keep<R: RegistryApi, T: Send + Sync + 'static>(api: &R, value: Arc<T>) -> Arc<T> {
+    let init_lock = R::state().init_locks.for_type(TypeId::of::<T>());
+    let _init = init_lock.lock().unwrap_or_else(|p| p.into_inner());
+
+    if let Some(existing) = lookup::<R, T>() {
+        return existing;
+    }
+
+    match store(api, value.clone(), |e| e) {
+        Ok(()) => value,
+        Err(_) => lookup::<R, T>().unwrap_or(value),
+    }
+}
+
+/// Emit the `Get` event for a value that was found or built.
+fn emit_found<R: RegistryApi, T>(api: &R) {
+    api.emit_event(&RegistryEvent::Get {
+        type_name: std::any::type_name::<T>(),
+        found: true,
+        meta: EventMeta::default(),
+    });
+}
+
+/// Resolve `T` as a dependency of the types in `chain`.
+pub(crate) fn resolve_in<R, T>(chain: &[(TypeId, &'static str)]) -> Result<Arc<T>, RegistryError>
+where
+    R: RegistryApi + Default + 'static,
+    T: Send + Sync + 'static,
+{
+    let api = R::default();
+    let Some(provider) = R::state().providers.get::<R, T>() else {
+        return api.get::<T>();
+    };
+
+    if let Some(value) = lookup::<R, T>() {
+        emit_found::<R, T>(&api);
+        return Ok(value);
+    }
+
+    let ctx = ResolveCtx::<R>::enter::<T>(chain)?;
+    let value = match &*provider {
+        Provider::Sync(build) => build(&ctx)?,
+        #[cfg(any(feature = "async", feature = "tokio"))]
+        Provider::Async(_) => {
+            return Err(RegistryError::ProvidedAsync {
+                type_name: std::any::type_name::<T>(),
+            })
+        }
+    };
+
+    let value = keep(&api, Arc::new(value));
+    emit_found::<R, T>(&api);
+    Ok(value)
+}
+
+/// A tuple of dependency types resolved together by `resolve_all`.
+///
+/// Implemented for tuples of up to eight types; `Output` is the tuple of their
+/// `Arc`s.
+pub trait Dependencies<R>: Sized {
+    /// The resolved values, `(Arc<A>, Arc<B>, ...)`.
+    type Output;
+
+    #[doc(hidden)]
+    fn resolve_all_in(chain: &[(TypeId, &'static str)]) -> Result<Self::Output, RegistryError>;
+}
+
+/// Resolve `T`, recording it in `missing` instead of failing if it is absent.
+fn resolve_or_record<R, T>(
+    chain: &[(TypeId, &'static str)],
+    missing: &mut Vec<MissingDependency>,
+) -> Result<Option<Arc<T>>, RegistryError>
+where
+    R: RegistryApi + Default + 'static,
+    T: Send + Sync + 'static,
+{
+    match resolve_in::<R, T>(chain) {
+        Ok(value) => Ok(Some(value)),
+        Err(RegistryError::TypeNotFound { type_name, .. })
+        | Err(RegistryError::TypeMismatch { type_name }) => {
+            missing.push(MissingDependency {
+                type_name,
+                required_by: chain.last().map(|(_, name)| *name),
+            });
+            Ok(None)
+        }
+        Err(RegistryError::MissingDependencies { missing: nested }) => {
+            missing.extend(nested);
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+macro_rules! impl_dependencies {
+    ($($T:ident),+) => {
+        impl<R, $($T),+> Dependencies<R> for ($($T,)+)
+        where
+            R: RegistryApi + Default + 'static,
+            $($T: Send + Sync + 'static),+
+        {
+            type Output = ($(Arc<$T>,)+);
+
+            #[allow(non_snake_case)]
+            fn resolve_all_in(
+                chain: &[(TypeId, &'static str)],
+            ) -> Result<Self::Output, RegistryError> {
+                let mut missing = Vec::new();
+                $(let $T = resolve_or_record::<R, $T>(chain, &mut missing)?;)+
+                match ($($T,)+) {
+                    ($(Some($T),)+) => Ok(($($T,)+)),
+                    _ => Err(RegistryError::MissingDependencies { missing }),
+                }
+            }
+        }
+    };
+}
+
+impl_dependencies!(A);
+impl_dependencies!(A, B);
+impl_dependencies!(A, B, C);
+impl_dependencies!(A, B, C, D);
+impl_dependencies!(A, B, C, D, E);
+impl_dependencies!(A, B, C, D, E, F);
+impl_dependencies!(A, B, C, D, E, F, G);
+impl_dependencies!(A, B, C, D, E, F, G, H);
+
+/// Resolve `T` as a dependency of the types in `chain`, awaiting async providers.
+#[cfg(any(feature = "async", feature = "tokio"))]
+pub(crate) async fn resolve_in_async<R, T>(
+    chain: Vec<(TypeId, &'static str)>,
+) -> Result<Arc<T>, RegistryError>
+where
+    R: RegistryApi + Default + Send + Sync + 'static,
+    T: Send + Sync + 'static,
+{
+    let api = R::default();
+    let Some(provider) = R::state().providers.get::<R, T>() else {
+        return api.get_async::<T>().await;
+    };
+
+    if let Some(value) = lookup::<R, T>() {
+        emit_found::<R, T>(&api);
+        return Ok(value);
+    }
+
+    let ctx = ResolveCtx::<R>::enter::<T>(&chain)?;
+    let value = match &*provider {
+        Provider::Sync(build) => build(&ctx)?,
+        Provider::Async(build) => build(ctx).await?,
+    };
+
+    let value = keep(&api, Arc::new(value));
+    emit_found::<R, T>(&api);
+    Ok(value)
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Config(&'static str);
+    #[derive(Debug)]
+    struct Db(&'static str);
+    #[derive(Debug)]
+    struct Repo(Arc<Db>);
+
+    #[test]
+    fn test_resolve_builds_dependencies_once() {
+        define_registry!(reg);
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        reg::register(Config("url"));
+        reg::register_provider(|ctx| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(Db(ctx.resolve::<Config>()?.0))
+        });
+        reg::register_provider(|ctx| Ok(Repo(ctx.resolve::<Db>()?)));
+
+        let repo = reg::resolve::<Repo>().unwrap();
+        assert_eq!(repo.0 .0, "url");
+        assert!(Arc::ptr_eq(&repo.0, &reg::resolve::<Db>().unwrap()));
+        assert!(Arc::ptr_eq(&repo, &reg::get::<Repo>().unwrap()));
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_resolve_prefers_registered_value() {
+        define_registry!(reg);
+
+        reg::register_provider(|_| -> Result<Db, RegistryError> { panic!("must not run") });
+        reg::register(Db("registered"));
+        assert_eq!(reg::resolve::<Db>().unwrap().0, "registered");
+    }
+
+    #[test]
+    fn test_missing_dependency_propagates() {
+        define_registry!(reg);
+
+        reg::register_provider(|ctx| Ok(Db(ctx.resolve::<Config>()?.0)));
+        assert!(matches!(
+            reg::resolve::<Db>().unwrap_err(),
+            RegistryError::TypeNotFound { type_name, .. }
+                if type_name == std::any::type_name::<Config>()
+        ));
+        assert!(!reg::contains::<Db>().unwrap());
+    }
+
+    #[test]
+    fn test_resolve_all_reports_every_missing_dependency() {
+        use crate::MissingDependency;
+
+        define_registry!(reg);
+
+        #[derive(Debug)]
+        struct Cache;
+        #[derive(Debug)]
+        struct Service;
+        reg::register_provider(|ctx| {
+            let (config, _cache) = ctx.resolve_all::<(Config, Cache)>()?;
+            Ok(Db(config.0))
+        });
+
+        let err = reg::resolve_all::<(Db, Service)>().unwrap_err();
+        let name = std::any::type_name::<Db>();
+        assert_eq!(
+            err,
+            RegistryError::MissingDependencies {
+                missing: vec![
+                    MissingDependency {
+                        type_name: std::any::type_name::<Config>(),
+                        required_by: Some(name),
+                    },
+                    MissingDependency {
+                        type_name: std::any::type_name::<Cache>(),
+                        required_by: Some(name),
+                    },
+                    MissingDependency {
+                        type_name: std::any::type_name::<Service>(),
+                        required_by: None,
+                    },
+                ]
+            }
+        );
+
+        reg::register(Config("url"));
+        reg::register(Cache);
+        reg::register(Service);
+        let (db, _service) = reg::resolve_all::<(Db, Service)>().unwrap();
+        assert_eq!(db.0, "url");
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        define_registry!(reg);
+
+        reg::register_provider(|ctx| Ok(Db(ctx.resolve::<Config>()?.0)));
+        reg::register_provider(|ctx| Ok(Config(ctx.resolve::<Db>()?.0)));
+
+        let err = reg::resolve::<Db>().unwrap_err();
+        assert_eq!(
+            err,
+            RegistryError::DependencyCycle {
+                cycle: vec![
+                    std::any::type_name::<Db>(),
+                    std::any::type_name::<Config>(),
+                    std::any::type_name::<Db>(),
+                ]
+            }
+        );
+    }
+
+    #[cfg(any(feature = "async", feature = "tokio"))]
+    #[test]
+    fn test_sync_resolve_rejects_async_provider() {
+        define_registry!(reg);
+
+        reg::register_provider_async(|_| async { Ok(Db("async")) });
+        assert_eq!(
+            reg::resolve::<Db>().unwrap_err(),
+            RegistryError::ProvidedAsync {
+                type_name: std::any::type_name::<Db>()
+            }
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_resolve_async_awaits_dependencies() {
+        define_registry!(reg);
+
+        reg::register(Config("url"));
+        reg::register_provider_async(|ctx| async move {
+            tokio::task::yield_now().await;
+            Ok(Db(ctx.resolve_async::<Config>().await?.0))
+        });
+        reg::register_provider(|ctx| Ok(Repo(ctx.resolve::<Db>()?)));
+
+        // The sync provider for `Repo` cannot build its async dependency
+        assert!(reg::resolve::<Repo>().is_err());
+
+        let db = reg::resolve_async::<Db>().await.unwrap();
+        assert_eq!(db.0, "url");
+        let repo = reg::resolve_async::<Repo>().await.unwrap();
+        assert!(Arc::ptr_eq(&repo.0, &db));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_cycle_across_sync_and_async_providers() {
+        define_registry!(reg);
+
+        reg::register_provider_async(|ctx| async move {
+            Ok(Db(ctx.resolve_async::<Config>().await?.0))
+        });
+        reg::register_provider(|ctx| Ok(Config(ctx.resolve::<Db>()?.0)));
+
+        let err = reg::resolve_async::<Db>().await.unwrap_err();
+        assert!(matches!(err, RegistryError::DependencyCycle { .. }));
+    }
+}
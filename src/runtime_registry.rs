@@ -0,0 +1,513 @@
+//! Registries created at runtime.
+//!
+//! `define_registry!` registries are statics: one per module, alive for the
+//! whole process. A [`Registry`] owns its storage instead, so a test or a
+//! tenant can create a fresh one with `Registry::new()` and drop it, with its
+//! values, when done.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::{Arc, LockResult};
+
+use crate::registry_trait::{
+    contains_entry, emit_via, get_entry, parent_link, protect_entry, remove_entry, store,
+    store_entry, store_lazy, RegistryCore, StorageMap, TraceSlot,
+};
+use crate::sync::{Mutex, MutexGuard};
+use crate::{
+    CallbackPanicPolicy, ChangeListenerGuard, DynRegistry, EventLevel, EventSampling, RegistryApi,
+    RegistryConfig, RegistryEntry, RegistryError, RegistryEvent, RegistryState,
+};
+
+/// A registry with owned storage, created and dropped at runtime.
+///
+/// Offers the core operations of [`RegistryApi`] as instance methods, backed
+/// by the same implementation: final, protected and append-only values are
+/// rejected alike, lazy providers, change listeners and waiters run, and
+/// events pass the trace level, sampling and callback panic policy before
+/// reaching the registry's own trace callback, its parent, the `tracing`
+/// bridge and the global trace sink. Operations tied to a static registry
+/// (history, timelines, snapshots, scopes, the file backend, ...) are only
+/// available on `define_registry!` registries.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::Registry;
+///
+/// let tenant = Registry::named("tenant-a");
+/// tenant.register(String::from("eu-west-1"));
+///
+/// assert_eq!(*tenant.get::<String>().unwrap(), "eu-west-1");
+/// assert!(!Registry::new().contains::<String>().unwrap());
+/// ```
+pub struct Registry {
+    name: &'static str,
+    config: RegistryConfig,
+    storage: Mutex<StorageMap>,
+    trace: Mutex<TraceSlot>,
+    state: RegistryState,
+}
+
+impl Registry {
+    /// Create an empty registry named `"registry"`.
+    pub fn new() -> Self {
+        Self::named("registry")
+    }
+
+    /// Create an empty registry reporting `name` in its events.
+    pub fn named(name: &'static str) -> Self {
+        Self::with_config(name, RegistryConfig::new())
+    }
+
+    /// Create an empty registry named `name` with the options that
+    /// `define_registry!` takes, e.g. append-only registration.
+    pub fn with_config(name: &'static str, config: RegistryConfig) -> Self {
+        Self {
+            name,
+            config,
+            storage: Mutex::new(HashMap::with_capacity(config.capacity)),
+            trace: Mutex::new(None),
+            state: RegistryState::new(),
+        }
+    }
+
+    /// Name of the registry, reported in the `registry` field of every event.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The registry's configuration.
+    pub fn config(&self) -> &RegistryConfig {
+        &self.config
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Tracing
+    // -------------------------------------------------------------------------------------------------
+
+    /// Set a tracing callback for the registry's operations.
+    pub fn set_trace_callback(&self, callback: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
+        let mut guard = self.lock_trace().unwrap_or_else(|p| p.into_inner());
+        self.state.callback_panics.reset();
+        *guard = Some(Arc::new(callback));
+    }
+
+    /// Clear the tracing callback.
+    pub fn clear_trace_callback(&self) {
+        *self.lock_trace().unwrap_or_else(|p| p.into_inner()) = None;
+    }
+
+    /// Emit only events at `level` or above, see [`RegistryApi::set_trace_level`].
+    pub fn set_trace_level(&self, level: EventLevel) {
+        self.state.trace_level.set(level);
+    }
+
+    /// Sample lookup events, see [`RegistryApi::set_event_sampling`].
+    pub fn set_event_sampling(&self, sampling: EventSampling) {
+        self.state.event_sampler.configure(sampling);
+    }
+
+    /// Decide what happens when the trace callback panics, see
+    /// [`RegistryApi::set_callback_panic_policy`].
+    pub fn set_callback_panic_policy(&self, policy: CallbackPanicPolicy) {
+        self.state.callback_panics.set(policy);
+    }
+
+    /// Forward every event of this registry to the registry `P`, see
+    /// [`RegistryApi::forward_events_to`].
+    ///
+    /// # Panics
+    ///
+    /// If `P` already forwards, directly or through other registries, to this
+    /// registry.
+    pub fn forward_events_to<P: RegistryApi>(&self) {
+        self.state.event_parent.set(&self.state, parent_link::<P>());
+    }
+
+    /// Stop forwarding events to the parent registry.
+    pub fn stop_forwarding_events(&self) {
+        self.state.event_parent.clear();
+    }
+
+    /// Acquire the trace lock.
+    #[cfg(not(feature = "contention-stats"))]
+    fn lock_trace(&self) -> LockResult<MutexGuard<'_, TraceSlot>> {
+        self.trace.lock()
+    }
+
+    /// Acquire the trace lock, recording contention.
+    #[cfg(feature = "contention-stats")]
+    fn lock_trace(&self) -> LockResult<MutexGuard<'_, TraceSlot>> {
+        self.state.trace_lock.acquire(&self.trace)
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Registry
+    // -------------------------------------------------------------------------------------------------
+
+    /// Register a value, replacing any previous value of the same type.
+    ///
+    /// A rejected value is reported via `RegisterRejected`; use
+    /// [`try_register`](Self::try_register) to get the error.
+    #[track_caller]
+    pub fn register<T: Send + Sync + 'static>(&self, value: T) {
+        self.register_arc(Arc::new(value));
+    }
+
+    /// Register an Arc-wrapped value.
+    #[track_caller]
+    pub fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        let _ = store(self, value, |e| e);
+    }
+
+    /// Register a value, reporting a rejection.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::FinalType` / `ProtectedType` if the registered value of `T`
+    ///   can't be replaced
+    /// - `RegistryError::AlreadyRegistered` if the registry is append-only
+    #[track_caller]
+    pub fn try_register<T: Send + Sync + 'static>(&self, value: T) -> Result<(), RegistryError> {
+        store(self, Arc::new(value), |e| e)
+    }
+
+    /// Register a value that can never be replaced, see [`RegistryApi::register_final`].
+    ///
+    /// # Errors
+    ///
+    /// As [`try_register`](Self::try_register).
+    #[track_caller]
+    pub fn register_final<T: Send + Sync + 'static>(&self, value: T) -> Result<(), RegistryError> {
+        store(self, Arc::new(value), RegistryEntry::into_final)
+    }
+
+    /// Protect the registered value of `T` from being replaced or removed, see
+    /// [`RegistryApi::protect`].
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::TypeNotFound` if `T` is not registered
+    pub fn protect<T: Send + Sync + 'static>(&self) -> Result<(), RegistryError> {
+        protect_entry(self, TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// Register a factory building `T` on its first retrieval, see
+    /// [`RegistryApi::register_lazy`].
+    #[track_caller]
+    pub fn register_lazy<T: Send + Sync + 'static>(
+        &self,
+        factory: impl FnOnce() -> T + Send + 'static,
+    ) {
+        store_lazy(self, factory);
+    }
+
+    /// Call `callback` with every value registered for `T` from now on, see
+    /// [`RegistryApi::on_change`].
+    pub fn on_change<T: Send + Sync + 'static>(
+        &self,
+        callback: impl Fn(&Arc<T>) + Send + Sync + 'static,
+    ) -> ChangeListenerGuard {
+        self.state.change_listeners.add::<T, _>(callback)
+    }
+
+    /// Retrieve a value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the registry
+    /// - Type mismatch (extremely rare)
+    /// - Registry lock is poisoned, unless the poison policy is `Recover`
+    #[track_caller]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        get_entry(self, TypeId::of::<T>(), type_name, Some(Location::caller()))?
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Retrieve a cloned value from the registry.
+    ///
+    /// # Errors
+    ///
+    /// As [`get`](Self::get).
+    #[track_caller]
+    pub fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.get::<T>().map(|arc| (*arc).clone())
+    }
+
+    /// Retrieve a value from the registry, returning `None` if not registered.
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Check if a type is registered, or has a pending lazy provider.
+    ///
+    /// # Errors
+    ///
+    /// - Registry lock is poisoned, unless the poison policy is `Recover`
+    pub fn contains<T: Send + Sync + 'static>(&self) -> Result<bool, RegistryError> {
+        contains_entry(self, TypeId::of::<T>(), std::any::type_name::<T>())
+    }
+
+    /// Remove the value of `T` from the registry and return it.
+    ///
+    /// # Errors
+    ///
+    /// - `RegistryError::TypeNotFound` if `T` is not registered
+    /// - `RegistryError::FinalType` / `ProtectedType` if the entry can't be removed
+    #[track_caller]
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        remove_entry(self, TypeId::of::<T>(), type_name, Some(Location::caller()))?
+            .value()
+            .clone()
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Number of registered values.
+    pub fn len(&self) -> usize {
+        self.storage.lock().unwrap_or_else(|p| p.into_inner()).len()
+    }
+
+    /// Returns `true` if no value is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// The shared core, reached through the instance's fields
+// -------------------------------------------------------------------------------------------------
+
+impl RegistryCore for Registry {
+    #[cfg(not(feature = "contention-stats"))]
+    fn core_storage(&self) -> LockResult<MutexGuard<'_, StorageMap>> {
+        self.storage.lock()
+    }
+
+    #[cfg(feature = "contention-stats")]
+    fn core_storage(&self) -> LockResult<MutexGuard<'_, StorageMap>> {
+        self.state.storage_lock.acquire(&self.storage)
+    }
+
+    fn core_state(&self) -> &RegistryState {
+        &self.state
+    }
+
+    fn core_config(&self) -> &RegistryConfig {
+        &self.config
+    }
+
+    fn core_emit(&self, event: &RegistryEvent) {
+        emit_via(
+            self.name,
+            &self.config,
+            &self.state,
+            || self.lock_trace(),
+            event,
+        );
+    }
+}
+
+impl DynRegistry for Registry {
+    fn registry_name(&self) -> &'static str {
+        self.name
+    }
+
+    fn register_entry(&self, type_id: TypeId, entry: RegistryEntry) -> Result<(), RegistryError> {
+        store_entry(self, type_id, entry, false)
+    }
+
+    fn get_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+        get_entry(self, type_id, type_name, None)
+    }
+
+    fn contains_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<bool, RegistryError> {
+        contains_entry(self, type_id, type_name)
+    }
+
+    fn remove_any(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Result<Arc<dyn Any + Send + Sync>, RegistryError> {
+        remove_entry(self, type_id, type_name, None).map(|entry| entry.value().clone())
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("name", &self.name)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registries_are_independent() {
+        let a = Registry::new();
+        let b = Registry::new();
+
+        a.register(1u8);
+        assert_eq!(*a.get::<u8>().unwrap(), 1);
+        assert_eq!(b.contains::<u8>(), Ok(false));
+        assert!(b.try_get::<u8>().is_none());
+        assert_eq!((a.len(), b.len()), (1, 0));
+    }
+
+    #[test]
+    fn test_drop_releases_values() {
+        let value = Arc::new(String::from("tenant"));
+        let registry = Registry::new();
+        registry.register_arc(value.clone());
+        assert_eq!(Arc::strong_count(&value), 2);
+
+        drop(registry);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_remove_and_events() {
+        let registry = Registry::named("tenant");
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        registry.set_trace_callback(move |event| {
+            seen.lock()
+                .unwrap()
+                .push(format!("[{}] {}", event.registry(), event));
+        });
+
+        registry.register(1u8);
+        assert_eq!(*registry.remove::<u8>().unwrap(), 1);
+        assert!(registry.remove::<u8>().is_err());
+        assert!(registry.is_empty());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "[tenant] register { type_name: u8 }",
+                "[tenant] register_completed { type_name: u8 }",
+                "[tenant] remove { type_name: u8 }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_final_protected_and_append_only() {
+        let registry = Registry::new();
+        registry.register_final(1u8).unwrap();
+        assert!(matches!(
+            registry.try_register(2u8),
+            Err(RegistryError::FinalType { .. })
+        ));
+
+        registry.register(1u16);
+        registry.protect::<u16>().unwrap();
+        registry.register(2u16);
+        assert_eq!(*registry.get::<u16>().unwrap(), 1);
+        assert!(matches!(
+            registry.remove::<u16>(),
+            Err(RegistryError::ProtectedType { .. })
+        ));
+
+        let append_only = Registry::with_config(
+            "append-only",
+            RegistryConfig::new().with_registration(crate::RegistrationMode::AppendOnly),
+        );
+        append_only.register(1u32);
+        assert!(matches!(
+            append_only.try_register(2u32),
+            Err(RegistryError::AlreadyRegistered { .. })
+        ));
+        assert_eq!(*append_only.get::<u32>().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_lazy_provider_and_change_listener() {
+        let registry = Registry::new();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let guard = registry.on_change::<u8>(move |value| sink.lock().unwrap().push(**value));
+
+        registry.register_lazy(|| 7u8);
+        assert_eq!(registry.contains::<u8>(), Ok(true));
+        assert_eq!(*registry.get::<u8>().unwrap(), 7);
+        drop(guard);
+        registry.register(8u8);
+
+        assert_eq!(*seen.lock().unwrap(), [7]);
+    }
+
+    #[test]
+    fn test_listener_guard_outlives_registry() {
+        let registry = Registry::new();
+        let guard = registry.on_change::<u8>(|_| {});
+        drop(registry);
+        drop(guard);
+    }
+
+    #[test]
+    fn test_trace_level() {
+        let registry = Registry::new();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        registry.set_trace_callback(move |event| seen.lock().unwrap().push(event.to_string()));
+
+        registry.set_trace_level(EventLevel::Info);
+        registry.register(1u8);
+        let _ = registry.get::<u8>();
+        let _ = registry.remove::<u8>();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "register { type_name: u8 }",
+                "register_completed { type_name: u8 }",
+                "remove { type_name: u8 }",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_type_reports_caller() {
+        let registry = Registry::new();
+        let line = line!() + 1;
+        match registry.get::<u8>() {
+            Err(RegistryError::TypeNotFound { location, .. }) => {
+                assert_eq!(
+                    location.map(|l| (l.file(), l.line())),
+                    Some((file!(), line))
+                );
+            }
+            other => panic!("expected TypeNotFound, got {other:?}"),
+        }
+    }
+}
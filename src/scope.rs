@@ -0,0 +1,311 @@
+//! Namespaced views of a registry.
+//!
+//! Plugins sharing a registry would overwrite each other's values of the same
+//! type. `scope("plugin-a")` returns a [`Scope`] whose values are keyed by
+//! `(scope, TypeId)`: each scope sees only its own registrations, while the
+//! registry's trace pipeline reports them all, tagged with
+//! [`EventMeta::scope`](crate::EventMeta::scope).
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::sync::Mutex;
+use crate::{EventMeta, RegistryApi, RegistryEntry, RegistryError, RegistryEvent};
+
+/// Values registered through scopes, keyed by `(scope, TypeId)`.
+#[derive(Default)]
+pub(crate) struct ScopedEntries {
+    entries: Mutex<HashMap<(&'static str, TypeId), RegistryEntry>>,
+}
+
+impl std::fmt::Debug for ScopedEntries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("ScopedEntries")
+            .field("entries", &entries.len())
+            .finish()
+    }
+}
+
+impl ScopedEntries {
+    /// Store `entry`, returning the entry it replaces.
+    fn insert(
+        &self,
+        scope: &'static str,
+        type_id: TypeId,
+        entry: RegistryEntry,
+    ) -> Option<RegistryEntry> {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let entry = entry.succeeding(entries.get(&(scope, type_id)));
+        entries.insert((scope, type_id), entry)
+    }
+
+    fn get(&self, scope: &'static str, type_id: TypeId) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(&(scope, type_id))
+            .map(|entry| entry.value().clone())
+    }
+
+    fn remove(&self, scope: &'static str, type_id: TypeId) -> Option<RegistryEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&(scope, type_id))
+    }
+
+    fn len(&self, scope: &'static str) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .keys()
+            .filter(|(s, _)| *s == scope)
+            .count()
+    }
+
+    /// Remove the entries of every scope.
+    pub(crate) fn drain(&self) -> Vec<RegistryEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .drain()
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+/// A namespaced view of a registry, created with
+/// [`RegistryApi::scope`](crate::RegistryApi::scope).
+///
+/// Values registered through the view are isolated from the registry's own
+/// values and from other scopes; views with the same name share their values.
+/// Operations emit the registry's usual events with `meta.scope` set.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(plugins);
+///
+/// plugins::scope("plugin-a").register(String::from("a"));
+/// plugins::scope("plugin-b").register(String::from("b"));
+///
+/// assert_eq!(*plugins::scope("plugin-a").get::<String>().unwrap(), "a");
+/// assert!(!plugins::contains::<String>().unwrap());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Scope<R> {
+    api: R,
+    name: &'static str,
+}
+
+impl<R: RegistryApi> Scope<R> {
+    pub(crate) fn new(api: R, name: &'static str) -> Self {
+        Self { api, name }
+    }
+
+    /// Name of the scope.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Register a value in the scope, replacing any previous value of the same type.
+    #[track_caller]
+    pub fn register<T: Send + Sync + 'static>(&self, value: T) {
+        self.register_arc(Arc::new(value));
+    }
+
+    /// Register an Arc-wrapped value in the scope.
+    #[track_caller]
+    pub fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        let location = Location::caller();
+        let type_name = std::any::type_name::<T>();
+        self.api.emit_event(&RegistryEvent::Register {
+            type_name,
+            meta: EventMeta::at(location).in_scope(self.name),
+        });
+
+        let started = Instant::now();
+        let entry = RegistryEntry::new(value).at(location);
+        let previous = R::state()
+            .scoped
+            .insert(self.name, TypeId::of::<T>(), entry);
+
+        self.api.emit_event(&RegistryEvent::RegisterCompleted {
+            type_name,
+            replaced: previous.is_some(),
+            rollback: false,
+            meta: EventMeta::timed(started)
+                .with_location(location)
+                .in_scope(self.name),
+        });
+        // The replaced value is dropped last, with no lock held
+        drop(previous);
+    }
+
+    /// Retrieve a value registered in the scope.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered in the scope
+    /// - Type mismatch (extremely rare)
+    #[track_caller]
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let started = Instant::now();
+        let type_name = std::any::type_name::<T>();
+        let result = match R::state().scoped.get(self.name, TypeId::of::<T>()) {
+            Some(value) => value
+                .downcast::<T>()
+                .map_err(|_| RegistryError::TypeMismatch { type_name }),
+            None => Err(RegistryError::TypeNotFound {
+                type_name,
+                location: Some(Location::caller()),
+                suggestions: Vec::new(),
+            }),
+        };
+
+        self.api.emit_event(&RegistryEvent::Get {
+            type_name,
+            found: result.is_ok(),
+            meta: EventMeta::timed(started).in_scope(self.name),
+        });
+
+        result
+    }
+
+    /// Retrieve a cloned value registered in the scope.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not registered in the scope
+    /// - Type mismatch
+    #[track_caller]
+    pub fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.get::<T>().map(|arc| (*arc).clone())
+    }
+
+    /// Retrieve a value registered in the scope, returning `None` if not registered.
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Check if a type is registered in the scope.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        let found = R::state()
+            .scoped
+            .get(self.name, TypeId::of::<T>())
+            .is_some();
+
+        self.api.emit_event(&RegistryEvent::Contains {
+            type_name: std::any::type_name::<T>(),
+            found,
+            meta: EventMeta::default().in_scope(self.name),
+        });
+
+        found
+    }
+
+    /// Remove the value of `T` from the scope and return it.
+    ///
+    /// # Errors
+    ///
+    /// `RegistryError::TypeNotFound` if `T` is not registered in the scope.
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let type_name = std::any::type_name::<T>();
+        let entry = R::state()
+            .scoped
+            .remove(self.name, TypeId::of::<T>())
+            .ok_or(RegistryError::TypeNotFound {
+                type_name,
+                location: None,
+                suggestions: Vec::new(),
+            })?;
+
+        self.api.emit_event(&RegistryEvent::Remove {
+            type_name,
+            meta: EventMeta::default().in_scope(self.name),
+        });
+
+        entry
+            .value()
+            .clone()
+            .downcast::<T>()
+            .map_err(|_| RegistryError::TypeMismatch { type_name })
+    }
+
+    /// Number of values registered in the scope.
+    pub fn len(&self) -> usize {
+        R::state().scoped.len(self.name)
+    }
+
+    /// Returns `true` if no value is registered in the scope.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_scopes_are_isolated() {
+        define_registry!(reg);
+
+        reg::register(0u8);
+        reg::scope("a").register(1u8);
+        reg::scope("b").register(2u8);
+
+        assert_eq!(*reg::get::<u8>().unwrap(), 0);
+        assert_eq!(*reg::scope("a").get::<u8>().unwrap(), 1);
+        assert_eq!(*reg::scope("b").remove::<u8>().unwrap(), 2);
+        assert!(!reg::scope("b").contains::<u8>());
+        assert!(reg::scope("c").get::<u8>().is_err());
+        assert_eq!((reg::scope("a").len(), reg::describe().len()), (1, 1));
+    }
+
+    #[test]
+    fn test_events_carry_the_scope() {
+        define_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = events.clone();
+        reg::set_trace_callback(move |event| seen.lock().unwrap().push(event.to_string()));
+
+        let plugin = reg::scope("plugin");
+        plugin.register(1u8);
+        let _ = plugin.get::<u8>();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "register { type_name: u8 } (scope: plugin)",
+                "register_completed { type_name: u8 } (scope: plugin)",
+                "get { type_name: u8, found: true } (scope: plugin)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clear_drops_scoped_values() {
+        define_registry!(reg);
+
+        let value = Arc::new(String::from("scoped"));
+        reg::scope("a").register_arc(value.clone());
+        reg::clear();
+
+        assert!(reg::scope("a").is_empty());
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! Registrations that can be serialized.
+//!
+//! `register_serializable(value)` keeps serde functions for the value's type
+//! in its entry, so `export_json()` can dump every such entry keyed by type
+//! name, e.g. for support bundles capturing the effective runtime
+//! configuration. Values registered otherwise are left out.
+
+use std::any::Any;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::RegistryEntry;
+
+type ToJsonFn = fn(&(dyn Any + Send + Sync)) -> Result<Value, serde_json::Error>;
+
+/// Serde functions for the concrete type of a type-erased value.
+#[derive(Clone, Copy)]
+pub(crate) struct SerdeShim {
+    to_json: ToJsonFn,
+    #[cfg(feature = "bincode")]
+    snapshot: crate::persist::SnapshotShim,
+}
+
+impl SerdeShim {
+    /// The functions for values of type `T`.
+    pub(crate) fn of<T>() -> Self
+    where
+        T: Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self {
+            to_json: to_json::<T>,
+            #[cfg(feature = "bincode")]
+            snapshot: crate::persist::SnapshotShim::of::<T>(),
+        }
+    }
+
+    /// The binary snapshot functions.
+    #[cfg(feature = "bincode")]
+    pub(crate) fn snapshot(&self) -> &crate::persist::SnapshotShim {
+        &self.snapshot
+    }
+
+    /// Serialize `value`, which must be of the type the shim was made for.
+    pub(crate) fn serialize(
+        &self,
+        value: &(dyn Any + Send + Sync),
+    ) -> Result<Value, serde_json::Error> {
+        (self.to_json)(value)
+    }
+}
+
+fn to_json<T: Serialize + 'static>(
+    value: &(dyn Any + Send + Sync),
+) -> Result<Value, serde_json::Error> {
+    match value.downcast_ref::<T>() {
+        Some(value) => serde_json::to_value(value),
+        None => Err(serde::ser::Error::custom("type mismatch")),
+    }
+}
+
+/// A pretty-printed JSON object of the serializable `entries`, keyed by type name.
+///
+/// A value whose serialization fails is shown as the string
+/// `"<unserializable: reason>"`.
+pub(crate) fn export_json(entries: &[RegistryEntry]) -> String {
+    let object: Map<String, Value> = entries
+        .iter()
+        .filter_map(|entry| {
+            let value = entry.serde_shim()?.serialize(&**entry.value());
+            let value =
+                value.unwrap_or_else(|err| Value::String(format!("<unserializable: {err}>")));
+            Some((entry.type_name().to_string(), value))
+        })
+        .collect();
+    serde_json::to_string_pretty(&object).expect("a JSON value always serializes")
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[test]
+    fn test_export_json() {
+        define_registry!(reg);
+
+        assert_eq!(reg::export_json(), "{}");
+
+        reg::register_serializable(Server {
+            host: "localhost".to_string(),
+            port: 8080,
+        });
+        reg::register_serializable(3u8);
+        reg::register(String::from("not serializable by registration"));
+
+        let exported: serde_json::Value = serde_json::from_str(&reg::export_json()).unwrap();
+        assert_eq!(
+            exported,
+            serde_json::json!({
+                std::any::type_name::<Server>(): { "host": "localhost", "port": 8080 },
+                "u8": 3,
+            })
+        );
+
+        // A plain registration replaces the serializable one
+        reg::register(4u8);
+        assert!(!reg::export_json().contains("\"u8\""));
+    }
+
+    #[test]
+    fn test_export_json_reports_failures() {
+        define_registry!(reg);
+
+        // JSON object keys must be strings
+        reg::register_serializable(HashMap::from([((1u8, 2u8), true)]));
+        assert!(reg::export_json().contains("<unserializable: key must be a string>"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Graceful shutdown orchestration (feature `tokio`).
+//!
+//! Long-lived services implement [`AsyncShutdown`] and are registered with
+//! `register_shutdown`. The registry knows every one of them, so
+//! `shutdown_with_timeout(timeout).await` drives teardown: it calls all
+//! `shutdown()` hooks concurrently, bounds each by `timeout`, and reports which
+//! services completed, timed out or panicked.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+
+use crate::registry_trait::lookup;
+use crate::sync::Mutex;
+use crate::RegistryApi;
+
+/// A service with async teardown, driven by `shutdown_with_timeout`.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, AsyncShutdown};
+/// use std::time::Duration;
+///
+/// define_registry!(services);
+///
+/// struct Db;
+///
+/// impl AsyncShutdown for Db {
+///     async fn shutdown(&self) {
+///         // flush and close connections
+///     }
+/// }
+///
+/// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+/// services::register_shutdown(Db);
+///
+/// let report = services::shutdown_with_timeout(Duration::from_secs(5)).await;
+/// assert!(report.is_clean());
+/// # });
+/// ```
+pub trait AsyncShutdown: Send + Sync + 'static {
+    /// Release the service's resources.
+    fn shutdown(&self) -> impl Future<Output = ()> + Send;
+}
+
+type BoxedShutdown = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A registered hook: builds the teardown future for the current value of its type.
+struct ShutdownHook {
+    type_id: TypeId,
+    type_name: &'static str,
+    start: Box<dyn Fn() -> Option<BoxedShutdown> + Send + Sync>,
+}
+
+/// Shutdown hooks in registration order, one per type.
+#[derive(Default)]
+pub(crate) struct ShutdownHooks {
+    hooks: Mutex<Vec<ShutdownHook>>,
+}
+
+impl fmt::Debug for ShutdownHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hooks = self.hooks.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_list()
+            .entries(hooks.iter().map(|h| h.type_name))
+            .finish()
+    }
+}
+
+impl ShutdownHooks {
+    /// Add the hook for `T` in `R`, unless it is already present.
+    pub(crate) fn add<R: RegistryApi + ?Sized, T: AsyncShutdown>(&self) {
+        let mut hooks = self.hooks.lock().unwrap_or_else(|p| p.into_inner());
+        if hooks.iter().any(|h| h.type_id == TypeId::of::<T>()) {
+            return;
+        }
+
+        hooks.push(ShutdownHook {
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            start: Box::new(|| {
+                let value: Arc<T> = lookup::<R, T>()?;
+                Some(Box::pin(async move { value.shutdown().await }))
+            }),
+        });
+    }
+
+    /// Run every hook whose type is still registered, each bounded by `timeout`.
+    pub(crate) async fn run(&self, timeout: Duration) -> ShutdownReport {
+        let started: Vec<(&'static str, BoxedShutdown)> = {
+            let hooks = self.hooks.lock().unwrap_or_else(|p| p.into_inner());
+            hooks
+                .iter()
+                .filter_map(|h| (h.start)().map(|fut| (h.type_name, fut)))
+                .collect()
+        };
+
+        let mut tasks = JoinSet::new();
+        let mut names = HashMap::new();
+        for (type_name, fut) in started {
+            let task = tasks.spawn(async move { tokio::time::timeout(timeout, fut).await.is_ok() });
+            names.insert(task.id(), type_name);
+        }
+
+        let mut report = ShutdownReport::default();
+        while let Some(joined) = tasks.join_next_with_id().await {
+            match joined {
+                Ok((id, true)) => report.completed.push(names[&id]),
+                Ok((id, false)) => report.timed_out.push(names[&id]),
+                Err(err) => report.panicked.push(names[&err.id()]),
+            }
+        }
+        report
+    }
+}
+
+/// Outcome of `shutdown_with_timeout`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Type names of services whose `shutdown()` finished in time.
+    pub completed: Vec<&'static str>,
+    /// Type names of services whose `shutdown()` exceeded the timeout.
+    pub timed_out: Vec<&'static str>,
+    /// Type names of services whose `shutdown()` panicked.
+    pub panicked: Vec<&'static str>,
+}
+
+impl ShutdownReport {
+    /// Whether every service shut down in time.
+    pub fn is_clean(&self) -> bool {
+        self.timed_out.is_empty() && self.panicked.is_empty()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct Db;
+    static DB_CLOSED: AtomicBool = AtomicBool::new(false);
+
+    impl AsyncShutdown for Db {
+        async fn shutdown(&self) {
+            DB_CLOSED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    struct Stuck;
+
+    impl AsyncShutdown for Stuck {
+        async fn shutdown(&self) {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+    }
+
+    struct Broken;
+
+    impl AsyncShutdown for Broken {
+        async fn shutdown(&self) {
+            panic!("teardown failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_hooks_and_reports_outcomes() {
+        define_registry!(reg);
+
+        reg::register_shutdown(Db);
+        reg::register_shutdown(Stuck);
+        reg::register_shutdown(Broken);
+
+        let report = reg::shutdown_with_timeout(Duration::from_millis(50)).await;
+
+        assert!(DB_CLOSED.load(Ordering::SeqCst));
+        assert_eq!(report.completed, vec![std::any::type_name::<Db>()]);
+        assert_eq!(report.timed_out, vec![std::any::type_name::<Stuck>()]);
+        assert_eq!(report.panicked, vec![std::any::type_name::<Broken>()]);
+        assert!(!report.is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_services_are_skipped() {
+        define_registry!(reg);
+
+        reg::register_shutdown(Stuck);
+        reg::clear();
+
+        let report = reg::shutdown_with_timeout(Duration::from_millis(10)).await;
+        assert_eq!(report, ShutdownReport::default());
+        assert!(report.is_clean());
+    }
+}
@@ -0,0 +1,97 @@
+//! Configuration reload on `SIGHUP` (feature `unix`, Unix only).
+//!
+//! Daemons conventionally re-read their configuration when they receive
+//! `SIGHUP`. `reload_on_sighup(reload)` starts a thread that waits for the
+//! signal and then runs `reload`, which re-registers the configuration: the
+//! registry's replace semantics make the new values visible to every later
+//! lookup. Each reload is announced with a `RegistryEvent::Reload` event.
+
+use std::io;
+use std::thread::JoinHandle;
+
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::{Handle, Signals};
+
+use crate::{EventMeta, RegistryApi, RegistryEvent};
+
+/// Keeps a `SIGHUP` reload handler installed; returned by
+/// [`reload_on_sighup`](crate::RegistryApi::reload_on_sighup).
+///
+/// Dropping the guard stops the handler thread. Signals arriving afterwards
+/// are ignored rather than terminating the process.
+#[must_use = "the reload handler stops when the guard is dropped"]
+#[derive(Debug)]
+pub struct SighupGuard {
+    handle: Handle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for SighupGuard {
+    fn drop(&mut self) {
+        self.handle.close();
+        if let Some(thread) = self.thread.take() {
+            // A panic in the reload closure has already been reported by the thread
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Run `reload` on a dedicated thread whenever the process receives `SIGHUP`.
+pub(crate) fn install<R>(api: R, reload: impl Fn() + Send + 'static) -> io::Result<SighupGuard>
+where
+    R: RegistryApi + Send + 'static,
+{
+    let mut signals = Signals::new([SIGHUP])?;
+    let handle = signals.handle();
+    let thread = std::thread::Builder::new()
+        .name(format!("{}-sighup", R::name()))
+        .spawn(move || {
+            for _ in signals.forever() {
+                api.emit_event(&RegistryEvent::Reload {
+                    meta: EventMeta::default(),
+                });
+                reload();
+            }
+        })?;
+
+    Ok(SighupGuard {
+        handle,
+        thread: Some(thread),
+    })
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryEvent};
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sighup_runs_the_reload() {
+        define_registry!(reg);
+
+        let (events, seen) = mpsc::channel();
+        let events = std::sync::Mutex::new(events);
+        reg::set_trace_callback(move |event| {
+            if matches!(event, RegistryEvent::Reload { .. }) {
+                let _ = events.lock().unwrap().send(());
+            }
+        });
+
+        reg::register(1u32);
+        let guard = reg::reload_on_sighup(|| reg::register(2u32)).unwrap();
+
+        let value = Arc::new(std::sync::Mutex::new(0u32));
+        let watched = value.clone();
+        let _listener = reg::on_change::<u32>(move |v| *watched.lock().unwrap() = **v);
+
+        signal_hook::low_level::raise(signal_hook::consts::SIGHUP).unwrap();
+        seen.recv_timeout(Duration::from_secs(5)).unwrap();
+        drop(guard); // joins the thread, so the reload has finished
+
+        assert_eq!(*reg::get::<u32>().unwrap(), 2);
+        assert_eq!(*value.lock().unwrap(), 2);
+    }
+}
@@ -0,0 +1,272 @@
+//! Point-in-time captures of a registry for test assertions.
+//!
+//! `snapshot()` records which value each type maps to, and
+//! `before.diff(&after)` reports which types a code path added, removed or
+//! replaced. A snapshot holds the `Arc`s it captured, so a replaced value's
+//! address cannot be reused and comparing pointers is reliable.
+//!
+//! `golden_snapshot()` renders the registry as text instead, for comparing
+//! against a checked-in file or with snapshot testing tools such as `insta`.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+use crate::RegistryEntry;
+
+/// Render `entries` deterministically: sorted by type name, with the `Debug`
+/// output of debuggable values, finality, protection and metadata, but no
+/// timestamps, locations or reference counts.
+pub(crate) fn render_golden(mut entries: Vec<RegistryEntry>) -> String {
+    entries.sort_by_key(RegistryEntry::type_name);
+
+    let mut out = String::new();
+    for entry in &entries {
+        render_entry(&mut out, entry).expect("writing to a String cannot fail");
+    }
+    out
+}
+
+fn render_entry(out: &mut String, entry: &RegistryEntry) -> fmt::Result {
+    match entry.debug_value() {
+        Some(value) => write!(out, "{} = {:?}", entry.type_name(), value)?,
+        None => write!(out, "{} = <opaque>", entry.type_name())?,
+    }
+    if entry.is_final() {
+        write!(out, " [final]")?;
+    }
+    if entry.is_protected() {
+        write!(out, " [protected]")?;
+    }
+    writeln!(out)?;
+
+    if let Some(metadata) = entry.metadata() {
+        if !metadata.description.is_empty() {
+            writeln!(out, "    description: {}", metadata.description)?;
+        }
+        if !metadata.owner.is_empty() {
+            writeln!(out, "    owner: {}", metadata.owner)?;
+        }
+        if !metadata.tags.is_empty() {
+            writeln!(out, "    tags: {}", metadata.tags.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+/// The entries of a registry at one point in time.
+///
+/// Returned by `snapshot()` (feature `test-util`). Keeps every captured value
+/// alive until dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// app::register(1u8);
+/// app::register(2u16);
+/// let before = app::snapshot();
+///
+/// app::register(3u16);
+/// app::register(4u32);
+/// let _ = app::remove::<u8>();
+///
+/// let diff = before.diff(&app::snapshot());
+/// assert_eq!(diff.added, ["u32"]);
+/// assert_eq!(diff.removed, ["u8"]);
+/// assert_eq!(diff.replaced, ["u16"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct RegistrySnapshot {
+    entries: HashMap<TypeId, (&'static str, Arc<dyn Any + Send + Sync>)>,
+}
+
+impl RegistrySnapshot {
+    pub(crate) fn new(
+        entries: impl IntoIterator<Item = (TypeId, &'static str, Arc<dyn Any + Send + Sync>)>,
+    ) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|(type_id, type_name, value)| (type_id, (type_name, value)))
+                .collect(),
+        }
+    }
+
+    /// Number of captured entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the registry was empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if a value of `T` was registered.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<T>())
+    }
+
+    /// The changes from this snapshot to the later `other`.
+    ///
+    /// A type counts as replaced when it maps to a different `Arc` in `other`,
+    /// even if the new value is equal to the old one.
+    pub fn diff(&self, other: &RegistrySnapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+        for (type_id, (type_name, value)) in &self.entries {
+            match other.entries.get(type_id) {
+                None => diff.removed.push(type_name),
+                Some((_, new)) if !Arc::ptr_eq(value, new) => diff.replaced.push(type_name),
+                Some(_) => {}
+            }
+        }
+        for (type_id, (type_name, _)) in &other.entries {
+            if !self.entries.contains_key(type_id) {
+                diff.added.push(type_name);
+            }
+        }
+        diff.added.sort_unstable();
+        diff.removed.sort_unstable();
+        diff.replaced.sort_unstable();
+        diff
+    }
+}
+
+impl fmt::Debug for RegistrySnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut type_names: Vec<&str> = self.entries.values().map(|(name, _)| *name).collect();
+        type_names.sort_unstable();
+        f.debug_set().entries(type_names).finish()
+    }
+}
+
+/// Types added, removed and replaced between two [`RegistrySnapshot`]s.
+///
+/// Each list holds type names, sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Types registered only in the later snapshot
+    pub added: Vec<&'static str>,
+    /// Types registered only in the earlier snapshot
+    pub removed: Vec<&'static str>,
+    /// Types whose value was registered again in between
+    pub replaced: Vec<&'static str>,
+}
+
+impl SnapshotDiff {
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.replaced.is_empty()
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+
+        let mut first = true;
+        for (label, names) in [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("replaced", &self.replaced),
+        ] {
+            if names.is_empty() {
+                continue;
+            }
+            if !first {
+                write!(f, "; ")?;
+            }
+            first = false;
+            write!(f, "{label}: {}", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_diff() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        reg::register(2u16);
+        reg::register(String::from("kept"));
+        let before = reg::snapshot();
+        assert_eq!(before.len(), 3);
+        assert!(before.diff(&reg::snapshot()).is_empty());
+
+        reg::register(2u16); // equal value, new Arc
+        reg::register(4u32);
+        let _ = reg::remove::<u8>();
+        let after = reg::snapshot();
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff,
+            SnapshotDiff {
+                added: vec!["u32"],
+                removed: vec!["u8"],
+                replaced: vec!["u16"],
+            }
+        );
+        assert_eq!(diff.to_string(), "added: u32; removed: u8; replaced: u16");
+        assert!(before.contains::<u8>() && !after.contains::<u8>());
+        assert_eq!(
+            format!("{after:?}"),
+            r#"{"alloc::string::String", "u16", "u32"}"#
+        );
+    }
+
+    #[test]
+    fn test_golden_snapshot() {
+        use crate::Metadata;
+
+        #[derive(Debug)]
+        struct Config {
+            port: u16,
+        }
+
+        define_registry!(reg);
+        assert_eq!(reg::golden_snapshot(), "");
+
+        reg::register(1u8);
+        reg::register_final(2u16).unwrap();
+        reg::register_debuggable(Config { port: 8080 });
+        reg::register_with_meta(
+            String::from("db"),
+            Metadata::new("connection string")
+                .with_owner("platform")
+                .with_tag("io")
+                .with_tag("secret"),
+        );
+
+        assert_eq!(
+            reg::golden_snapshot(),
+            "alloc::string::String = <opaque>
+    description: connection string
+    owner: platform
+    tags: io, secret
+singleton_registry::snapshot::tests::test_golden_snapshot::Config = Config { port: 8080 }
+u16 = <opaque> [final]
+u8 = <opaque>
+"
+        );
+    }
+
+    #[test]
+    fn test_display_empty() {
+        assert_eq!(SnapshotDiff::default().to_string(), "no changes");
+    }
+}
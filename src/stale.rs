@@ -0,0 +1,62 @@
+//! Last-access tracking and stale-entry reports.
+//!
+//! With `set_access_tracking(true)` every `get` stamps the entry it returns.
+//! `stale_entries(older_than)` then lists the entries nobody has retrieved for
+//! at least `older_than`, so long-running processes can spot registered
+//! services that are never used and are candidates for removal.
+
+use std::time::Duration;
+
+/// An entry that has not been retrieved for a while.
+///
+/// Returned by `stale_entries()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleEntry {
+    /// The type name of the value
+    pub type_name: &'static str,
+    /// Time since the value was last retrieved, or registered if it never was
+    pub idle: Duration,
+    /// Whether the value was ever retrieved while access tracking was on
+    pub accessed: bool,
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_unused_entries_go_stale() {
+        define_registry!(reg);
+
+        reg::set_access_tracking(true);
+        assert!(reg::access_tracking());
+
+        reg::register(1u8);
+        reg::register(2u16);
+        thread::sleep(Duration::from_millis(30));
+        let _ = reg::get::<u8>();
+
+        let stale = reg::stale_entries(Duration::from_millis(20));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].type_name, "u16");
+        assert!(!stale[0].accessed);
+        assert!(stale[0].idle >= Duration::from_millis(30));
+
+        thread::sleep(Duration::from_millis(30));
+        let stale = reg::stale_entries(Duration::from_millis(20));
+        assert_eq!(stale.len(), 2);
+        assert!(stale.iter().any(|e| e.type_name == "u8" && e.accessed));
+    }
+
+    #[test]
+    fn test_untracked_gets_do_not_count() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        let _ = reg::get::<u8>();
+        assert!(!reg::stale_entries(Duration::ZERO)[0].accessed);
+    }
+}
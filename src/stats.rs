@@ -0,0 +1,144 @@
+//! Per-type lookup statistics (feature `stats`).
+//!
+//! Every `get` counts as a hit or a miss for the requested type, so
+//! `stats::<T>()` answers "is anything even using this registered service?"
+//! without external tooling. Counters live as long as the registry and are
+//! only reset by `reset_stats()`.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::sync::{AtomicU64, Mutex};
+
+/// Lookup counters of a single type.
+///
+/// Returned by `stats::<T>()` and `stats_all()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeStats {
+    /// The type name the counters belong to
+    pub type_name: &'static str,
+    /// Number of `get` calls that found a value
+    pub hits: u64,
+    /// Number of `get` calls that found nothing
+    pub misses: u64,
+}
+
+impl TypeStats {
+    /// Total number of lookups.
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Per-registry lookup counters, keyed by type.
+#[derive(Debug, Default)]
+pub(crate) struct LookupStats {
+    types: Mutex<HashMap<TypeId, (&'static str, Arc<Counters>)>>,
+}
+
+impl LookupStats {
+    /// Count one lookup of the type `type_id`, named `type_name`.
+    pub(crate) fn record(&self, type_id: TypeId, type_name: &'static str, found: bool) {
+        let counters = self
+            .types
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(type_id)
+            .or_insert_with(|| (type_name, Arc::default()))
+            .1
+            .clone();
+
+        let counter = if found {
+            &counters.hits
+        } else {
+            &counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The counters of `T`, zero if it was never looked up.
+    pub(crate) fn get<T: 'static>(&self) -> TypeStats {
+        let types = self.types.lock().unwrap_or_else(|p| p.into_inner());
+        match types.get(&TypeId::of::<T>()) {
+            Some((type_name, counters)) => snapshot(type_name, counters),
+            None => TypeStats {
+                type_name: std::any::type_name::<T>(),
+                hits: 0,
+                misses: 0,
+            },
+        }
+    }
+
+    /// The counters of every type looked up so far, sorted by type name.
+    pub(crate) fn all(&self) -> Vec<TypeStats> {
+        let mut all: Vec<TypeStats> = self
+            .types
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .map(|(type_name, counters)| snapshot(type_name, counters))
+            .collect();
+        all.sort_by_key(|s| s.type_name);
+        all
+    }
+
+    /// Forget all counters.
+    pub(crate) fn reset(&self) {
+        self.types.lock().unwrap_or_else(|p| p.into_inner()).clear();
+    }
+}
+
+fn snapshot(type_name: &'static str, counters: &Counters) -> TypeStats {
+    TypeStats {
+        type_name,
+        hits: counters.hits.load(Ordering::Relaxed),
+        misses: counters.misses.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_hits_and_misses() {
+        define_registry!(reg);
+
+        assert_eq!(reg::stats::<u8>().lookups(), 0);
+
+        let _ = reg::get::<u8>();
+        reg::register(1u8);
+        let _ = reg::get::<u8>();
+        let _ = reg::get_cloned::<u8>();
+        let _ = reg::get::<u16>();
+
+        assert_eq!(
+            reg::stats::<u8>(),
+            TypeStats {
+                type_name: "u8",
+                hits: 2,
+                misses: 1
+            }
+        );
+        assert_eq!(
+            reg::stats_all()
+                .iter()
+                .map(|s| (s.type_name, s.lookups()))
+                .collect::<Vec<_>>(),
+            [("u16", 1), ("u8", 3)]
+        );
+
+        reg::reset_stats();
+        assert!(reg::stats_all().is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+//! "Did you mean" suggestions for failed lookups.
+//!
+//! `get::<mycrate::Config>()` failing while `mycrate::config::Config` is
+//! registered usually means a re-export or a duplicated struct. The registered
+//! type names whose last path segment matches the requested one, exactly or
+//! within a small edit distance, are offered in `RegistryError::TypeNotFound`.
+
+/// At most this many suggestions are reported.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Registered type names resembling `wanted`, best matches first.
+pub(crate) fn suggestions(
+    wanted: &str,
+    registered: impl IntoIterator<Item = &'static str>,
+) -> Vec<&'static str> {
+    let wanted_name = last_segment(wanted);
+    // Short names like `u8` are too close to everything to allow typos
+    let threshold = match wanted_name.chars().count() {
+        0..=3 => 0,
+        len => (len / 4).clamp(1, 2),
+    };
+
+    let mut matches: Vec<(usize, &'static str)> = registered
+        .into_iter()
+        .filter(|name| *name != wanted)
+        .filter_map(|name| {
+            let distance = edit_distance(wanted_name, last_segment(name));
+            (distance <= threshold).then_some((distance, name))
+        })
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// The type name without its module path, e.g. `Config` for `app::Config` and
+/// `Vec<u8>` for `alloc::vec::Vec<u8>`.
+fn last_segment(type_name: &str) -> &str {
+    let head = type_name.find('<').unwrap_or(type_name.len());
+    let start = type_name[..head].rfind("::").map_or(0, |i| i + 2);
+    &type_name[start..]
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, RegistryError};
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("Config", "Config"), 0);
+        assert_eq!(edit_distance("Config", "Confg"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_last_segment() {
+        assert_eq!(last_segment("app::config::Config"), "Config");
+        assert_eq!(last_segment("alloc::vec::Vec<app::Item>"), "Vec<app::Item>");
+        assert_eq!(last_segment("u8"), "u8");
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let registered = [
+            "app::config::Config",
+            "app::Confg",
+            "app::Logger",
+            "app::Conf",
+        ];
+        assert_eq!(
+            suggestions("app::Config", registered),
+            ["app::config::Config", "app::Confg"]
+        );
+        assert!(suggestions("app::Database", registered).is_empty());
+        assert!(suggestions("u8", ["i8", "u16"]).is_empty());
+    }
+
+    mod config {
+        pub struct Config;
+    }
+    #[derive(Debug)]
+    struct Config;
+
+    #[test]
+    fn test_not_found_suggests_registered_types() {
+        define_registry!(reg);
+
+        reg::register(config::Config);
+        let err = reg::get::<Config>().unwrap_err();
+        let RegistryError::TypeNotFound { suggestions, .. } = &err else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(*suggestions, [std::any::type_name::<config::Config>()]);
+        assert!(err
+            .to_string()
+            .ends_with("; did you mean singleton_registry::suggest::tests::config::Config?"));
+    }
+}
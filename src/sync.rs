@@ -0,0 +1,91 @@
+//! Synchronization primitives used by the registry.
+//!
+//! Every lock, atomic and lazily-initialized static the registry relies on is
+//! imported from this module instead of `std::sync`. Building with `--cfg loom`
+//! swaps them for [loom](https://docs.rs/loom)'s model-checked counterparts, so
+//! the registry's concurrency guarantees can be verified exhaustively:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! The `once_cell` feature replaces `std::sync::LazyLock`, stable since Rust
+//! 1.80 (the crate's MSRV), with `once_cell::sync::Lazy`, so the crate also
+//! builds on compilers down to 1.75 (which `impl Trait` in trait methods
+//! requires). Both have the same `new`/`force` API, so the registry and the
+//! macro expansions are unchanged.
+//!
+//! Wiring configuration that is set up once rather than raced on (the global
+//! trace sink, event forwarding links, callback panic policies) deliberately
+//! uses `std::sync` directly, which keeps the models' state space to the
+//! registry's data paths.
+//!
+//! The module is public only so `define_registry!` can name these types; it is
+//! not part of the supported API.
+
+#[cfg(not(loom))]
+pub use std::sync::{Condvar, Mutex, MutexGuard};
+
+#[cfg(all(not(loom), not(feature = "once_cell")))]
+pub use std::sync::LazyLock;
+
+#[cfg(all(not(loom), feature = "once_cell"))]
+pub use once_cell::sync::Lazy as LazyLock;
+
+#[cfg(not(loom))]
+#[allow(unused_imports)]
+pub(crate) use std::sync::atomic::AtomicU64;
+
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::{AtomicBool, AtomicU8};
+
+#[cfg(loom)]
+pub use loom::sync::{Condvar, Mutex, MutexGuard};
+
+#[cfg(loom)]
+#[allow(unused_imports)]
+pub(crate) use loom::sync::atomic::AtomicU64;
+
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{AtomicBool, AtomicU8};
+
+// Async registries use tokio's lock when the `tokio` feature is enabled and the
+// executor-agnostic `async-lock` otherwise, so async-std and smol users don't pull in tokio.
+#[cfg(feature = "tokio")]
+pub use tokio::sync::{OnceCell as AsyncOnceCell, RwLock as AsyncRwLock};
+
+#[cfg(all(feature = "async", not(feature = "tokio")))]
+pub use async_lock::{OnceCell as AsyncOnceCell, RwLock as AsyncRwLock};
+
+// Channels behind `subscribe::<T>()` and `subscribe_events()`.
+#[cfg(feature = "tokio")]
+pub use tokio::sync::{mpsc, watch};
+
+// Task-local storage behind `define_task_local_registry!`.
+#[cfg(feature = "tokio")]
+pub use tokio::task::{futures::TaskLocalFuture, LocalKey};
+#[cfg(feature = "tokio")]
+pub use tokio::task_local;
+
+/// Loom replacement for `std::sync::LazyLock`.
+///
+/// Backed by loom's lazy statics, which are reset at the start of every model
+/// execution. Values are reached through [`LazyLock::force`], mirroring the std API.
+#[cfg(loom)]
+pub struct LazyLock<T: 'static>(loom::lazy_static::Lazy<T>);
+
+#[cfg(loom)]
+impl<T: 'static> LazyLock<T> {
+    /// Create a lazily-initialized value.
+    pub const fn new(init: fn() -> T) -> Self {
+        Self(loom::lazy_static::Lazy {
+            init,
+            _p: std::marker::PhantomData,
+        })
+    }
+
+    /// Force initialization and return a reference to the value.
+    pub fn force(this: &'static Self) -> &'static T {
+        this.0.get()
+    }
+}
@@ -0,0 +1,279 @@
+//! Task-local registries for async request scopes (feature `tokio`).
+//!
+//! The storage of a registry created with
+//! [`define_task_local_registry!`](crate::define_task_local_registry) lives in a
+//! `tokio` task-local. Each `scope(future)` gets its own empty map, so per-request
+//! values (request id, tenant context) use the familiar `register`/`get` API
+//! without leaking across concurrently running requests.
+//!
+//! A task is polled by one thread at a time, so the map sits in a `RefCell`
+//! instead of behind a lock. The trace callback is shared by all scopes.
+
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::Location;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::task::futures::TaskLocalFuture;
+use tokio::task::LocalKey;
+
+use crate::sync::{LazyLock, Mutex};
+use crate::{EventMeta, RegistryEntry, RegistryError, RegistryEvent};
+
+/// Type alias for the trace callback storage.
+///
+/// Note: This type is also defined in the `define_task_local_registry!` macro.
+/// Keep both definitions in sync.
+type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>>>;
+
+/// The map held in the task-local of a task-local registry.
+pub type TaskLocalStorage = RefCell<HashMap<TypeId, RegistryEntry>>;
+
+/// Core trait defining task-local registry behavior.
+///
+/// Mirrors [`RegistryApi`](crate::RegistryApi), scoped to the current task. Only
+/// the `storage` and `trace` accessors have to be implemented.
+///
+/// # Panics
+///
+/// Every storage operation panics when called outside of [`scope`](Self::scope)
+/// or [`sync_scope`](Self::sync_scope), like `tokio::task::LocalKey::with`.
+pub trait TaskLocalRegistryApi {
+    // -------------------------------------------------------------------------------------------------
+    // Tracing
+    // -------------------------------------------------------------------------------------------------
+
+    /// Access the trace callback static.
+    fn trace() -> &'static TraceCallback;
+
+    /// Name of the registry, reported in the `registry` field of every event.
+    ///
+    /// `define_task_local_registry!` returns the module name; defaults to the implementing
+    /// type's name.
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Set a tracing callback for registry operations in every scope.
+    fn set_trace_callback(&self, callback: impl Fn(&RegistryEvent) + Send + Sync + 'static) {
+        let mut guard = LazyLock::force(Self::trace())
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        *guard = Some(Arc::new(callback));
+    }
+
+    /// Clear the tracing callback.
+    fn clear_trace_callback(&self) {
+        let mut guard = LazyLock::force(Self::trace())
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        *guard = None;
+    }
+
+    /// Emit a registry event using the current callback.
+    ///
+    /// The trace lock is released before the callback is invoked.
+    fn emit_event(&self, event: &RegistryEvent) {
+        let event = &event.with_meta(EventMeta::capture(Self::name()));
+        #[cfg(feature = "tracing")]
+        crate::tracing_bridge::emit(event);
+        let callback = {
+            let guard = LazyLock::force(Self::trace())
+                .lock()
+                .unwrap_or_else(|p| p.into_inner());
+            guard.as_ref().cloned()
+        };
+        if let Some(cb) = callback {
+            cb(event);
+        }
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Scopes
+    // -------------------------------------------------------------------------------------------------
+
+    /// Access the task-local storage.
+    fn storage() -> &'static LocalKey<TaskLocalStorage>;
+
+    /// Run `future` with a fresh, empty registry.
+    ///
+    /// Values registered inside the scope are dropped when the future completes.
+    /// Nested scopes shadow the outer one.
+    fn scope<F: Future>(&self, future: F) -> TaskLocalFuture<TaskLocalStorage, F> {
+        Self::storage().scope(TaskLocalStorage::default(), future)
+    }
+
+    /// Run the closure `f` with a fresh, empty registry.
+    fn sync_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        Self::storage().sync_scope(TaskLocalStorage::default(), f)
+    }
+
+    // -------------------------------------------------------------------------------------------------
+    // Registry
+    // -------------------------------------------------------------------------------------------------
+
+    /// Register a value in the current scope, replacing any previous value of the same type.
+    fn register<T: Send + Sync + 'static>(&self, value: T) {
+        self.register_arc(Arc::new(value));
+    }
+
+    /// Register an Arc-wrapped value in the current scope.
+    fn register_arc<T: Send + Sync + 'static>(&self, value: Arc<T>) {
+        self.emit_event(&RegistryEvent::Register {
+            type_name: std::any::type_name::<T>(),
+            meta: EventMeta::default(),
+        });
+
+        let started = Instant::now();
+
+        let previous = Self::storage().with(|map| {
+            map.borrow_mut()
+                .insert(TypeId::of::<T>(), RegistryEntry::new(value))
+        });
+        let replaced = previous.is_some();
+        // Drop the old value only after the borrow ends; its `Drop` may use the registry
+        drop(previous);
+
+        self.emit_event(&RegistryEvent::RegisterCompleted {
+            type_name: std::any::type_name::<T>(),
+            replaced,
+            rollback: false,
+            meta: EventMeta::timed(started),
+        });
+    }
+
+    /// Retrieve a value from the current scope.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the current scope
+    /// - Type mismatch (extremely rare)
+    #[track_caller]
+    fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, RegistryError> {
+        let started = Instant::now();
+        let any_arc_opt = Self::storage().with(|map| {
+            map.borrow()
+                .get(&TypeId::of::<T>())
+                .map(|e| e.value().clone())
+        });
+
+        let result = match any_arc_opt {
+            Some(any_arc) => any_arc
+                .downcast::<T>()
+                .map_err(|_| RegistryError::TypeMismatch {
+                    type_name: std::any::type_name::<T>(),
+                }),
+            None => Err(RegistryError::TypeNotFound {
+                type_name: std::any::type_name::<T>(),
+                location: Some(Location::caller()),
+                suggestions: Vec::new(),
+            }),
+        };
+
+        self.emit_event(&RegistryEvent::Get {
+            type_name: std::any::type_name::<T>(),
+            found: result.is_ok(),
+            meta: EventMeta::timed(started),
+        });
+
+        result
+    }
+
+    /// Retrieve a cloned value from the current scope.
+    ///
+    /// # Errors
+    ///
+    /// - Type `T` is not found in the current scope
+    /// - Type mismatch
+    #[track_caller]
+    fn get_cloned<T: Send + Sync + Clone + 'static>(&self) -> Result<T, RegistryError> {
+        self.get::<T>().map(|arc| (*arc).clone())
+    }
+
+    /// Retrieve a value from the current scope, returning `None` if not registered.
+    fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get::<T>().ok()
+    }
+
+    /// Check if a type is registered in the current scope.
+    fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        let found = Self::storage().with(|map| map.borrow().contains_key(&TypeId::of::<T>()));
+
+        self.emit_event(&RegistryEvent::Contains {
+            type_name: std::any::type_name::<T>(),
+            found,
+            meta: EventMeta::default(),
+        });
+
+        found
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// Tests
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_task_local_registry;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    struct RequestId(u32);
+
+    #[tokio::test]
+    async fn test_register_and_get_in_scope() {
+        define_task_local_registry!(request);
+
+        request::scope(async {
+            request::register(RequestId(7));
+            let id: Arc<RequestId> = request::get().unwrap();
+            assert_eq!(*id, RequestId(7));
+            assert!(request::contains::<RequestId>());
+            assert!(request::try_get::<u8>().is_none());
+        })
+        .await;
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_scopes_are_isolated() {
+        define_task_local_registry!(request);
+
+        let handles: Vec<_> = (0..16u32)
+            .map(|i| {
+                tokio::spawn(request::scope(async move {
+                    request::register(RequestId(i));
+                    tokio::task::yield_now().await;
+                    request::get::<RequestId>().unwrap().0
+                }))
+            })
+            .collect();
+
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap(), i as u32);
+        }
+    }
+
+    #[test]
+    fn test_sync_scope_starts_empty() {
+        define_task_local_registry!(request);
+
+        request::sync_scope(|| {
+            request::register(1u8);
+            request::sync_scope(|| assert!(!request::contains::<u8>()));
+            assert!(request::contains::<u8>());
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_access_outside_scope_panics() {
+        define_task_local_registry!(request);
+
+        request::register(1u8);
+    }
+}
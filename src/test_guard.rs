@@ -0,0 +1,220 @@
+//! RAII fixtures that undo a test's registrations.
+//!
+//! Tests share the process-wide registries, so a value registered by one test
+//! is seen by the next unless someone remembers to `clear()`. A [`TestGuard`]
+//! captures the registry when created and puts it back when dropped, which
+//! also happens when the test panics. A [`TestLockGuard`] keeps tests touching
+//! the same registry from running at the same time under `cargo test`.
+
+use std::any::TypeId;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::registry_trait::lock_storage;
+use crate::{RegistryApi, RegistryEntry};
+
+/// Restores a registry to its state at creation when dropped.
+///
+/// Returned by `test_guard()` (feature `test-util`). Values registered while
+/// the guard is alive are dropped, replaced or removed values come back, and
+/// scoped values are cleared. A guard created on an empty registry therefore
+/// clears it on drop.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// app::register(1u8);
+/// {
+///     let _guard = app::test_guard();
+///     app::register(2u8);
+///     app::register(String::from("test only"));
+/// }
+/// assert_eq!(*app::get::<u8>().unwrap(), 1);
+/// assert!(!app::contains::<String>().unwrap());
+/// ```
+#[must_use = "the registry is restored when the guard is dropped"]
+pub struct TestGuard<R: RegistryApi> {
+    entries: Vec<(TypeId, RegistryEntry)>,
+    registry: PhantomData<R>,
+}
+
+impl<R: RegistryApi> TestGuard<R> {
+    pub(crate) fn new() -> Self {
+        let entries = lock_storage::<R>()
+            .unwrap_or_else(|p| p.into_inner())
+            .iter()
+            .map(|(type_id, entry)| (*type_id, entry.clone()))
+            .collect();
+        Self {
+            entries,
+            registry: PhantomData,
+        }
+    }
+
+    /// Number of entries that are restored on drop.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if dropping the guard leaves the registry empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<R: RegistryApi> Drop for TestGuard<R> {
+    fn drop(&mut self) {
+        let discarded: Vec<RegistryEntry> = {
+            let mut map = lock_storage::<R>().unwrap_or_else(|p| p.into_inner());
+            let discarded = map.drain().map(|(_, entry)| entry).collect();
+            map.extend(mem::take(&mut self.entries));
+            discarded
+        };
+        drop(R::state().scoped.drain());
+        // Values are dropped with no lock held
+        drop(discarded);
+    }
+}
+
+impl<R: RegistryApi> fmt::Debug for TestGuard<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestGuard")
+            .field("registry", &R::name())
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+/// Per-registry lock serializing the tests that take it.
+#[derive(Debug, Default)]
+pub(crate) struct TestLock {
+    // Wiring configuration rather than registry data: std types, see `crate::sync`.
+    lock: Mutex<()>,
+}
+
+impl TestLock {
+    pub(crate) fn acquire(&'static self, registry: &'static str) -> TestLockGuard {
+        TestLockGuard {
+            registry,
+            // A test failing while holding the lock must not fail the others
+            _guard: self.lock.lock().unwrap_or_else(|p| p.into_inner()),
+        }
+    }
+}
+
+/// Exclusive access to a registry for the duration of a test.
+///
+/// Returned by `lock_for_test()` (feature `test-util`). Tests holding the lock
+/// of the same registry run one after another, replacing `serial_test` for
+/// tests that share a static registry. The lock is released on drop, also when
+/// the test panics.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::define_registry;
+///
+/// define_registry!(app);
+///
+/// // At the start of every test using `app`
+/// let _lock = app::lock_for_test();
+/// let _guard = app::test_guard();
+///
+/// app::register(8080u16);
+/// assert_eq!(*app::get::<u16>().unwrap(), 8080);
+/// ```
+#[must_use = "the registry is unlocked when the guard is dropped"]
+pub struct TestLockGuard {
+    registry: &'static str,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl fmt::Debug for TestLockGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TestLockGuard")
+            .field(&self.registry)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::panic;
+
+    #[test]
+    fn test_restores_replaced_and_removed_values() {
+        define_registry!(reg);
+
+        reg::register(1u8);
+        reg::register(1u16);
+        let guard = reg::test_guard();
+        assert_eq!(guard.len(), 2);
+
+        reg::register(2u8);
+        reg::remove::<u16>().unwrap();
+        reg::register(1u32);
+        reg::scope("tenant").register(1i8);
+        drop(guard);
+
+        assert_eq!(*reg::get::<u8>().unwrap(), 1);
+        assert_eq!(*reg::get::<u16>().unwrap(), 1);
+        assert!(!reg::contains::<u32>().unwrap());
+        assert!(!reg::scope("tenant").contains::<i8>());
+    }
+
+    #[test]
+    fn test_clears_on_panic() {
+        define_registry!(reg);
+
+        let result = panic::catch_unwind(|| {
+            let guard = reg::test_guard();
+            assert!(guard.is_empty());
+            reg::register(1u8);
+            panic!("test failed");
+        });
+        assert!(result.is_err());
+        assert!(!reg::contains::<u8>().unwrap());
+    }
+
+    #[test]
+    fn test_lock_for_test_serializes() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        define_registry!(reg);
+
+        let busy = Arc::new(AtomicBool::new(false));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let busy = busy.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        let _lock = reg::lock_for_test();
+                        assert!(!busy.swap(true, Ordering::SeqCst));
+                        std::thread::yield_now();
+                        busy.store(false, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // A panicking holder releases the lock
+        let _ = panic::catch_unwind(|| {
+            let _lock = reg::lock_for_test();
+            panic!("test failed");
+        });
+        let lock = reg::lock_for_test();
+        assert_eq!(format!("{lock:?}"), "TestLockGuard(\"reg\")");
+    }
+}
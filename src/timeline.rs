@@ -0,0 +1,273 @@
+//! Opt-in recording of every mutation of a registry.
+//!
+//! Initialization-order bugs are hard to see in a registry that only holds the
+//! final state. `start_timeline()` records each registration, replacement,
+//! revert, removal and clear from then on, with the entry's generation, a
+//! timestamp and the caller's location. Records of registrations keep the
+//! registered entry, so `replay_timeline(&records[..n])` can rebuild the state
+//! after the first `n` mutations in a fresh registry.
+
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::sync::{AtomicBool, Mutex};
+use crate::RegistryEntry;
+
+/// The kind of mutation a [`TimelineRecord`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineOp {
+    /// A value was registered for a type without one.
+    Register,
+    /// A value replaced the registered value of its type.
+    Replace,
+    /// `revert` restored a previous value.
+    Revert,
+    /// A value was removed.
+    Remove,
+    /// The registry was cleared.
+    Clear,
+}
+
+/// One mutation recorded by `start_timeline()`.
+#[derive(Clone)]
+pub struct TimelineRecord {
+    /// What happened
+    pub op: TimelineOp,
+    /// The type of the value; `None` for `Clear`
+    pub type_name: Option<&'static str>,
+    /// Generation of the entry that was stored or removed; `0` for `Clear`
+    pub generation: u64,
+    /// When the mutation happened
+    pub timestamp: SystemTime,
+    /// Source location of the mutating call, if known
+    pub location: Option<&'static Location<'static>>,
+    pub(crate) type_id: Option<TypeId>,
+    /// The stored entry, for `Register`, `Replace` and `Revert`
+    pub(crate) entry: Option<RegistryEntry>,
+}
+
+impl TimelineRecord {
+    /// Record a mutation of the entry of `type_id`; `entry` is the stored one.
+    pub(crate) fn new(
+        op: TimelineOp,
+        type_id: TypeId,
+        type_name: &'static str,
+        generation: u64,
+        location: Option<&'static Location<'static>>,
+        entry: Option<RegistryEntry>,
+    ) -> Self {
+        Self {
+            op,
+            type_name: Some(type_name),
+            generation,
+            timestamp: SystemTime::now(),
+            location,
+            type_id: Some(type_id),
+            entry,
+        }
+    }
+
+    /// Record a clear.
+    pub(crate) fn clear() -> Self {
+        Self {
+            op: TimelineOp::Clear,
+            type_name: None,
+            generation: 0,
+            timestamp: SystemTime::now(),
+            location: None,
+            type_id: None,
+            entry: None,
+        }
+    }
+
+    /// The registered value, for `Register`, `Replace` and `Revert` records of `T`.
+    ///
+    /// The record shares the value with the registry, so this is the value as
+    /// it was registered.
+    pub fn value<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let value: Arc<dyn Any + Send + Sync> = self.entry.as_ref()?.value().clone();
+        value.downcast::<T>().ok()
+    }
+}
+
+impl fmt::Debug for TimelineRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimelineRecord")
+            .field("op", &self.op)
+            .field("type_name", &self.type_name)
+            .field("generation", &self.generation)
+            .field("timestamp", &self.timestamp)
+            .field("location", &self.location)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for TimelineRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            TimelineOp::Register => "register",
+            TimelineOp::Replace => "replace",
+            TimelineOp::Revert => "revert",
+            TimelineOp::Remove => "remove",
+            TimelineOp::Clear => "clear",
+        };
+        write!(f, "{op}")?;
+        if let Some(type_name) = self.type_name {
+            write!(f, " {type_name} (generation {})", self.generation)?;
+        }
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The records of a registry's timeline, while it is recording.
+#[derive(Default)]
+pub(crate) struct Timeline {
+    recording: AtomicBool,
+    records: Mutex<Vec<TimelineRecord>>,
+}
+
+impl fmt::Debug for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let records = self.records.lock().unwrap_or_else(|p| p.into_inner());
+        f.debug_struct("Timeline")
+            .field("recording", &self.is_recording())
+            .field("records", &records.len())
+            .finish()
+    }
+}
+
+impl Timeline {
+    /// Whether mutations are being recorded.
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+    }
+
+    /// Start recording from scratch, returning the previous records to be
+    /// dropped without the lock held.
+    pub(crate) fn start(&self) -> Vec<TimelineRecord> {
+        let mut records = self.records.lock().unwrap_or_else(|p| p.into_inner());
+        self.recording.store(true, Ordering::Relaxed);
+        std::mem::take(&mut *records)
+    }
+
+    /// Stop recording and hand out the records.
+    pub(crate) fn stop(&self) -> Vec<TimelineRecord> {
+        let mut records = self.records.lock().unwrap_or_else(|p| p.into_inner());
+        self.recording.store(false, Ordering::Relaxed);
+        std::mem::take(&mut *records)
+    }
+
+    /// Append `record` if recording; built lazily since records clone entries.
+    pub(crate) fn record(&self, record: impl FnOnce() -> TimelineRecord) {
+        if !self.is_recording() {
+            return;
+        }
+        let record = record();
+        let mut records = self.records.lock().unwrap_or_else(|p| p.into_inner());
+        // Recording may have been stopped in the meantime
+        if self.is_recording() {
+            records.push(record);
+        }
+    }
+
+    /// The records so far.
+    pub(crate) fn records(&self) -> Vec<TimelineRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+
+    #[test]
+    fn test_records_mutations() {
+        define_registry!(reg);
+
+        reg::register(0u8); // before recording
+        reg::start_timeline();
+        reg::enable_history::<u8>(1);
+        reg::register(1u8);
+        reg::register(2u16);
+        reg::revert::<u8>().unwrap();
+        reg::remove::<u16>().unwrap();
+        let _ = reg::get::<u8>(); // lookups are not recorded
+        let records = reg::stop_timeline();
+        reg::register(3u8); // after recording
+
+        let ops: Vec<(TimelineOp, Option<&str>, u64)> = records
+            .iter()
+            .map(|r| (r.op, r.type_name, r.generation))
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                (TimelineOp::Replace, Some("u8"), 2),
+                (TimelineOp::Register, Some("u16"), 1),
+                (TimelineOp::Revert, Some("u8"), 3),
+                (TimelineOp::Remove, Some("u16"), 1),
+            ]
+        );
+        assert!(records[0].location.unwrap().file().ends_with("timeline.rs"));
+        assert_eq!(records[0].value::<u8>().as_deref(), Some(&1));
+        assert_eq!(records[2].value::<u8>().as_deref(), Some(&0));
+        assert!(records[3].value::<u16>().is_none());
+        assert!(reg::timeline().is_empty());
+    }
+
+    #[test]
+    fn test_replay_up_to_a_point() {
+        define_registry!(source);
+        define_registry!(replica);
+
+        source::start_timeline();
+        source::register(1u8);
+        source::register(String::from("first"));
+        source::register(2u8);
+        source::clear();
+        source::register(3u8);
+        let records = source::timeline();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[3].to_string(), "clear");
+
+        replica::replay_timeline(&records[..2]).unwrap();
+        assert_eq!(*replica::get::<u8>().unwrap(), 1);
+        assert_eq!(*replica::get::<String>().unwrap(), "first");
+
+        replica::replay_timeline(&records[2..]).unwrap();
+        assert_eq!(*replica::get::<u8>().unwrap(), 3);
+        assert!(!replica::contains::<String>().unwrap());
+    }
+
+    #[test]
+    fn test_replayed_clear_keeps_protected_entries() {
+        define_registry!(source);
+        define_registry!(replica);
+
+        source::register(1u8);
+        source::protect::<u8>().unwrap();
+        source::start_timeline();
+        source::register(String::from("dropped"));
+        source::clear();
+        let records = source::timeline();
+        assert!(source::contains::<u8>().unwrap());
+
+        replica::register(1u8);
+        replica::protect::<u8>().unwrap();
+        replica::replay_timeline(&records).unwrap();
+        assert_eq!(*replica::get::<u8>().unwrap(), 1);
+        assert!(!replica::contains::<String>().unwrap());
+    }
+}
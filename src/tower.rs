@@ -0,0 +1,209 @@
+//! tower middleware (feature `tower`).
+//!
+//! [`RegistryLayer`] clones the `Arc`s of configured types from a registry into
+//! each request's extensions, so downstream middlewares and handlers in any
+//! tower-based stack (axum, tonic, hyper) read registry singletons uniformly via
+//! `req.extensions().get::<Arc<T>>()`.
+//!
+//! Values are looked up per request, so replacements are picked up immediately.
+//! Types that are not registered are skipped.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use http::{Extensions, Request};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::RegistryApi;
+
+type Injector = Box<dyn Fn(&mut Extensions) + Send + Sync>;
+
+/// Layer injecting registry values of `R` into request extensions.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, RegistryLayer};
+///
+/// define_registry!(services);
+///
+/// struct Db;
+/// struct Mailer;
+///
+/// let layer = RegistryLayer::<services::Api>::new()
+///     .with::<Db>()
+///     .with::<Mailer>();
+/// // router.layer(layer) — handlers then read `Extension<Arc<Db>>`
+/// ```
+pub struct RegistryLayer<R> {
+    injectors: Arc<Vec<Injector>>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<R: RegistryApi + Default> RegistryLayer<R> {
+    /// Start with no configured types.
+    pub fn new() -> Self {
+        Self {
+            injectors: Arc::new(Vec::new()),
+            _registry: PhantomData,
+        }
+    }
+
+    /// Inject `Arc<T>` into every request's extensions.
+    pub fn with<T: Send + Sync + 'static>(mut self) -> Self {
+        let injectors = Arc::get_mut(&mut self.injectors)
+            .expect("RegistryLayer is configured before it is cloned");
+        injectors.push(Box::new(|extensions| {
+            if let Some(value) = R::default().try_get::<T>() {
+                extensions.insert(value);
+            }
+        }));
+        self
+    }
+}
+
+impl<R: RegistryApi + Default> Default for RegistryLayer<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> Clone for RegistryLayer<R> {
+    fn clone(&self) -> Self {
+        Self {
+            injectors: self.injectors.clone(),
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for RegistryLayer<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistryLayer")
+            .field("types", &self.injectors.len())
+            .finish()
+    }
+}
+
+impl<S, R> Layer<S> for RegistryLayer<R> {
+    type Service = RegistryService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RegistryService {
+            inner,
+            injectors: self.injectors.clone(),
+            _registry: PhantomData,
+        }
+    }
+}
+
+/// Service produced by [`RegistryLayer`].
+pub struct RegistryService<S, R> {
+    inner: S,
+    injectors: Arc<Vec<Injector>>,
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<S: Clone, R> Clone for RegistryService<S, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            injectors: self.injectors.clone(),
+            _registry: PhantomData,
+        }
+    }
+}
+
+impl<S: fmt::Debug, R> fmt::Debug for RegistryService<S, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistryService")
+            .field("inner", &self.inner)
+            .field("types", &self.injectors.len())
+            .finish()
+    }
+}
+
+impl<S, R, B> Service<Request<B>> for RegistryService<S, R>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        for inject in self.injectors.iter() {
+            inject(req.extensions_mut());
+        }
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    /// Inner service reporting which values reached the extensions.
+    struct Probe;
+
+    impl Service<Request<()>> for Probe {
+        type Response = (Option<Arc<String>>, Option<Arc<u8>>);
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            ready(Ok((
+                req.extensions().get::<Arc<String>>().cloned(),
+                req.extensions().get::<Arc<u8>>().cloned(),
+            )))
+        }
+    }
+
+    #[test]
+    fn test_injects_registered_values() {
+        define_registry!(reg);
+        reg::register("db".to_string());
+
+        let mut service = RegistryLayer::<reg::Api>::new()
+            .with::<String>()
+            .with::<u8>()
+            .layer(Probe);
+
+        let (string, byte) =
+            futures_lite::future::block_on(service.call(Request::new(()))).unwrap();
+        assert!(Arc::ptr_eq(
+            &string.unwrap(),
+            &reg::get::<String>().unwrap()
+        ));
+        // Unregistered types are skipped
+        assert!(byte.is_none());
+    }
+
+    #[test]
+    fn test_picks_up_replacements() {
+        define_registry!(reg);
+        let mut service = RegistryLayer::<reg::Api>::new().with::<u8>().layer(Probe);
+
+        reg::register(1u8);
+        let (_, first) = futures_lite::future::block_on(service.call(Request::new(()))).unwrap();
+        reg::register(2u8);
+        let (_, second) = futures_lite::future::block_on(service.call(Request::new(()))).unwrap();
+
+        assert_eq!((*first.unwrap(), *second.unwrap()), (1, 2));
+    }
+}
@@ -0,0 +1,176 @@
+//! Correlation context for registry events.
+//!
+//! `with_trace_context("request-42", || ...)` marks everything the closure does
+//! on the current thread; every event a registry emits in the meantime carries
+//! the context in its [`EventMeta`](crate::EventMeta), so registry activity can
+//! be matched to the request that triggered it. Async code wraps a future with
+//! `with_trace_context_async` instead: the context is installed on whichever
+//! thread polls it, so it follows the task across executor threads.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous context on drop, so a panic does not leak the scope.
+struct Restore(Option<Arc<str>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+fn enter(context: Arc<str>) -> Restore {
+    Restore(CURRENT.with(|current| current.borrow_mut().replace(context)))
+}
+
+/// Run `f` with `context` attached to every registry event it triggers.
+///
+/// Scopes nest: an inner context replaces the outer one until it ends.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{define_registry, with_trace_context};
+///
+/// define_registry!(services);
+///
+/// services::set_trace_callback(|event| println!("{event}"));
+/// services::register(1u32);
+///
+/// with_trace_context("request-42", || {
+///     // prints "get { type_name: u32, found: true } (context: request-42)"
+///     let _ = services::get::<u32>();
+/// });
+/// ```
+pub fn with_trace_context<R>(context: impl Into<Arc<str>>, f: impl FnOnce() -> R) -> R {
+    let _restore = enter(context.into());
+    f()
+}
+
+/// Attach `context` to every registry event emitted while `future` is polled.
+///
+/// Works with any executor.
+pub fn with_trace_context_async<F: Future>(
+    context: impl Into<Arc<str>>,
+    future: F,
+) -> WithTraceContext<F> {
+    WithTraceContext {
+        context: context.into(),
+        future: Box::pin(future),
+    }
+}
+
+/// The correlation context active on the current thread, if any.
+pub fn trace_context() -> Option<Arc<str>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Future returned by [`with_trace_context_async`].
+#[must_use = "futures do nothing unless polled"]
+pub struct WithTraceContext<F> {
+    context: Arc<str>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithTraceContext<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _restore = enter(self.context.clone());
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl<F> std::fmt::Debug for WithTraceContext<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WithTraceContext")
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::define_registry;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_scopes_nest_and_restore() {
+        assert_eq!(trace_context(), None);
+        with_trace_context("outer", || {
+            assert_eq!(trace_context().as_deref(), Some("outer"));
+            with_trace_context("inner", || {
+                assert_eq!(trace_context().as_deref(), Some("inner"));
+            });
+            assert_eq!(trace_context().as_deref(), Some("outer"));
+        });
+        assert_eq!(trace_context(), None);
+    }
+
+    #[test]
+    fn test_restored_after_panic() {
+        let result = std::panic::catch_unwind(|| with_trace_context("doomed", || panic!("boom")));
+        assert!(result.is_err());
+        assert_eq!(trace_context(), None);
+    }
+
+    #[test]
+    fn test_events_carry_context() {
+        define_registry!(reg);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        reg::set_trace_callback(move |e| {
+            sink.lock()
+                .unwrap()
+                .push((e.context().map(str::to_string), e.to_string()))
+        });
+
+        reg::register(1u8);
+        with_trace_context("request-42", || {
+            let _ = reg::get::<u8>();
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[1].0, None);
+        assert_eq!(
+            events[2],
+            (
+                Some("request-42".to_string()),
+                "get { type_name: u8, found: true } (context: request-42)".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_async_scope() {
+        define_registry!(reg);
+
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+        let sink = contexts.clone();
+        reg::set_trace_callback(move |e| {
+            sink.lock().unwrap().push(e.context().map(str::to_string))
+        });
+
+        futures_lite::future::block_on(with_trace_context_async("task-7", async {
+            futures_lite::future::yield_now().await;
+            let _ = reg::contains::<u8>();
+        }));
+        let _ = reg::contains::<u8>();
+
+        assert_eq!(
+            *contexts.lock().unwrap(),
+            [Some("task-7".to_string()), None]
+        );
+    }
+}
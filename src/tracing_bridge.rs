@@ -0,0 +1,146 @@
+//! `tracing` integration (feature `tracing`).
+//!
+//! Every event a registry emits is also recorded as a `tracing` event with
+//! target `singleton_registry`, so applications already using the tracing
+//! ecosystem get registry observability without writing a callback adapter.
+//! Events carry structured fields: `registry`, `op`, `type_name`, and where
+//! they apply `found`, `replaced`, `context` and `duration_us`. The trace level
+//! and event sampling filter them like any other sink.
+
+use crate::{EventLevel, RegistryEvent};
+
+/// Record `event` with the current `tracing` subscriber.
+pub(crate) fn emit(event: &RegistryEvent) {
+    macro_rules! record {
+        ($level:expr) => {
+            ::tracing::event!(
+                target: "singleton_registry",
+                $level,
+                registry = event.registry(),
+                op = event.op(),
+                type_name = event.type_name(),
+                found = found(event),
+                replaced = replaced(event),
+                context = event.context(),
+                duration_us = event.meta().duration.map(|d| d.as_micros() as u64),
+                "{}",
+                event
+            )
+        };
+    }
+
+    match event.level() {
+        EventLevel::Debug => record!(::tracing::Level::DEBUG),
+        EventLevel::Info | EventLevel::Notice => record!(::tracing::Level::INFO),
+        EventLevel::Warn => record!(::tracing::Level::WARN),
+    }
+}
+
+fn found(event: &RegistryEvent) -> Option<bool> {
+    match event {
+        RegistryEvent::Get { found, .. } | RegistryEvent::Contains { found, .. } => Some(*found),
+        _ => None,
+    }
+}
+
+fn replaced(event: &RegistryEvent) -> Option<bool> {
+    match event {
+        RegistryEvent::RegisterCompleted { replaced, .. } => Some(*replaced),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::define_registry;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Collects the fields of every event as `name=value` strings.
+    #[derive(Clone, Default)]
+    struct Collect(Arc<Mutex<Vec<(tracing::Level, String)>>>);
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() != "message" {
+                self.0.push_str(&format!("{}={:?} ", field.name(), value));
+            }
+        }
+    }
+
+    impl Subscriber for Collect {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            assert_eq!(event.metadata().target(), "singleton_registry");
+            let mut fields = Fields(String::new());
+            event.record(&mut fields);
+            self.0
+                .lock()
+                .unwrap()
+                .push((*event.metadata().level(), fields.0.trim_end().to_string()));
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_events_reach_tracing() {
+        define_registry!(reg);
+
+        let collect = Collect::default();
+        tracing::subscriber::with_default(collect.clone(), || {
+            reg::register(1u8);
+            reg::register(2u8);
+            let _ = reg::contains::<u8>();
+            let _ = reg::get::<u16>();
+        });
+
+        let events = collect.0.lock().unwrap();
+        let strip = |s: &str| s.split(" duration_us").next().unwrap().to_string();
+        let events: Vec<_> = events.iter().map(|(l, f)| (*l, strip(f))).collect();
+        assert_eq!(
+            events,
+            [
+                (
+                    tracing::Level::INFO,
+                    r#"registry="reg" op="register" type_name="u8""#.to_string()
+                ),
+                (
+                    tracing::Level::INFO,
+                    r#"registry="reg" op="register_completed" type_name="u8" replaced=false"#
+                        .to_string()
+                ),
+                (
+                    tracing::Level::INFO,
+                    r#"registry="reg" op="register" type_name="u8""#.to_string()
+                ),
+                (
+                    tracing::Level::INFO,
+                    r#"registry="reg" op="register_completed" type_name="u8" replaced=true"#
+                        .to_string()
+                ),
+                (
+                    tracing::Level::DEBUG,
+                    r#"registry="reg" op="contains" type_name="u8" found=true"#.to_string()
+                ),
+                (
+                    tracing::Level::WARN,
+                    r#"registry="reg" op="get" type_name="u16" found=false"#.to_string()
+                ),
+            ]
+        );
+    }
+}
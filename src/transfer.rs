@@ -0,0 +1,250 @@
+//! Transfers of entries between registries.
+//!
+//! Bootstrap code often builds values in a temporary staging registry before
+//! promoting them into the application registry. [`copy_type`] and
+//! [`move_type`] hand over a single type's entry, keeping the same `Arc` and
+//! the entry's metadata, so holders of the staged value and readers of the
+//! promoted one share it. `merge_from` imports every entry of a registry,
+//! composing per-crate registries into one application registry at startup.
+
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::panic::Location;
+
+use crate::registry_trait::{lock_storage, remove_entry, store_entry};
+use crate::{RegistryApi, RegistryEntry, RegistryError};
+
+/// The entry of `T` in the registry `R`, or `TypeNotFound` naming `location`.
+fn entry_of<T: 'static, R: RegistryApi>(
+    location: &'static Location<'static>,
+) -> Result<RegistryEntry, RegistryError> {
+    lock_storage::<R>()
+        .unwrap_or_else(|p| p.into_inner())
+        .get(&TypeId::of::<T>())
+        .cloned()
+        .ok_or(RegistryError::TypeNotFound {
+            type_name: std::any::type_name::<T>(),
+            location: Some(location),
+            suggestions: Vec::new(),
+        })
+}
+
+/// Register the value of `T` in `from` in `to` as well.
+///
+/// Both registries then hold the same `Arc`; the entry keeps its metadata,
+/// finality and original registration location. Registering a new value of
+/// `T` in either registry afterwards does not affect the other.
+///
+/// # Example
+///
+/// ```rust
+/// use singleton_registry::{copy_type, define_registry};
+/// use std::sync::Arc;
+///
+/// define_registry!(staging);
+/// define_registry!(app);
+///
+/// staging::register(String::from("postgres://localhost"));
+/// copy_type::<String>(&staging::Api, &app::Api).unwrap();
+///
+/// assert!(Arc::ptr_eq(
+///     &staging::get::<String>().unwrap(),
+///     &app::get::<String>().unwrap()
+/// ));
+/// ```
+///
+/// # Errors
+///
+/// - `RegistryError::TypeNotFound` if `T` is not registered in `from`
+/// - `RegistryError::FinalType` if the value of `T` in `to` is final
+#[track_caller]
+pub fn copy_type<T: Send + Sync + 'static>(
+    from: &impl RegistryApi,
+    to: &impl RegistryApi,
+) -> Result<(), RegistryError> {
+    transfer::<T, _, _>(from, to, false, Location::caller())
+}
+
+/// Move the value of `T` from `from` to `to`.
+///
+/// Like [`copy_type`], then removes `T` from `from`. Nothing changes if the
+/// move fails.
+///
+/// # Errors
+///
+/// - `RegistryError::TypeNotFound` if `T` is not registered in `from`
+/// - `RegistryError::FinalType` if the value of `T` in either registry is final
+#[track_caller]
+pub fn move_type<T: Send + Sync + 'static>(
+    from: &impl RegistryApi,
+    to: &impl RegistryApi,
+) -> Result<(), RegistryError> {
+    transfer::<T, _, _>(from, to, true, Location::caller())
+}
+
+fn transfer<T: 'static, F: RegistryApi, D: RegistryApi>(
+    from: &F,
+    to: &D,
+    remove: bool,
+    location: &'static Location<'static>,
+) -> Result<(), RegistryError> {
+    let type_name = std::any::type_name::<T>();
+    let entry = entry_of::<T, F>(location)?;
+    if remove && entry.is_final() {
+        return Err(RegistryError::FinalType { type_name });
+    }
+    if remove && entry.is_protected() {
+        return Err(RegistryError::ProtectedType { type_name });
+    }
+    store_entry(to, TypeId::of::<T>(), entry.restamped(), false)?;
+    if remove {
+        remove_entry(from, TypeId::of::<T>(), type_name, Some(location))?;
+    }
+    Ok(())
+}
+
+/// What `merge_from` does with types registered in both registries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the value already registered.
+    #[default]
+    Skip,
+    /// Replace it with the other registry's value, unless it is final.
+    Overwrite,
+    /// Fail with `RegistryError::MergeConflict` and import nothing.
+    Error,
+}
+
+/// Store the entries of the registry `O` in `api` under `policy`.
+///
+/// Returns the number of entries stored.
+pub(crate) fn merge<R: RegistryApi + ?Sized, O: RegistryApi>(
+    api: &R,
+    _other: &O,
+    policy: ConflictPolicy,
+) -> Result<usize, RegistryError> {
+    let mut entries: Vec<(TypeId, RegistryEntry)> = lock_storage::<O>()
+        .unwrap_or_else(|p| p.into_inner())
+        .iter()
+        .map(|(type_id, entry)| (*type_id, entry.clone()))
+        .collect();
+    entries.sort_by_key(|(_, entry)| entry.type_name());
+
+    let existing: HashSet<TypeId> = lock_storage::<R>()
+        .unwrap_or_else(|p| p.into_inner())
+        .keys()
+        .copied()
+        .collect();
+    match policy {
+        ConflictPolicy::Skip => entries.retain(|(type_id, _)| !existing.contains(type_id)),
+        ConflictPolicy::Overwrite => {}
+        ConflictPolicy::Error => {
+            let mut conflicts: Vec<&'static str> = entries
+                .iter()
+                .filter(|(type_id, _)| existing.contains(type_id))
+                .map(|(_, entry)| entry.type_name())
+                .collect();
+            if !conflicts.is_empty() {
+                conflicts.sort_unstable();
+                return Err(RegistryError::MergeConflict { conflicts });
+            }
+        }
+    }
+
+    let mut merged = 0;
+    for (type_id, entry) in entries {
+        // A final value rejects the entry; reported via `RegisterRejected`
+        if store_entry(api, type_id, entry.restamped(), false).is_ok() {
+            merged += 1;
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use super::*;
+    use crate::{define_registry, Metadata};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_copy_type() {
+        define_registry!(staging);
+        define_registry!(app);
+
+        staging::register_with_meta(5u8, Metadata::new("retries"));
+        copy_type::<u8>(&staging::Api, &app::Api).unwrap();
+
+        assert!(Arc::ptr_eq(
+            &staging::get::<u8>().unwrap(),
+            &app::get::<u8>().unwrap()
+        ));
+        assert_eq!(app::metadata::<u8>().unwrap().description, "retries");
+
+        let err = copy_type::<u16>(&staging::Api, &app::Api).unwrap_err();
+        assert!(matches!(
+            err,
+            RegistryError::TypeNotFound {
+                type_name: "u16",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_move_type() {
+        define_registry!(staging);
+        define_registry!(app);
+
+        staging::register(5u8);
+        let staged = staging::get::<u8>().unwrap();
+        move_type::<u8>(&staging::Api, &app::Api).unwrap();
+        assert!(Arc::ptr_eq(&staged, &app::get::<u8>().unwrap()));
+        assert!(!staging::contains::<u8>().unwrap());
+
+        // A final target leaves the source untouched
+        staging::register(6u16);
+        app::register_final(7u16).unwrap();
+        assert_eq!(
+            move_type::<u16>(&staging::Api, &app::Api).unwrap_err(),
+            RegistryError::FinalType { type_name: "u16" }
+        );
+        assert_eq!(*staging::get::<u16>().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_merge_from() {
+        define_registry!(plugin);
+        define_registry!(app);
+
+        plugin::register(1u8);
+        plugin::register(2u16);
+        plugin::register(3u32);
+        app::register(10u16);
+        app::register_final(20u32).unwrap();
+
+        assert_eq!(
+            app::merge_from(&plugin::Api, ConflictPolicy::Error).unwrap_err(),
+            RegistryError::MergeConflict {
+                conflicts: vec!["u16", "u32"]
+            }
+        );
+        assert!(!app::contains::<u8>().unwrap());
+
+        assert_eq!(app::merge_from(&plugin::Api, ConflictPolicy::Skip), Ok(1));
+        assert!(Arc::ptr_eq(
+            &app::get::<u8>().unwrap(),
+            &plugin::get::<u8>().unwrap()
+        ));
+        assert_eq!(*app::get::<u16>().unwrap(), 10);
+
+        // Final values are kept even when overwriting
+        assert_eq!(
+            app::merge_from(&plugin::Api, ConflictPolicy::Overwrite),
+            Ok(2)
+        );
+        assert_eq!(*app::get::<u16>().unwrap(), 2);
+        assert_eq!(*app::get::<u32>().unwrap(), 20);
+    }
+}
@@ -0,0 +1,280 @@
+//! Waiting for a type to be registered.
+//!
+//! Components started before their dependencies can `await` a registration
+//! instead of polling `contains` in a loop. The future is executor-agnostic:
+//! it parks its `Waker` in the registry's state, and every `register` of the
+//! awaited type wakes it. Non-async code blocks on a `Condvar` instead, which
+//! every registration notifies, or retries lookups with `get_eventually`.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::registry_trait::lookup;
+use crate::sync::{Condvar, Mutex};
+use crate::{RegistryApi, RegistryError};
+
+/// Tasks and threads waiting for a type to be registered.
+#[derive(Debug, Default)]
+pub(crate) struct RegistrationWaiters {
+    /// Wakers of waiting tasks, keyed by `TypeId`.
+    wakers: Mutex<HashMap<TypeId, Vec<Waker>>>,
+    /// Number of registrations so far; guards `registered`.
+    epoch: Mutex<u64>,
+    /// Notified on every registration.
+    registered: Condvar,
+}
+
+impl RegistrationWaiters {
+    /// Wake every task waiting for `type_id`.
+    ///
+    /// Must be called after the value has been inserted and the storage lock released.
+    pub(crate) fn notify(&self, type_id: TypeId) {
+        let wakers = self
+            .wakers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(&type_id);
+
+        for waker in wakers.into_iter().flatten() {
+            waker.wake();
+        }
+
+        *self.epoch.lock().unwrap_or_else(|p| p.into_inner()) += 1;
+        self.registered.notify_all();
+    }
+}
+
+/// Block the current thread until `T` is registered in `R` or `timeout` elapses.
+pub(crate) fn wait_blocking<R: RegistryApi + ?Sized, T: Send + Sync + 'static>(
+    timeout: Duration,
+) -> Result<Arc<T>, RegistryError> {
+    let waiters = &R::state().registration_waiters;
+    // A timeout too large for an `Instant` means waiting with no deadline
+    let deadline = Instant::now().checked_add(timeout);
+
+    // Hold the epoch lock across the lookup so a registration landing in between
+    // cannot notify before this thread starts waiting.
+    let mut epoch = waiters.epoch.lock().unwrap_or_else(|p| p.into_inner());
+    loop {
+        if let Some(value) = lookup::<R, T>() {
+            return Ok(value);
+        }
+
+        let Some(deadline) = deadline else {
+            epoch = waiters
+                .registered
+                .wait(epoch)
+                .unwrap_or_else(|p| p.into_inner());
+            continue;
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(RegistryError::Timeout {
+                type_name: std::any::type_name::<T>(),
+                timeout,
+            });
+        }
+
+        epoch = waiters
+            .registered
+            .wait_timeout(epoch, remaining)
+            .unwrap_or_else(|p| p.into_inner())
+            .0;
+    }
+}
+
+/// Look up `T` in `R`, retrying up to `retries` times while it is not registered.
+///
+/// Sleeps `backoff` before the first retry and doubles the delay before each
+/// further one. Only `TypeNotFound` is retried; other errors return at once.
+pub(crate) fn get_with_retries<R: RegistryApi + ?Sized, T: Send + Sync + 'static>(
+    api: &R,
+    retries: u32,
+    backoff: Duration,
+) -> Result<Arc<T>, RegistryError> {
+    let mut delay = backoff;
+    let mut attempt = 0;
+    loop {
+        match api.get::<T>() {
+            Err(RegistryError::TypeNotFound { .. }) if attempt < retries => {
+                std::thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Future returned by `wait_for`, resolving to the value once `T` is registered.
+///
+/// Resolves immediately if `T` is already registered. Waiting does not emit
+/// trace events.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitFor<R: ?Sized, T> {
+    _marker: PhantomData<fn(&R) -> T>,
+}
+
+impl<R: RegistryApi + ?Sized, T: Send + Sync + 'static> WaitFor<R, T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R: RegistryApi + ?Sized, T: Send + Sync + 'static> Future for WaitFor<R, T> {
+    type Output = Arc<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Hold the waiters lock across the lookup so a registration landing in between
+        // cannot notify before our waker is parked.
+        let mut wakers = R::state()
+            .registration_waiters
+            .wakers
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+
+        if let Some(value) = lookup::<R, T>() {
+            return Poll::Ready(value);
+        }
+
+        let waiting = wakers.entry(TypeId::of::<T>()).or_default();
+        if !waiting.iter().any(|w| w.will_wake(cx.waker())) {
+            waiting.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_resolves_immediately_when_registered() {
+        define_registry!(reg);
+
+        reg::register(5u8);
+        let value: Arc<u8> = futures_lite::future::block_on(reg::wait_for());
+        assert_eq!(*value, 5);
+    }
+
+    #[test]
+    fn test_resolves_after_registration() {
+        define_registry!(reg);
+
+        let registrar = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            reg::register("ready".to_string());
+        });
+
+        let value: Arc<String> = futures_lite::future::block_on(reg::wait_for());
+        assert_eq!(&*value, "ready");
+        registrar.join().unwrap();
+    }
+
+    #[test]
+    fn test_pending_until_registered() {
+        define_registry!(reg);
+
+        let mut future = Box::pin(reg::wait_for::<u32>());
+        assert!(
+            futures_lite::future::block_on(futures_lite::future::poll_once(&mut future)).is_none()
+        );
+
+        reg::register(9u32);
+        let value = futures_lite::future::block_on(future);
+        assert_eq!(*value, 9);
+    }
+
+    #[test]
+    fn test_blocking_wait_after_registration() {
+        define_registry!(reg);
+
+        let registrar = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            reg::register(3i16);
+        });
+
+        let value = reg::wait_for_blocking::<i16>(Duration::from_secs(5)).unwrap();
+        assert_eq!(*value, 3);
+        registrar.join().unwrap();
+    }
+
+    #[test]
+    fn test_blocking_wait_times_out() {
+        define_registry!(reg);
+
+        let result = reg::wait_for_blocking::<i16>(Duration::from_millis(10));
+        assert_eq!(
+            result.unwrap_err(),
+            RegistryError::Timeout {
+                type_name: "i16",
+                timeout: Duration::from_millis(10),
+            }
+        );
+    }
+
+    #[test]
+    fn test_blocking_wait_without_deadline() {
+        define_registry!(reg);
+
+        reg::register(5i16);
+        assert_eq!(*reg::wait_for_blocking::<i16>(Duration::MAX).unwrap(), 5);
+
+        let registrar = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            reg::register(6u16);
+        });
+        assert_eq!(*reg::wait_for_blocking::<u16>(Duration::MAX).unwrap(), 6);
+        registrar.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_eventually_after_registration() {
+        define_registry!(reg);
+
+        let registrar = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(30));
+            reg::register(4u64);
+        });
+
+        let value = reg::get_eventually::<u64>(10, Duration::from_millis(5)).unwrap();
+        assert_eq!(*value, 4);
+        registrar.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_eventually_gives_up() {
+        define_registry!(reg);
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        reg::set_trace_callback(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+
+        let result = reg::get_eventually::<u64>(3, Duration::from_millis(1));
+        assert!(matches!(
+            result.unwrap_err(),
+            RegistryError::TypeNotFound {
+                type_name: "u64",
+                ..
+            }
+        ));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+}
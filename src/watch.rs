@@ -0,0 +1,127 @@
+//! Watch-channel subscriptions to replacements of a type (feature `tokio`).
+//!
+//! `subscribe::<T>()` hands out a `tokio::sync::watch::Receiver<Arc<T>>` that
+//! holds the current value and observes every later registration of `T`, so
+//! hot-reload consumers can react to replacements instead of re-calling `get()`.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::sync::{watch, Mutex};
+
+/// Sends a type-erased value on a type-erased `watch::Sender<Arc<T>>`.
+///
+/// Returns `false` if the channel has no receivers left.
+type PublishFn = fn(&(dyn Any + Send + Sync), &Arc<dyn Any + Send + Sync>) -> bool;
+
+fn publish_as<T: Send + Sync + 'static>(
+    sender: &(dyn Any + Send + Sync),
+    value: &Arc<dyn Any + Send + Sync>,
+) -> bool {
+    let Some(sender) = sender.downcast_ref::<watch::Sender<Arc<T>>>() else {
+        return true;
+    };
+    if sender.receiver_count() == 0 {
+        return false;
+    }
+    if let Ok(value) = value.clone().downcast::<T>() {
+        sender.send_replace(value);
+    }
+    true
+}
+
+/// A `watch::Sender<Arc<T>>`, type-erased, with the function publishing on it.
+type ErasedSender = (Box<dyn Any + Send + Sync>, PublishFn);
+
+/// Watch senders per registered type.
+#[derive(Debug, Default)]
+pub(crate) struct Watchers {
+    senders: Mutex<HashMap<TypeId, ErasedSender>>,
+}
+
+impl Watchers {
+    /// Subscribe to `T`, seeding a new channel with `current` if none exists yet.
+    pub(crate) fn subscribe<T: Send + Sync + 'static>(
+        &self,
+        current: Arc<T>,
+    ) -> watch::Receiver<Arc<T>> {
+        let mut senders = self.senders.lock().unwrap_or_else(|p| p.into_inner());
+
+        let (sender, _) = senders
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| (Box::new(watch::Sender::new(current)), publish_as::<T>));
+
+        sender
+            .downcast_ref::<watch::Sender<Arc<T>>>()
+            .expect("watch sender stored under the TypeId of its value type")
+            .subscribe()
+    }
+
+    /// Publish a newly registered value of type `type_id` to its subscribers, if any.
+    ///
+    /// Channels whose receivers have all been dropped are discarded.
+    pub(crate) fn publish(&self, type_id: TypeId, value: &Arc<dyn Any + Send + Sync>) {
+        let mut senders = self.senders.lock().unwrap_or_else(|p| p.into_inner());
+
+        let Some((sender, publish)) = senders.get(&type_id) else {
+            return;
+        };
+        if !publish(&**sender, value) {
+            senders.remove(&type_id);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use crate::{define_registry, RegistryError};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_subscribe_requires_registration() {
+        define_registry!(reg);
+
+        assert!(matches!(
+            reg::subscribe::<u8>().unwrap_err(),
+            RegistryError::TypeNotFound {
+                type_name: "u8",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_sees_replacements() {
+        define_registry!(reg);
+
+        reg::register("v1".to_string());
+        let mut rx = reg::subscribe::<String>().unwrap();
+        assert_eq!(&**rx.borrow_and_update(), "v1");
+
+        reg::register("v2".to_string());
+        rx.changed().await.unwrap();
+        assert_eq!(&**rx.borrow_and_update(), "v2");
+
+        // Late subscribers start from the current value
+        let late = reg::subscribe::<String>().unwrap();
+        assert_eq!(&**late.borrow(), "v2");
+    }
+
+    #[test]
+    fn test_dropped_receivers_release_channel() {
+        define_registry!(reg);
+
+        reg::register(1u32);
+        let rx = reg::subscribe::<u32>().unwrap();
+        drop(rx);
+
+        // Publishing with no receivers discards the channel; a new subscriber starts fresh
+        reg::register(2u32);
+        let rx = reg::subscribe::<u32>().unwrap();
+        assert_eq!(**rx.borrow(), 2);
+        let held: Arc<u32> = reg::get().unwrap();
+        assert!(Arc::ptr_eq(&held, &rx.borrow()));
+    }
+}
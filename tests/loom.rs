@@ -0,0 +1,70 @@
+//! Model-checked concurrency tests using loom.
+//!
+//! Loom explores every possible interleaving of the threads in each model, so these
+//! tests verify the registry's guarantees exhaustively instead of relying on timing.
+//! They only build when the `loom` cfg is set:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! Registry statics are backed by loom lazy statics and reset between executions.
+
+#![cfg(loom)]
+
+use loom::thread;
+use singleton_registry::define_registry;
+use std::sync::Arc;
+
+define_registry!(replace_reg);
+define_registry!(distinct_reg);
+define_registry!(callback_reg);
+
+#[test]
+fn test_replace_while_reading() {
+    loom::model(|| {
+        replace_reg::register(1u32);
+
+        let reader = thread::spawn(|| {
+            // A concurrent replacement is never observed as a missing value
+            let value: Arc<u32> = replace_reg::get().unwrap();
+            assert!(*value == 1 || *value == 2);
+            value
+        });
+
+        replace_reg::register(2u32);
+
+        // A handle obtained before the replacement stays valid afterwards
+        let seen = reader.join().unwrap();
+        assert!(*seen == 1 || *seen == 2);
+        assert_eq!(*replace_reg::get::<u32>().unwrap(), 2);
+    });
+}
+
+#[test]
+fn test_concurrent_registration_of_distinct_types() {
+    loom::model(|| {
+        let writer = thread::spawn(|| distinct_reg::register(1u8));
+        distinct_reg::register(2u16);
+        writer.join().unwrap();
+
+        assert_eq!(*distinct_reg::get::<u8>().unwrap(), 1);
+        assert_eq!(*distinct_reg::get::<u16>().unwrap(), 2);
+    });
+}
+
+#[test]
+fn test_callback_runs_without_registry_locks() {
+    loom::model(|| {
+        // The callback touches the storage lock; a lock held during emission would deadlock
+        callback_reg::set_trace_callback(|_| {
+            let _ = callback_reg::strong_count::<u8>();
+        });
+
+        let other = thread::spawn(|| callback_reg::register(1u8));
+        let _ = callback_reg::contains::<u8>();
+        other.join().unwrap();
+
+        assert!(callback_reg::contains::<u8>().unwrap());
+    });
+}
@@ -246,3 +246,210 @@ fn test_callback_can_use_different_registry() {
 
     main_registry::clear_trace_callback();
 }
+
+#[test]
+fn test_trace_replacement_is_flagged() {
+    define_registry!(traced_replace);
+
+    let events = traced_replace::record_events();
+    traced_replace::register(1u8);
+    traced_replace::register(2u8);
+    traced_replace::remove::<u8>().unwrap();
+    traced_replace::register(3u8);
+    traced_replace::register(1u16);
+
+    // Only a registration over an existing value of the same type is a replacement
+    let replaced: Vec<bool> = events
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            singleton_registry::RegistryEvent::RegisterCompleted { replaced, .. } => {
+                Some(*replaced)
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(replaced, [false, true, false, false]);
+
+    traced_replace::clear_trace_callback();
+}
+
+#[test]
+fn test_events_name_their_registry() {
+    define_registry!(traced_named_a);
+    define_registry!(traced_named_b);
+
+    assert_eq!(traced_named_a::name(), "traced_named_a");
+
+    let events_a = traced_named_a::record_events();
+    let events_b = traced_named_b::record_events();
+
+    traced_named_a::register(1u8);
+    let _ = traced_named_a::get::<u16>(); // a miss is named too
+    let _ = traced_named_b::contains::<u8>();
+
+    // Forwarded events keep the name of the registry that emitted them
+    traced_named_a::forward_events_to::<traced_named_b::Api>();
+    traced_named_a::register(2u8);
+    traced_named_a::stop_forwarding_events();
+
+    let names = |events: &singleton_registry::EventRecorder| -> Vec<&'static str> {
+        events.events().iter().map(|e| e.registry()).collect()
+    };
+    assert_eq!(
+        events_a.ops(),
+        [
+            "register",
+            "register_completed",
+            "get",
+            "register",
+            "register_completed"
+        ]
+    );
+    assert!(names(&events_a)
+        .iter()
+        .all(|name| *name == "traced_named_a"));
+    assert_eq!(
+        names(&events_b),
+        ["traced_named_b", "traced_named_a", "traced_named_a"]
+    );
+
+    traced_named_a::clear_trace_callback();
+    traced_named_b::clear_trace_callback();
+}
+
+#[test]
+fn test_events_record_thread_when_enabled() {
+    define_registry!(traced_threads);
+
+    let events = traced_threads::record_events();
+
+    // Off by default
+    traced_threads::register(1u8);
+    assert!(events.events().iter().all(|e| e.meta().thread_id.is_none()));
+
+    traced_threads::set_event_threads(true);
+    assert!(traced_threads::event_threads());
+    events.clear();
+    let worker = std::thread::Builder::new()
+        .name("late-registrar".into())
+        .spawn(|| traced_threads::register(2u8))
+        .unwrap();
+    let worker_id = worker.thread().id();
+    worker.join().unwrap();
+
+    let late = &events.events()[0];
+    assert_eq!(late.meta().thread_id, Some(worker_id));
+    assert_eq!(late.meta().thread_name.as_deref(), Some("late-registrar"));
+    assert_eq!(
+        late.to_string(),
+        "register { type_name: u8 } (thread: late-registrar)"
+    );
+
+    // An unnamed thread is identified by its id alone
+    events.clear();
+    let unnamed = std::thread::spawn(|| traced_threads::register(3u8));
+    let unnamed_id = unnamed.thread().id();
+    unnamed.join().unwrap();
+    let event = &events.events()[0];
+    assert_eq!(event.meta().thread_id, Some(unnamed_id));
+    assert_eq!(event.meta().thread_name, None);
+    assert_eq!(
+        event.to_string(),
+        format!("register {{ type_name: u8 }} (thread: {unnamed_id:?})")
+    );
+
+    traced_threads::set_event_threads(false);
+    events.clear();
+    traced_threads::register(4u8);
+    assert!(events.events().iter().all(|e| e.meta().thread_id.is_none()));
+
+    traced_threads::clear_trace_callback();
+}
+
+#[test]
+fn test_get_and_register_events_are_timed() {
+    use singleton_registry::RegistryApi;
+    use std::time::Duration;
+
+    define_registry!(traced_timed);
+
+    let events = traced_timed::record_events();
+    traced_timed::register(1u8);
+    let _ = traced_timed::get::<u8>();
+    let _ = traced_timed::get::<u16>();
+    let _ = traced_timed::contains::<u8>();
+
+    let timed: Vec<(&str, bool)> = events
+        .events()
+        .iter()
+        .map(|e| (e.op(), e.meta().duration.is_some()))
+        .collect();
+    assert_eq!(
+        timed,
+        [
+            ("register", false),
+            ("register_completed", true),
+            ("get", true),
+            ("get", true),
+            ("contains", false),
+        ]
+    );
+
+    // The duration includes waiting for the storage lock
+    events.clear();
+    let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+    let holder = std::thread::spawn(move || {
+        let _guard = traced_timed::Api::storage().lock().unwrap();
+        locked_tx.send(()).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+    });
+    locked_rx.recv().unwrap();
+    let _ = traced_timed::get::<u8>();
+    holder.join().unwrap();
+
+    let waited = events.events()[0].meta().duration.unwrap();
+    assert!(waited >= Duration::from_millis(40), "{waited:?}");
+
+    traced_timed::clear_trace_callback();
+}
+
+#[test]
+fn test_replacement_reports_call_site() {
+    define_registry!(traced_sites);
+
+    let events = traced_sites::record_events();
+
+    assert_eq!(traced_sites::registered_at::<u8>(), None);
+    let first_line = line!() + 1;
+    traced_sites::register(1u8);
+    let second_line = line!() + 1;
+    traced_sites::try_register(2u8).unwrap();
+
+    // Every register event carries the caller's location, through the macro wrappers
+    let lines: Vec<u32> = events
+        .events()
+        .iter()
+        .map(|e| e.meta().location.unwrap().line())
+        .collect();
+    assert_eq!(lines, [first_line, first_line, second_line, second_line]);
+
+    let location = traced_sites::registered_at::<u8>().unwrap();
+    assert_eq!((location.file(), location.line()), (file!(), second_line));
+
+    // Only a replacement shows the location in its `Display`
+    let shown: Vec<String> = events.events_for_type::<u8>()[1..]
+        .iter()
+        .step_by(2)
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        shown,
+        [
+            "register_completed { type_name: u8 }".to_string(),
+            format!("register_completed {{ type_name: u8, replaced: true }} (at {location})"),
+        ]
+    );
+
+    traced_sites::clear_trace_callback();
+}
@@ -8,10 +8,16 @@
 //! Running them in parallel would cause interference and non-deterministic failures.
 
 use serial_test::serial;
-use singleton_registry::{RegistryApi, RegistryEvent};
-use std::any::{Any, TypeId};
+use singleton_registry::{RegistryApi, RegistryEntry, RegistryEvent, RegistryState};
+use std::any::TypeId;
 use std::collections::HashMap;
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::{Arc, Mutex};
+
+// Manual implementations use the same lazy static type as the registry
+#[cfg(feature = "once_cell")]
+use once_cell::sync::Lazy as LazyLock;
+#[cfg(not(feature = "once_cell"))]
+use std::sync::LazyLock;
 
 /// Type alias for the trace callback (same as in registry_trait.rs)
 type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&RegistryEvent) + Send + Sync>>>>;
@@ -21,23 +27,30 @@ type TraceCallback = LazyLock<Mutex<Option<Arc<dyn Fn(&RegistryEvent) + Send + S
 // ============================================================================
 
 /// Define the static storage for our registry
-static MY_STORAGE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
+static MY_STORAGE: LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// Define the static trace callback storage
 static MY_TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
 
+/// Define the static auxiliary state
+static MY_STATE: LazyLock<RegistryState> = LazyLock::new(RegistryState::new);
+
 /// Our custom registry API implementation
 struct MyRegistry;
 
 impl RegistryApi for MyRegistry {
-    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> {
+    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> {
         &MY_STORAGE
     }
 
     fn trace() -> &'static TraceCallback {
         &MY_TRACE
     }
+
+    fn state() -> &'static RegistryState {
+        &MY_STATE
+    }
 }
 
 /// Constant instance of our registry
@@ -205,21 +218,27 @@ fn test_trait_object() {
 // ============================================================================
 
 /// Second registry for isolation testing
-static ANOTHER_STORAGE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> =
+static ANOTHER_STORAGE: LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
 static ANOTHER_TRACE: TraceCallback = LazyLock::new(|| Mutex::new(None));
 
+static ANOTHER_STATE: LazyLock<RegistryState> = LazyLock::new(RegistryState::new);
+
 struct AnotherRegistry;
 
 impl RegistryApi for AnotherRegistry {
-    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>> {
+    fn storage() -> &'static LazyLock<Mutex<HashMap<TypeId, RegistryEntry>>> {
         &ANOTHER_STORAGE
     }
 
     fn trace() -> &'static TraceCallback {
         &ANOTHER_TRACE
     }
+
+    fn state() -> &'static RegistryState {
+        &ANOTHER_STATE
+    }
 }
 
 const ANOTHER: AnotherRegistry = AnotherRegistry;